@@ -0,0 +1,85 @@
+//! Packs an asset folder into a single archive that `Vfs::mount_archive` can mount.
+//!
+//! Usage: `panko-pack [--store] <asset-dir> <archive>`
+
+use panko::archive::ArchiveWriter;
+use panko::{Error, Result};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: panko-pack [--store] <asset-dir> <archive>
+
+Packs every file under <asset-dir> into <archive>, keyed by its path relative to <asset-dir>.
+Files are deflate-compressed unless --store is given.";
+
+fn main() -> ExitCode {
+    let mut compress = true;
+    let mut paths = Vec::new();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--store" => compress = false,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    let [root, archive] = paths.as_slice() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+
+    match pack(root, archive, compress) {
+        Ok(count) => {
+            println!("Packed {} files into {}.", count, archive.display());
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Returns the number of files packed.
+fn pack(root: &Path, archive: &Path, compress: bool) -> Result<usize> {
+    let mut files = Vec::new();
+    collect_files(root, &mut files)?;
+    // an archive written inside the folder would otherwise pack the previous version of itself
+    if let Ok(archive) = archive.canonicalize() {
+        files.retain(|file| file.canonicalize().map_or(true, |file| file != archive));
+    }
+    // stable output for the same input
+    files.sort();
+
+    let mut writer = ArchiveWriter::new();
+    for file in files.iter() {
+        let relative = file.strip_prefix(root).unwrap();
+        let path = relative
+            .to_str()
+            .ok_or_else(|| Error::Other(format!("Path {} is not UTF-8.", relative.display())))?;
+        let bytes = std::fs::read(file).map_err(|e| io_error(file, e))?;
+        writer.add(path, &bytes, compress)?;
+    }
+
+    std::fs::write(archive, writer.to_bytes()).map_err(|e| io_error(archive, e))?;
+    Ok(files.len())
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result {
+    let entries = std::fs::read_dir(dir).map_err(|e| io_error(dir, e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| io_error(dir, e))?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> Error {
+    Error::Io(format!("{}: {}", path.display(), error))
+}
@@ -0,0 +1,68 @@
+use panko::archive::Archive;
+use panko::vfs::Mount;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// A fresh temporary directory holding `files`, at paths relative to it.
+fn directory(test: &str, files: &[(&str, &[u8])]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("panko-pack-{}", test));
+    let _ = std::fs::remove_dir_all(&dir);
+    for (path, bytes) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, bytes).unwrap();
+    }
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn pack(args: &[&Path]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_panko-pack"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn sorted_paths(archive: &Archive) -> Vec<&str> {
+    let mut paths = archive.paths().collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn packs_every_file_under_the_folder() {
+    let dir = directory(
+        "every-file",
+        &[("assets/a.png", b"a"), ("assets/maps/b.tmx", b"b")],
+    );
+    let out = dir.join("assets.pak");
+
+    let output = pack(&[&dir.join("assets"), &out]);
+    assert!(output.status.success());
+
+    let archive = Archive::open(&out).unwrap();
+    assert_eq!(sorted_paths(&archive), ["a.png", "maps/b.tmx"]);
+    assert_eq!(archive.read("maps/b.tmx").unwrap().unwrap(), b"b");
+}
+
+#[test]
+fn archive_inside_the_folder_is_not_packed() {
+    let dir = directory("inside", &[("a.png", b"a")]);
+    let out = dir.join("assets.pak");
+
+    // the second run finds the archive of the first one in the folder
+    for _ in 0..2 {
+        assert!(pack(&[&dir, &out]).status.success());
+    }
+
+    let archive = Archive::open(&out).unwrap();
+    assert_eq!(sorted_paths(&archive), ["a.png"]);
+}
+
+#[test]
+fn missing_arguments_fail_with_the_usage() {
+    let output = pack(&[Path::new("assets")]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("Usage:"));
+}
@@ -0,0 +1,105 @@
+//! Draws many small sprites to measure what batching copies is worth.
+//!
+//! For a few seconds every sprite is copied from one texture, so the copies reach SDL in large
+//! batches. Then the sprites alternate between two textures, which ends a batch at every copy.
+//! The average frame time of both phases is printed before quitting. Presenting waits for the
+//! display, so raise `SPRITES` if both phases report the refresh interval.
+
+use panko::canvas::Canvas;
+use panko::texture::Texture;
+use panko::types::*;
+use panko::{run_event_loop, Application, Context, Result};
+use panko_sdl2::BackendSDL2;
+
+const SPRITES: i32 = 50_000;
+const PHASE_MILLIS: u64 = 5_000;
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 600;
+
+struct Sprites {
+    red: Texture,
+    blue: Texture,
+    alternate: bool,
+    phase_millis: u64,
+    frames: u64,
+}
+
+impl Sprites {
+    fn new(context: &mut Context) -> Result<Self> {
+        Ok(Self {
+            red: square(context, Color::RED)?,
+            blue: square(context, Color::new(0, 0, 255, 255))?,
+            alternate: false,
+            phase_millis: 0,
+            frames: 0,
+        })
+    }
+}
+
+fn square(context: &mut Context, color: Color) -> Result<Texture> {
+    let pixels = [color.r, color.g, color.b, color.a].repeat(8 * 8);
+    context.create_texture_from_pixels(8, 8, PixelFormat::Rgba8, &pixels)
+}
+
+impl Application for Sprites {
+    fn update(&mut self, context: &mut Context, delta_ms: u64) -> Result {
+        self.phase_millis += delta_ms;
+        self.frames += 1;
+        if self.phase_millis < PHASE_MILLIS {
+            return Ok(());
+        }
+        let phase = if self.alternate {
+            "alternating textures"
+        } else {
+            "one texture"
+        };
+        println!(
+            "{} sprites from {}: {:.2} ms per frame",
+            SPRITES,
+            phase,
+            self.phase_millis as f64 / self.frames as f64
+        );
+        if self.alternate {
+            context.request_quit();
+        }
+        self.alternate = true;
+        self.phase_millis = 0;
+        self.frames = 0;
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, _context: &mut Context, _fixed_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, _alpha_secs: f32) -> Result {
+        for i in 0..SPRITES {
+            let texture = if self.alternate && i % 2 == 1 {
+                &self.blue
+            } else {
+                &self.red
+            };
+            let x = i * 7 % (WIDTH as i32 - 8);
+            let y = i * 13 % (HEIGHT as i32 - 8);
+            canvas.copy_texture(
+                texture,
+                CopyTextureOptions {
+                    dest: Some(Rect::new(x, y, 8, 8)),
+                    ..Default::default()
+                },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn main() -> Result {
+    let backend = BackendSDL2::new(
+        "sprites",
+        WindowConfig::Bordered {
+            size: Dimensions::new(WIDTH, HEIGHT),
+            resizable: false,
+        },
+    )?;
+    run_event_loop(backend, Sprites::new)
+}
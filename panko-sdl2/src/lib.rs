@@ -1,6 +1,8 @@
 use panko::backend::*;
+use panko::image::Image;
+use panko::slots::Slots;
 use panko::types::*;
-use panko::Result;
+use panko::{Error, ResourceKind, Result};
 use sdl2_sys::*;
 use std::ffi::c_char;
 use std::ffi::c_int;
@@ -8,23 +10,133 @@ use std::ffi::CStr;
 use std::ffi::CString;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex, MutexGuard};
 
 static IS_SDL2_INITIALIZED: AtomicBool = AtomicBool::new(false);
+/// SDL_ttf opens every font with the same FreeType library, which only allows one thread at a
+/// time to open or close a face. Held around both, as loader threads open fonts too.
+static FONT_FACES: Mutex<()> = Mutex::new(());
 
 pub struct BackendSDL2 {
     window: *mut SDL_Window,
     renderer: *mut SDL_Renderer,
-    textures: Vec<Option<*mut SDL_Texture>>,
-    fonts: Vec<Option<*mut ttf::TTF_Font>>,
+    textures: Slots<*mut SDL_Texture>,
+    fonts: Slots<SdlFont>,
+}
+
+struct SdlFont {
+    raw: *mut ttf::TTF_Font,
+    /// SDL_ttf keeps reading from the buffer of fonts opened from memory, so it lives as long as
+    /// the font.
+    #[allow(dead_code)]
+    bytes: Option<Box<[u8]>>,
+}
+
+impl SdlFont {
+    /// Opens a font from memory, on any thread.
+    fn open(bytes: Box<[u8]>, scale: u8) -> Result<Self> {
+        let raw = unsafe {
+            let rw = SDL_RWFromConstMem(bytes.as_ptr() as *const _, bytes.len() as c_int);
+            if rw.is_null() {
+                return Err(sdl_error());
+            }
+            let _faces = lock_font_faces();
+            // the RWops is freed by SDL_ttf once the font is closed
+            ttf::TTF_OpenFontRW(rw, 1, scale as c_int)
+        };
+        if (raw as *mut ()).is_null() {
+            return Err(Error::Decode(unsafe { sdl_error_message() }));
+        }
+        Ok(Self {
+            raw,
+            bytes: Some(bytes),
+        })
+    }
+
+    fn close(self) {
+        let _faces = lock_font_faces();
+        unsafe { ttf::TTF_CloseFont(self.raw) };
+    }
+}
+
+/// A font opened on a loader thread. Closed if it is never created, unless SDL_ttf has already
+/// shut down along with the backend.
+struct DecodedSdlFont(Option<SdlFont>);
+
+// SDL_ttf fonts may move between threads, as long as only one uses them at a time
+unsafe impl Send for DecodedSdlFont {}
+
+impl Drop for DecodedSdlFont {
+    fn drop(&mut self) {
+        if let Some(font) = self.0.take() {
+            if unsafe { ttf::TTF_WasInit() } != 0 {
+                font.close();
+            }
+        }
+    }
+}
+
+/// Decodes images with SDL_image and opens fonts with SDL_ttf, neither of which needs the
+/// renderer.
+struct SdlDecoder;
+
+impl Decoder for SdlDecoder {
+    fn decode_image(&self, bytes: &[u8]) -> Result<Image> {
+        unsafe {
+            let rw = SDL_RWFromConstMem(bytes.as_ptr() as *const _, bytes.len() as c_int);
+            if rw.is_null() {
+                return Err(sdl_error());
+            }
+            // the RWops is freed by SDL_image
+            let decoded = sdl2_sys::image::IMG_Load_RW(rw, 1);
+            if decoded.is_null() {
+                return Err(Error::Decode(sdl_error_message()));
+            }
+            let surface = SDL_ConvertSurfaceFormat(
+                decoded,
+                SDL_PixelFormatEnum::SDL_PIXELFORMAT_RGBA32 as u32,
+                0,
+            );
+            SDL_FreeSurface(decoded);
+            if surface.is_null() {
+                return Err(sdl_error());
+            }
+
+            let width = (*surface).w as usize;
+            let height = (*surface).h as usize;
+            let pitch = (*surface).pitch as usize;
+            let rows = std::slice::from_raw_parts((*surface).pixels as *const u8, pitch * height);
+            let mut pixels = Vec::with_capacity(width * height * 4);
+            for y in 0..height {
+                pixels.extend_from_slice(&rows[y * pitch..y * pitch + width * 4]);
+            }
+            SDL_FreeSurface(surface);
+            Ok(Image {
+                width: width as u32,
+                height: height as u32,
+                pixels,
+            })
+        }
+    }
+
+    fn decode_font(&self, bytes: Vec<u8>, scale: u8) -> Result<DecodedFont> {
+        let font = SdlFont::open(bytes.into_boxed_slice(), scale)?;
+        Ok(DecodedFont {
+            scale,
+            font: Box::new(DecodedSdlFont(Some(font))),
+        })
+    }
 }
 
 impl BackendSDL2 {
     pub fn new(title: &str, config: WindowConfig) -> Result<Self> {
         if IS_SDL2_INITIALIZED.load(Ordering::Relaxed) {
-            return Err(String::from("Context SDL2 already initialized."));
+            return Err(Error::Backend(String::from(
+                "Context SDL2 already initialized.",
+            )));
         }
 
-        let window_name = CString::new(title).map_err(|e| e.to_string())?;
+        let window_name = CString::new(title).map_err(|e| Error::Backend(e.to_string()))?;
 
         unsafe {
             if SDL_Init(SDL_INIT_VIDEO) < 0 {
@@ -108,12 +220,47 @@ impl BackendSDL2 {
             Ok(Self {
                 window,
                 renderer,
-                textures: Vec::with_capacity(32),
-                fonts: Vec::with_capacity(32),
+                textures: Slots::with_capacity(ResourceKind::Texture, 32),
+                fonts: Slots::with_capacity(ResourceKind::Font, 32),
             })
         }
     }
 
+    /// Takes ownership of a freshly loaded texture, destroying it if its size can't be queried.
+    unsafe fn insert_texture(&mut self, texture: *mut SDL_Texture) -> Result<TextureData> {
+        let mut width: i32 = 0;
+        let mut height: i32 = 0;
+        if SDL_QueryTexture(
+            texture,
+            std::ptr::null_mut::<u32>(),
+            std::ptr::null_mut::<i32>(),
+            &mut width as *mut i32,
+            &mut height as *mut i32,
+        ) != 0
+        {
+            let error = sdl_error();
+            SDL_DestroyTexture(texture);
+            return Err(error);
+        }
+
+        let id = self.textures.insert(texture);
+
+        Ok(TextureData {
+            id: TextureId(id),
+            width: width as u32,
+            height: height as u32,
+        })
+    }
+
+    fn insert_font(&mut self, font: SdlFont) -> FontData {
+        let height = unsafe { ttf::TTF_FontHeight(font.raw) } as u32;
+        let id = self.fonts.insert(font);
+        FontData {
+            id: FontId(id),
+            glyphs_height: height,
+        }
+    }
+
     fn create_raw_sdl_target_texture(&mut self, w: u32, h: u32) -> Result<*mut SDL_Texture> {
         const ZEROES: &[u8] = &[0_u8; 4 * 2048 * 2048];
 
@@ -185,12 +332,18 @@ impl Backend for BackendSDL2 {
         Ok(())
     }
 
+    fn window_size(&mut self) -> Result<(u32, u32)> {
+        let mut w: c_int = 0;
+        let mut h: c_int = 0;
+        unsafe { SDL_GetWindowSize(self.window, &mut w, &mut h) };
+        Ok((w as u32, h as u32))
+    }
+
     fn texture_create(&mut self, w: u32, h: u32) -> Result<TextureData> {
         let texture = self.create_raw_sdl_target_texture(w, h)?;
-        let id = self.textures.len();
-        self.textures.push(Some(texture));
+        let id = self.textures.insert(texture);
         Ok(TextureData {
-            id: TextureId(id as u32),
+            id: TextureId(id),
             width: w,
             height: h,
         })
@@ -200,105 +353,197 @@ impl Backend for BackendSDL2 {
         use std::path::Path;
 
         if !Path::new(path).exists() {
-            return Err(String::from("File does not exist."));
+            return Err(Error::AssetNotFound(path.to_owned()));
         }
 
-        let c_str = CString::new(path).map_err(|e| e.to_string())?;
+        let c_str = CString::new(path).map_err(|_| Error::AssetNotFound(path.to_owned()))?;
         let c_str = c_str.as_ptr();
 
-        let (texture, width, height) = unsafe {
+        unsafe {
             let texture = sdl2_sys::image::IMG_LoadTexture(self.renderer, c_str);
             if texture.is_null() {
+                return Err(Error::Decode(sdl_error_message()));
+            }
+            self.insert_texture(texture)
+        }
+    }
+
+    fn texture_load_from_memory(&mut self, bytes: &[u8]) -> Result<TextureData> {
+        unsafe {
+            let rw = SDL_RWFromConstMem(bytes.as_ptr() as *const _, bytes.len() as c_int);
+            if rw.is_null() {
                 return Err(sdl_error());
             }
+            // the RWops is freed by SDL_image
+            let texture = sdl2_sys::image::IMG_LoadTexture_RW(self.renderer, rw, 1);
+            if texture.is_null() {
+                return Err(Error::Decode(sdl_error_message()));
+            }
+            self.insert_texture(texture)
+        }
+    }
 
-            let mut width: i32 = 0;
-            let mut height: i32 = 0;
-            if SDL_QueryTexture(
-                texture,
-                std::ptr::null_mut::<u32>(),
-                std::ptr::null_mut::<i32>(),
-                &mut width as *mut i32,
-                &mut height as *mut i32,
-            ) != 0
+    fn texture_create_from_pixels(
+        &mut self,
+        w: u32,
+        h: u32,
+        format: PixelFormat,
+        pixels: &[u8],
+    ) -> Result<TextureData> {
+        let expected = format.buffer_size(w, h);
+        if pixels.len() != expected {
+            return Err(Error::PixelBufferSize {
+                expected,
+                actual: pixels.len(),
+            });
+        }
+
+        unsafe {
+            let texture = SDL_CreateTexture(
+                self.renderer,
+                sdl_pixel_format(format) as u32,
+                SDL_TextureAccess::SDL_TEXTUREACCESS_STREAMING as c_int,
+                w as c_int,
+                h as c_int,
+            );
+            if texture.is_null() {
+                return Err(sdl_error());
+            }
+            let pitch = (w as usize * format.bytes_per_pixel()) as c_int;
+            if SDL_SetTextureBlendMode(texture, SDL_BlendMode::SDL_BLENDMODE_BLEND) < 0
+                || SDL_UpdateTexture(texture, std::ptr::null(), pixels.as_ptr() as *const _, pitch)
+                    < 0
             {
                 let error = sdl_error();
                 SDL_DestroyTexture(texture);
                 return Err(error);
             }
+            self.insert_texture(texture)
+        }
+    }
 
-            (texture, width, height)
-        };
-
-        let id = self.textures.len();
+    fn texture_update(&mut self, id: TextureId, rect: Option<Rect>, pixels: &[u8]) -> Result {
+        let texture = *self.textures.get(id.0)?;
 
-        self.textures.push(Some(texture));
+        let mut format: u32 = 0;
+        let mut access: c_int = 0;
+        let mut width: c_int = 0;
+        let mut height: c_int = 0;
+        unsafe {
+            if SDL_QueryTexture(texture, &mut format, &mut access, &mut width, &mut height) != 0 {
+                return Err(sdl_error());
+            }
+        }
+        if access != SDL_TextureAccess::SDL_TEXTUREACCESS_STREAMING as c_int {
+            return Err(Error::NotStreaming);
+        }
+        let format = [PixelFormat::Rgba8, PixelFormat::Bgra8, PixelFormat::Rgb8]
+            .into_iter()
+            .find(|f| sdl_pixel_format(*f) as u32 == format)
+            .ok_or(Error::NotStreaming)?;
+
+        let rect = rect.unwrap_or(Rect::new(0, 0, width as u32, height as u32));
+        if !rect.is_within(width as u32, height as u32) {
+            return Err(Error::OutOfBounds);
+        }
+        let expected = format.buffer_size(rect.w, rect.h);
+        if pixels.len() != expected {
+            return Err(Error::PixelBufferSize {
+                expected,
+                actual: pixels.len(),
+            });
+        }
 
-        Ok(TextureData {
-            id: TextureId(id as u32),
-            width: width as u32,
-            height: height as u32,
-        })
+        let sdl_rect = rect_to_sdl_rect(rect);
+        let pitch = (rect.w as usize * format.bytes_per_pixel()) as c_int;
+        unsafe {
+            if SDL_UpdateTexture(texture, &sdl_rect, pixels.as_ptr() as *const _, pitch) < 0 {
+                return Err(sdl_error());
+            }
+        }
+        Ok(())
     }
 
     fn texture_destroy(&mut self, id: TextureId) -> Result {
-        let Some(texture) = self.textures.get_mut(id.0 as usize) else {
-            return Ok(());
-        };
-        let Some(texture) = texture.take() else {
+        let Some(texture) = self.textures.remove(id.0) else {
             return Ok(());
         };
         unsafe { SDL_DestroyTexture(texture) };
         Ok(())
     }
 
+    fn texture_reload(&mut self, id: TextureId, path: &str) -> Result<TextureData> {
+        self.textures.get(id.0)?;
+        let data = self.texture_load(path)?;
+        let texture = self.textures.remove(data.id.0).unwrap();
+        let old = std::mem::replace(self.textures.get_mut(id.0)?, texture);
+        unsafe { SDL_DestroyTexture(old) };
+        Ok(TextureData { id, ..data })
+    }
+
     fn font_load(&mut self, path: &str, scale: u8) -> Result<FontData> {
         use std::path::Path;
 
         if !Path::new(path).exists() {
-            return Err(String::from("File does not exist."));
+            return Err(Error::AssetNotFound(path.to_owned()));
         }
 
-        let c_str = CString::new(path).map_err(|e| e.to_string())?;
+        let c_str = CString::new(path).map_err(|_| Error::AssetNotFound(path.to_owned()))?;
         let c_str_ptr = c_str.as_ptr();
 
-        let (font, height) = unsafe {
-            let font = ttf::TTF_OpenFont(c_str_ptr, scale as i32);
-            if (font as *mut ()).is_null() {
-                return Err(sdl_error());
-            }
-
-            let height = ttf::TTF_FontHeight(font) as u32;
-            (font, height)
+        let font = {
+            let _faces = lock_font_faces();
+            unsafe { ttf::TTF_OpenFont(c_str_ptr, scale as i32) }
         };
+        if (font as *mut ()).is_null() {
+            return Err(Error::Decode(unsafe { sdl_error_message() }));
+        }
 
         drop(c_str);
 
-        let id = self.fonts.len();
-        self.fonts.push(Some(font));
-        Ok(FontData {
-            id: FontId(id as u32),
-            glyphs_height: height,
-        })
+        // plain `None` is X11's constant from the sdl2_sys glob import
+        Ok(self.insert_font(SdlFont {
+            raw: font,
+            bytes: Option::None,
+        }))
+    }
+
+    fn font_load_from_memory(&mut self, bytes: &[u8], scale: u8) -> Result<FontData> {
+        let font = SdlFont::open(bytes.into(), scale)?;
+        Ok(self.insert_font(font))
     }
 
     fn font_destroy(&mut self, id: FontId) -> Result {
-        let Some(font) = self.fonts.get_mut(id.0 as usize) else {
-            return Ok(());
-        };
-        let Some(font) = font.take() else {
+        let Some(font) = self.fonts.remove(id.0) else {
             return Ok(());
         };
-        unsafe { ttf::TTF_CloseFont(font) };
+        font.close();
         Ok(())
     }
 
+    fn font_reload(&mut self, id: FontId, path: &str, scale: u8) -> Result<FontData> {
+        self.fonts.get(id.0)?;
+        let data = self.font_load(path, scale)?;
+        let font = self.fonts.remove(data.id.0).unwrap();
+        let old = std::mem::replace(self.fonts.get_mut(id.0)?, font);
+        old.close();
+        Ok(FontData { id, ..data })
+    }
+
+    fn font_create_decoded(&mut self, font: DecodedFont) -> Result<FontData> {
+        let mut font = font
+            .font
+            .downcast::<DecodedSdlFont>()
+            .map_err(|_| Error::Backend(String::from("Font was decoded by another backend.")))?;
+        Ok(self.insert_font(font.0.take().unwrap()))
+    }
+
+    fn decoder(&self) -> Arc<dyn Decoder> {
+        Arc::new(SdlDecoder)
+    }
+
     fn font_glyph_metrics(&mut self, font: FontId, glyph: char) -> Result<GlyphMetrics> {
-        let font = self
-            .fonts
-            .get(font.0 as usize)
-            .ok_or(String::from("Font was never registered"))?;
-        let font = font.ok_or(String::from("Font was already deleted."))?;
+        let font = self.fonts.get(font.0)?.raw;
 
         let mut min_x = 0;
         let mut max_x = 0;
@@ -319,7 +564,9 @@ impl Backend for BackendSDL2 {
         };
 
         if ret != 0 {
-            return Err(String::from("Unable to calculate glyph metrics."));
+            return Err(Error::Backend(String::from(
+                "Unable to calculate glyph metrics.",
+            )));
         }
 
         Ok(GlyphMetrics {
@@ -331,9 +578,36 @@ impl Backend for BackendSDL2 {
         })
     }
 
-    fn render_set_logical_size(&mut self, w: u32, h: u32) -> Result {
+    fn render_set_logical_screen(&mut self, screen: Option<LogicalScreen>) -> Result {
+        let Some(screen) = screen else {
+            unsafe {
+                if SDL_RenderSetScale(self.renderer, 1.0, 1.0) != 0
+                    || SDL_RenderSetViewport(self.renderer, std::ptr::null()) != 0
+                {
+                    return Err(sdl_error());
+                }
+            }
+            return Ok(());
+        };
+
+        // the viewport is in window units, which can be several pixels each on high-DPI screens
+        let (window_w, _) = self.window_size()?;
+        let (output_w, _) = self.render_output_size()?;
+        let dpi = output_w as f32 / window_w.max(1) as f32;
+        let viewport = screen.viewport;
+        let scale_x = viewport.w as f32 * dpi / screen.size.width as f32;
+        let scale_y = viewport.h as f32 * dpi / screen.size.height as f32;
+        // SDL scales the viewport too, so it is given in logical pixels
+        let sdl_viewport = SDL_Rect {
+            x: (viewport.x as f32 * dpi / scale_x).round() as c_int,
+            y: (viewport.y as f32 * dpi / scale_y).round() as c_int,
+            w: screen.size.width as c_int,
+            h: screen.size.height as c_int,
+        };
         unsafe {
-            if SDL_RenderSetLogicalSize(self.renderer, w as i32, h as i32) != 0 {
+            if SDL_RenderSetScale(self.renderer, scale_x, scale_y) != 0
+                || SDL_RenderSetViewport(self.renderer, &sdl_viewport) != 0
+            {
                 return Err(sdl_error());
             }
         }
@@ -343,14 +617,7 @@ impl Backend for BackendSDL2 {
     fn render_set_target(&mut self, target: Option<TextureId>) -> Result {
         match target {
             Some(TextureId(id)) => {
-                let index = id as usize;
-                let texture = self
-                    .textures
-                    .get(index)
-                    .ok_or(String::from("Texture was never created."))?;
-                let texture = texture
-                    .clone()
-                    .ok_or(String::from("Texture was already deleted."))?;
+                let texture = *self.textures.get(id)?;
                 unsafe {
                     if SDL_SetRenderTarget(self.renderer, texture) != 0 {
                         return Err(sdl_error());
@@ -366,6 +633,19 @@ impl Backend for BackendSDL2 {
         Ok(())
     }
 
+    fn render_set_clip_rect(&mut self, rect: Option<Rect>) -> Result {
+        let rect = rect.map(rect_to_sdl_rect);
+        let rect = rect
+            .as_ref()
+            .map_or(std::ptr::null(), |r| r as *const SDL_Rect);
+        unsafe {
+            if SDL_RenderSetClipRect(self.renderer, rect) != 0 {
+                return Err(sdl_error());
+            }
+        }
+        Ok(())
+    }
+
     fn render_set_draw_color(&mut self, color: Color) -> Result {
         unsafe {
             if SDL_SetRenderDrawColor(self.renderer, color.r, color.g, color.b, color.a) != 0 {
@@ -389,14 +669,20 @@ impl Backend for BackendSDL2 {
         Ok(())
     }
 
+    fn render_flush(&mut self) -> Result {
+        Ok(())
+    }
+
+    fn render_check_copy(&mut self, texture: TextureId) -> Result {
+        let texture = *self.textures.get(texture.0)?;
+        if unsafe { SDL_GetRenderTarget(self.renderer) } == texture {
+            return Err(Error::SourceIsTarget);
+        }
+        Ok(())
+    }
+
     fn render_copy_texture(&mut self, texture: TextureId, options: CopyTextureOptions) -> Result {
-        let texture = self
-            .textures
-            .get(texture.0 as usize)
-            .ok_or(String::from("Texture was never created."))?;
-        let texture = texture
-            .clone()
-            .ok_or(String::from("Texture was already deleted."))?;
+        let texture = *self.textures.get(texture.0)?;
         let src = options.src.map(rect_to_sdl_rect);
         let src = src
             .as_ref()
@@ -409,20 +695,23 @@ impl Backend for BackendSDL2 {
         let center = center
             .as_ref()
             .map_or(std::ptr::null(), |p| p as *const SDL_Point);
-        let flip = if options.flip_h {
-            SDL_RendererFlip::SDL_FLIP_HORIZONTAL
-        } else if options.flip_v {
-            SDL_RendererFlip::SDL_FLIP_VERTICAL
-        } else {
-            SDL_RendererFlip::SDL_FLIP_NONE
-        };
+        let mut flip = SDL_RendererFlip::SDL_FLIP_NONE as u32;
+        if options.flip_h {
+            flip |= SDL_RendererFlip::SDL_FLIP_HORIZONTAL as u32;
+        }
+        if options.flip_v {
+            flip |= SDL_RendererFlip::SDL_FLIP_VERTICAL as u32;
+        }
+        // modulation and blending stay set on the texture, so every copy sets all of them
+        let color = options.color_mod.unwrap_or(Color::WHITE);
         unsafe {
-            if let Some(color) = options.color_mod {
-                if SDL_SetTextureColorMod(texture, color.r, color.g, color.b) != 0 {
-                    return Err(sdl_error());
-                }
+            if SDL_SetTextureColorMod(texture, color.r, color.g, color.b) != 0
+                || SDL_SetTextureAlphaMod(texture, color.a) != 0
+                || set_texture_blend_mode(texture, sdl_blend_mode(options.blend_mode)) != 0
+            {
+                return Err(sdl_error());
             }
-            if SDL_RenderCopyEx(
+            if render_copy_ex(
                 self.renderer,
                 texture,
                 src,
@@ -438,6 +727,108 @@ impl Backend for BackendSDL2 {
         Ok(())
     }
 
+    fn render_copy_texture_batch(
+        &mut self,
+        texture: TextureId,
+        copies: &[CopyTextureOptions],
+    ) -> Result {
+        let Some(first) = copies.first() else {
+            return Ok(());
+        };
+        let texture = *self.textures.get(texture.0)?;
+        let mut vertices = Vec::with_capacity(copies.len() * 4);
+        let mut indices: Vec<c_int> = Vec::with_capacity(copies.len() * 6);
+        unsafe {
+            let (mut w, mut h) = (0, 0);
+            let mut viewport = SDL_Rect {
+                x: 0,
+                y: 0,
+                w: 0,
+                h: 0,
+            };
+            if SDL_QueryTexture(
+                texture,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                &mut w,
+                &mut h,
+            ) != 0
+            {
+                return Err(sdl_error());
+            }
+            SDL_RenderGetViewport(self.renderer, &mut viewport);
+
+            for options in copies {
+                let src = options.src.unwrap_or(Rect::new(0, 0, w as u32, h as u32));
+                let dest = options.dest.unwrap_or(Rect::new(
+                    0,
+                    0,
+                    viewport.w as u32,
+                    viewport.h as u32,
+                ));
+                let (w, h) = (w as f32, h as f32);
+                let mut u0 = src.x as f32 / w;
+                let mut u1 = (src.x as f32 + src.w as f32) / w;
+                let mut v0 = src.y as f32 / h;
+                let mut v1 = (src.y as f32 + src.h as f32) / h;
+                if options.flip_h {
+                    std::mem::swap(&mut u0, &mut u1);
+                }
+                if options.flip_v {
+                    std::mem::swap(&mut v0, &mut v1);
+                }
+                let (dest_w, dest_h) = (dest.w as f32, dest.h as f32);
+                let (cx, cy) = options
+                    .center
+                    .map_or((dest_w / 2.0, dest_h / 2.0), |c| (c.x as f32, c.y as f32));
+                let (sin, cos) = (options.angle.to_radians() as f32).sin_cos();
+                let color = options.color_mod.unwrap_or(Color::WHITE);
+
+                let base = vertices.len() as c_int;
+                let corners = [
+                    (0.0, 0.0, u0, v0),
+                    (dest_w, 0.0, u1, v0),
+                    (dest_w, dest_h, u1, v1),
+                    (0.0, dest_h, u0, v1),
+                ];
+                for (x, y, u, v) in corners {
+                    let (x, y) = (x - cx, y - cy);
+                    vertices.push(SDL_Vertex {
+                        position: SDL_FPoint {
+                            x: dest.x as f32 + cx + x * cos - y * sin,
+                            y: dest.y as f32 + cy + x * sin + y * cos,
+                        },
+                        color: SDL_Color {
+                            r: color.r,
+                            g: color.g,
+                            b: color.b,
+                            a: color.a,
+                        },
+                        tex_coord: SDL_FPoint { x: u, y: v },
+                    });
+                }
+                indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+            }
+
+            // the vertices carry the modulation, which the texture would otherwise add to
+            if SDL_SetTextureColorMod(texture, 255, 255, 255) != 0
+                || SDL_SetTextureAlphaMod(texture, 255) != 0
+                || set_texture_blend_mode(texture, sdl_blend_mode(first.blend_mode)) != 0
+                || SDL_RenderGeometry(
+                    self.renderer,
+                    texture,
+                    vertices.as_ptr(),
+                    vertices.len() as c_int,
+                    indices.as_ptr(),
+                    indices.len() as c_int,
+                ) != 0
+            {
+                return Err(sdl_error());
+            }
+        }
+        Ok(())
+    }
+
     fn render_fill_rect(&mut self, rect: Option<Rect>, color: Color) -> Result {
         let rect = rect.map(rect_to_sdl_rect);
         let rect = rect
@@ -466,13 +857,67 @@ impl Backend for BackendSDL2 {
         Ok(())
     }
 
+    fn render_draw_points(&mut self, points: &[Point], color: Color) -> Result {
+        let points: Vec<SDL_Point> = points.iter().copied().map(point_to_sdl_point).collect();
+        self.render_set_draw_color(color)?;
+        unsafe {
+            if SDL_RenderDrawPoints(self.renderer, points.as_ptr(), points.len() as c_int) != 0 {
+                return Err(sdl_error());
+            }
+        }
+        Ok(())
+    }
+
+    fn render_draw_lines(&mut self, points: &[Point], color: Color) -> Result {
+        let points: Vec<SDL_Point> = points.iter().copied().map(point_to_sdl_point).collect();
+        self.render_set_draw_color(color)?;
+        unsafe {
+            if SDL_RenderDrawLines(self.renderer, points.as_ptr(), points.len() as c_int) != 0 {
+                return Err(sdl_error());
+            }
+        }
+        Ok(())
+    }
+
+    fn render_geometry(&mut self, vertices: &[Vertex]) -> Result {
+        if vertices.is_empty() {
+            return Ok(());
+        }
+        let vertices: Vec<SDL_Vertex> = vertices
+            .iter()
+            .map(|vertex| SDL_Vertex {
+                position: SDL_FPoint {
+                    x: vertex.x,
+                    y: vertex.y,
+                },
+                color: SDL_Color {
+                    r: vertex.color.r,
+                    g: vertex.color.g,
+                    b: vertex.color.b,
+                    a: vertex.color.a,
+                },
+                tex_coord: SDL_FPoint { x: 0.0, y: 0.0 },
+            })
+            .collect();
+        unsafe {
+            if SDL_RenderGeometry(
+                self.renderer,
+                std::ptr::null_mut(),
+                vertices.as_ptr(),
+                vertices.len() as c_int,
+                std::ptr::null(),
+                0,
+            ) != 0
+            {
+                return Err(sdl_error());
+            }
+        }
+        Ok(())
+    }
+
     fn render_font_glyph(&mut self, font: FontId, glyph: char, origin: Point) -> Result {
         unsafe {
-            let font = self
-                .fonts
-                .get_mut(font.0 as usize)
-                .ok_or(String::from("Font was never created."))?
-                .ok_or(String::from("Font was already deleted."))?;
+            let font = self.fonts.get(font.0)?.raw;
 
             let font_glyph_surface = ttf::TTF_RenderGlyph_Blended(
                 font,
@@ -564,6 +1009,54 @@ impl Backend for BackendSDL2 {
         Ok(())
     }
 
+    fn render_output_size(&mut self) -> Result<(u32, u32)> {
+        let mut w: c_int = 0;
+        let mut h: c_int = 0;
+        unsafe {
+            if SDL_GetRendererOutputSize(self.renderer, &mut w, &mut h) != 0 {
+                return Err(sdl_error());
+            }
+        }
+        Ok((w as u32, h as u32))
+    }
+
+    fn render_read_pixels(&mut self, rect: Option<Rect>) -> Result<Vec<u8>> {
+        let (w, h) = self.render_output_size()?;
+        let rect = rect.unwrap_or(Rect::new(0, 0, w, h));
+        if !rect.is_within(w, h) {
+            return Err(Error::OutOfBounds);
+        }
+
+        let format = PixelFormat::Rgba8;
+        let mut pixels = vec![0u8; format.buffer_size(rect.w, rect.h)];
+        let sdl_rect = rect_to_sdl_rect(rect);
+        let pitch = (rect.w as usize * format.bytes_per_pixel()) as c_int;
+        unsafe {
+            // the logical size letterboxes the viewport, and SDL only reads inside the viewport,
+            // so it is widened to the whole output while reading.
+            let mut viewport = SDL_Rect {
+                x: 0,
+                y: 0,
+                w: 0,
+                h: 0,
+            };
+            SDL_RenderGetViewport(self.renderer, &mut viewport);
+            SDL_RenderSetViewport(self.renderer, std::ptr::null());
+            let read = SDL_RenderReadPixels(
+                self.renderer,
+                &sdl_rect,
+                sdl_pixel_format(format) as u32,
+                pixels.as_mut_ptr() as *mut _,
+                pitch,
+            );
+            SDL_RenderSetViewport(self.renderer, &viewport);
+            if read != 0 {
+                return Err(sdl_error());
+            }
+        }
+        Ok(pixels)
+    }
+
     fn events_pump(&mut self, events: &mut Vec<Event>) {
         use std::mem::MaybeUninit;
 
@@ -605,6 +1098,13 @@ impl Backend for BackendSDL2 {
                     }
                 } else if event.type_ == SDL_EventType::SDL_MOUSEBUTTONUP as u32 {
                     events.push(Event::MouseUp);
+                } else if event.type_ == SDL_EventType::SDL_WINDOWEVENT as u32
+                    && event.window.event == SDL_WindowEventID::SDL_WINDOWEVENT_SIZE_CHANGED as u8
+                {
+                    events.push(Event::WindowResized(Dimensions::new(
+                        event.window.data1 as u32,
+                        event.window.data2 as u32,
+                    )));
                 }
             }
         }
@@ -628,26 +1128,83 @@ impl Backend for BackendSDL2 {
 
 impl Drop for BackendSDL2 {
     fn drop(&mut self) {
-        for texture in self.textures.iter_mut() {
-            let Some(texture) = texture.take() else {
-                continue;
-            };
+        for texture in self.textures.drain() {
             unsafe { SDL_DestroyTexture(texture) };
         }
-        for font in self.fonts.iter_mut() {
-            let Some(font) = font.take() else {
-                continue;
-            };
-            unsafe { ttf::TTF_CloseFont(font) };
+        for font in self.fonts.drain() {
+            font.close();
         }
     }
 }
 
-unsafe fn sdl_error() -> String {
+fn lock_font_faces() -> MutexGuard<'static, ()> {
+    FONT_FACES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+unsafe fn sdl_error() -> Error {
+    Error::Backend(sdl_error_message())
+}
+
+unsafe fn sdl_error_message() -> String {
     let err = SDL_GetError();
     CStr::from_ptr(err as *const _).to_str().unwrap().to_owned()
 }
 
+fn sdl_pixel_format(format: PixelFormat) -> SDL_PixelFormatEnum {
+    match format {
+        PixelFormat::Rgba8 => SDL_PixelFormatEnum::SDL_PIXELFORMAT_RGBA32,
+        PixelFormat::Bgra8 => SDL_PixelFormatEnum::SDL_PIXELFORMAT_BGRA32,
+        PixelFormat::Rgb8 => SDL_PixelFormatEnum::SDL_PIXELFORMAT_RGB24,
+    }
+}
+
+// Custom blend modes and both flips at once are outside of the `SDL_BlendMode` and
+// `SDL_RendererFlip` enums of the bindings, so the functions making and taking them are declared
+// again with plain integers.
+extern "C" {
+    #[link_name = "SDL_ComposeCustomBlendMode"]
+    fn compose_custom_blend_mode(
+        src_color_factor: SDL_BlendFactor,
+        dst_color_factor: SDL_BlendFactor,
+        color_operation: SDL_BlendOperation,
+        src_alpha_factor: SDL_BlendFactor,
+        dst_alpha_factor: SDL_BlendFactor,
+        alpha_operation: SDL_BlendOperation,
+    ) -> u32;
+    #[link_name = "SDL_SetTextureBlendMode"]
+    fn set_texture_blend_mode(texture: *mut SDL_Texture, mode: u32) -> c_int;
+    #[link_name = "SDL_RenderCopyEx"]
+    fn render_copy_ex(
+        renderer: *mut SDL_Renderer,
+        texture: *mut SDL_Texture,
+        src: *const SDL_Rect,
+        dest: *const SDL_Rect,
+        angle: f64,
+        center: *const SDL_Point,
+        flip: u32,
+    ) -> c_int;
+}
+
+fn sdl_blend_mode(mode: BlendMode) -> u32 {
+    match mode {
+        BlendMode::None => SDL_BlendMode::SDL_BLENDMODE_NONE as u32,
+        BlendMode::Alpha => SDL_BlendMode::SDL_BLENDMODE_BLEND as u32,
+        BlendMode::Additive => SDL_BlendMode::SDL_BLENDMODE_ADD as u32,
+        BlendMode::Multiply => SDL_BlendMode::SDL_BLENDMODE_MUL as u32,
+        BlendMode::Modulate => SDL_BlendMode::SDL_BLENDMODE_MOD as u32,
+        BlendMode::Premultiplied => unsafe {
+            compose_custom_blend_mode(
+                SDL_BlendFactor::SDL_BLENDFACTOR_ONE,
+                SDL_BlendFactor::SDL_BLENDFACTOR_ONE_MINUS_SRC_ALPHA,
+                SDL_BlendOperation::SDL_BLENDOPERATION_ADD,
+                SDL_BlendFactor::SDL_BLENDFACTOR_ONE,
+                SDL_BlendFactor::SDL_BLENDFACTOR_ONE_MINUS_SRC_ALPHA,
+                SDL_BlendOperation::SDL_BLENDOPERATION_ADD,
+            )
+        },
+    }
+}
+
 fn rect_to_sdl_rect(rect: Rect) -> SDL_Rect {
     SDL_Rect {
         x: rect.x,
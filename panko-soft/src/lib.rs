@@ -0,0 +1,731 @@
+pub mod snapshot;
+mod surface;
+
+pub use surface::Surface;
+
+use panko::backend::*;
+use panko::image::Image;
+use panko::slots::Slots;
+use panko::types::*;
+use panko::{Error, ResourceKind, Result};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+
+const DEFAULT_FRAME_MILLIS: u64 = 16;
+
+/// A headless backend that rasterizes everything into in-memory RGBA8 surfaces.
+///
+/// Time does not pass on its own: the clock advances by a fixed amount every time a frame is
+/// presented, which keeps runs of `run_event_loop` deterministic.
+pub struct BackendSoft {
+    window_width: u32,
+    window_height: u32,
+    logical: Option<LogicalScreen>,
+    screen: Surface,
+    target: Option<TextureId>,
+    draw_color: Color,
+    textures: Slots<Surface>,
+    /// Formats of the textures created from pixels, which `texture_update` expects.
+    streaming: HashMap<ResourceId, PixelFormat>,
+    fonts: Slots<SoftFont>,
+    millis: u64,
+    frame_millis: u64,
+    shared: Rc<RefCell<Shared>>,
+}
+
+/// A cloneable view into a `BackendSoft` that stays usable after the backend has been moved into
+/// a `Context`.
+#[derive(Clone)]
+pub struct SoftHandle(Rc<RefCell<Shared>>);
+
+#[derive(Default)]
+struct Shared {
+    frame: Option<Surface>,
+    frames_presented: u64,
+    events: VecDeque<Event>,
+    mouse_position: (i32, i32),
+}
+
+struct SoftFont {
+    font: fontdue::Font,
+    px: f32,
+    ascent: i32,
+    height: u32,
+}
+
+impl SoftFont {
+    fn parse(bytes: &[u8], scale: u8) -> Result<Self> {
+        let px = scale as f32;
+        let font = fontdue::Font::from_bytes(bytes, fontdue::FontSettings::default())
+            .map_err(|e| Error::Decode(e.to_owned()))?;
+        let line = font
+            .horizontal_line_metrics(px)
+            .ok_or(Error::Decode(String::from(
+                "Font has no horizontal metrics.",
+            )))?;
+        Ok(Self {
+            font,
+            px,
+            ascent: line.ascent.ceil() as i32,
+            height: (line.ascent - line.descent).ceil() as u32,
+        })
+    }
+}
+
+/// Decodes PNGs and parses fonts the way `BackendSoft` does, on any thread.
+struct SoftDecoder;
+
+impl Decoder for SoftDecoder {
+    fn decode_image(&self, bytes: &[u8]) -> Result<Image> {
+        Image::decode_png(bytes)
+    }
+
+    fn decode_font(&self, bytes: Vec<u8>, scale: u8) -> Result<DecodedFont> {
+        Ok(DecodedFont {
+            scale,
+            font: Box::new(SoftFont::parse(&bytes, scale)?),
+        })
+    }
+}
+
+impl BackendSoft {
+    pub fn new(width: u32, height: u32) -> Result<Self> {
+        Ok(Self {
+            window_width: width,
+            window_height: height,
+            logical: None,
+            screen: Surface::new(width, height)?,
+            target: None,
+            draw_color: Color::BLACK,
+            textures: Slots::with_capacity(ResourceKind::Texture, 32),
+            streaming: HashMap::new(),
+            fonts: Slots::with_capacity(ResourceKind::Font, 32),
+            millis: 0,
+            frame_millis: DEFAULT_FRAME_MILLIS,
+            shared: Rc::new(RefCell::new(Shared::default())),
+        })
+    }
+
+    /// Sets how many milliseconds the clock advances on every `render_present`.
+    pub fn set_frame_millis(&mut self, millis: u64) {
+        self.frame_millis = millis;
+    }
+
+    pub fn handle(&self) -> SoftHandle {
+        SoftHandle(Rc::clone(&self.shared))
+    }
+
+    fn target_surface(&mut self) -> Result<&mut Surface> {
+        match self.target {
+            Some(id) => self.textures.get_mut(id.0),
+            None => Ok(&mut self.screen),
+        }
+    }
+
+    fn texture(&self, id: TextureId) -> Result<&Surface> {
+        self.textures.get(id.0)
+    }
+
+    /// Fails for textures that can not be copied from, like SDL does for the current target.
+    fn check_source(&self, id: TextureId) -> Result {
+        self.texture(id)?;
+        if self.target == Some(id) {
+            return Err(Error::SourceIsTarget);
+        }
+        Ok(())
+    }
+
+    fn font(&self, id: FontId) -> Result<&SoftFont> {
+        self.fonts.get(id.0)
+    }
+
+    fn push_font(&mut self, font: SoftFont) -> FontData {
+        let glyphs_height = font.height;
+        FontData {
+            id: FontId(self.fonts.insert(font)),
+            glyphs_height,
+        }
+    }
+
+    fn push_texture(&mut self, surface: Surface) -> TextureData {
+        let width = surface.width();
+        let height = surface.height();
+        TextureData {
+            id: TextureId(self.textures.insert(surface)),
+            width,
+            height,
+        }
+    }
+
+    /// The rect of the window the logical screen is scaled into.
+    fn viewport(&self) -> Rect {
+        match self.logical {
+            Some(logical) => logical.viewport,
+            None => Rect::new(0, 0, self.window_width, self.window_height),
+        }
+    }
+
+    fn resize_screen(&mut self) -> Result {
+        let (w, h) = self.logical.map_or(
+            (self.window_width, self.window_height),
+            |logical| (logical.size.width, logical.size.height),
+        );
+        if self.screen.width() != w || self.screen.height() != h {
+            let clip = self.screen.clip_rect();
+            self.screen = Surface::new(w, h)?;
+            self.screen.set_clip(clip);
+        }
+        Ok(())
+    }
+}
+
+impl SoftHandle {
+    /// Returns the last presented frame, at window resolution.
+    pub fn frame(&self) -> Option<Surface> {
+        self.0.borrow().frame.clone()
+    }
+
+    pub fn frames_presented(&self) -> u64 {
+        self.0.borrow().frames_presented
+    }
+
+    /// Queues an event to be returned by the next `events_pump`.
+    pub fn push_event(&self, event: Event) {
+        self.0.borrow_mut().events.push_back(event);
+    }
+
+    pub fn set_mouse_position(&self, x: i32, y: i32) {
+        self.0.borrow_mut().mouse_position = (x, y);
+    }
+}
+
+impl Backend for BackendSoft {
+    fn window_set_config(&mut self, config: WindowConfig) -> Result {
+        match config {
+            WindowConfig::Bordered { size, .. } | WindowConfig::Borderless(size) => {
+                self.window_width = size.width;
+                self.window_height = size.height;
+                self.resize_screen()?;
+            }
+            WindowConfig::Fullscreen => {}
+        }
+        Ok(())
+    }
+
+    fn window_size(&mut self) -> Result<(u32, u32)> {
+        Ok((self.window_width, self.window_height))
+    }
+
+    fn texture_create(&mut self, w: u32, h: u32) -> Result<TextureData> {
+        Ok(self.push_texture(Surface::new(w, h)?))
+    }
+
+    fn texture_load(&mut self, path: &str) -> Result<TextureData> {
+        use std::path::Path;
+
+        if !Path::new(path).exists() {
+            return Err(Error::AssetNotFound(path.to_owned()));
+        }
+
+        let bytes = std::fs::read(path).map_err(|e| Error::Io(e.to_string()))?;
+        self.texture_load_from_memory(&bytes)
+    }
+
+    fn texture_load_from_memory(&mut self, bytes: &[u8]) -> Result<TextureData> {
+        let surface = Surface::from_png(bytes)?;
+        Ok(self.push_texture(surface))
+    }
+
+    fn texture_create_from_pixels(
+        &mut self,
+        w: u32,
+        h: u32,
+        format: PixelFormat,
+        pixels: &[u8],
+    ) -> Result<TextureData> {
+        let expected = format.buffer_size(w, h);
+        if pixels.len() != expected {
+            return Err(Error::PixelBufferSize {
+                expected,
+                actual: pixels.len(),
+            });
+        }
+        let surface = Surface::from_pixels(w, h, format.to_rgba8(pixels))?;
+        let data = self.push_texture(surface);
+        self.streaming.insert(data.id.0, format);
+        Ok(data)
+    }
+
+    fn texture_update(&mut self, id: TextureId, rect: Option<Rect>, pixels: &[u8]) -> Result {
+        let surface = self.textures.get_mut(id.0)?;
+        let format = *self.streaming.get(&id.0).ok_or(Error::NotStreaming)?;
+        let rect = rect.unwrap_or(Rect::new(0, 0, surface.width(), surface.height()));
+        if !rect.is_within(surface.width(), surface.height()) {
+            return Err(Error::OutOfBounds);
+        }
+        let expected = format.buffer_size(rect.w, rect.h);
+        if pixels.len() != expected {
+            return Err(Error::PixelBufferSize {
+                expected,
+                actual: pixels.len(),
+            });
+        }
+        surface.write(rect, &format.to_rgba8(pixels));
+        Ok(())
+    }
+
+    fn texture_destroy(&mut self, id: TextureId) -> Result {
+        self.textures.remove(id.0);
+        self.streaming.remove(&id.0);
+        Ok(())
+    }
+
+    fn texture_reload(&mut self, id: TextureId, path: &str) -> Result<TextureData> {
+        self.texture(id)?;
+        let data = self.texture_load(path)?;
+        let surface = self.textures.remove(data.id.0).unwrap();
+        *self.textures.get_mut(id.0)? = surface;
+        Ok(TextureData { id, ..data })
+    }
+
+    fn font_load(&mut self, path: &str, scale: u8) -> Result<FontData> {
+        use std::path::Path;
+
+        if !Path::new(path).exists() {
+            return Err(Error::AssetNotFound(path.to_owned()));
+        }
+
+        let bytes = std::fs::read(path).map_err(|e| Error::Io(e.to_string()))?;
+        self.font_load_from_memory(&bytes, scale)
+    }
+
+    fn font_load_from_memory(&mut self, bytes: &[u8], scale: u8) -> Result<FontData> {
+        let font = SoftFont::parse(bytes, scale)?;
+        Ok(self.push_font(font))
+    }
+
+    fn font_destroy(&mut self, id: FontId) -> Result {
+        self.fonts.remove(id.0);
+        Ok(())
+    }
+
+    fn font_reload(&mut self, id: FontId, path: &str, scale: u8) -> Result<FontData> {
+        self.font(id)?;
+        let data = self.font_load(path, scale)?;
+        let font = self.fonts.remove(data.id.0).unwrap();
+        *self.fonts.get_mut(id.0)? = font;
+        Ok(FontData { id, ..data })
+    }
+
+    fn font_create_decoded(&mut self, font: DecodedFont) -> Result<FontData> {
+        let font = font
+            .font
+            .downcast::<SoftFont>()
+            .map_err(|_| Error::Backend(String::from("Font was decoded by another backend.")))?;
+        Ok(self.push_font(*font))
+    }
+
+    fn decoder(&self) -> Arc<dyn Decoder> {
+        Arc::new(SoftDecoder)
+    }
+
+    fn font_glyph_metrics(&mut self, font: FontId, glyph: char) -> Result<GlyphMetrics> {
+        let font = self.font(font)?;
+        let metrics = font.font.metrics(glyph, font.px);
+        Ok(GlyphMetrics {
+            min_x: metrics.xmin,
+            max_x: metrics.xmin + metrics.width as i32,
+            min_y: metrics.ymin,
+            max_y: metrics.ymin + metrics.height as i32,
+            advance: metrics.advance_width.round() as u32,
+        })
+    }
+
+    fn render_set_logical_screen(&mut self, screen: Option<LogicalScreen>) -> Result {
+        self.logical = screen.filter(|s| s.size.width > 0 && s.size.height > 0);
+        self.resize_screen()
+    }
+
+    fn render_set_target(&mut self, target: Option<TextureId>) -> Result {
+        if let Some(id) = target {
+            self.textures.get_mut(id.0)?.set_clip(None);
+        }
+        self.target = target;
+        Ok(())
+    }
+
+    fn render_set_clip_rect(&mut self, rect: Option<Rect>) -> Result {
+        self.target_surface()?.set_clip(rect);
+        Ok(())
+    }
+
+    fn render_set_draw_color(&mut self, color: Color) -> Result {
+        self.draw_color = color;
+        Ok(())
+    }
+
+    fn render_clear(&mut self) -> Result {
+        let color = self.draw_color;
+        self.target_surface()?.fill(color);
+        Ok(())
+    }
+
+    fn render_present(&mut self) -> Result {
+        let frame = self
+            .screen
+            .scaled(self.viewport(), self.window_width, self.window_height)?;
+        let mut shared = self.shared.borrow_mut();
+        shared.frame = Some(frame);
+        shared.frames_presented += 1;
+        self.millis += self.frame_millis;
+        Ok(())
+    }
+
+    fn render_flush(&mut self) -> Result {
+        Ok(())
+    }
+
+    fn render_check_copy(&mut self, texture: TextureId) -> Result {
+        self.check_source(texture)
+    }
+
+    fn render_copy_texture(&mut self, texture: TextureId, options: CopyTextureOptions) -> Result {
+        self.check_source(texture)?;
+        // the source is swapped out of its slot so it can be read while the target is written.
+        let src = std::mem::take(self.textures.get_mut(texture.0)?);
+        let result = self
+            .target_surface()
+            .map(|target| copy_surface(&src, target, options));
+        *self.textures.get_mut(texture.0)? = src;
+        result
+    }
+
+    fn render_copy_texture_batch(
+        &mut self,
+        texture: TextureId,
+        copies: &[CopyTextureOptions],
+    ) -> Result {
+        self.check_source(texture)?;
+        let src = std::mem::take(self.textures.get_mut(texture.0)?);
+        let result = self.target_surface().map(|target| {
+            for options in copies {
+                copy_surface(&src, target, *options);
+            }
+        });
+        *self.textures.get_mut(texture.0)? = src;
+        result
+    }
+
+    fn render_fill_rect(&mut self, rect: Option<Rect>, color: Color) -> Result {
+        self.render_set_draw_color(color)?;
+        let target = self.target_surface()?;
+        let Some((x0, y0, x1, y1)) = target.clip(rect) else {
+            return Ok(());
+        };
+        for y in y0..y1 {
+            for x in x0..x1 {
+                target.blend_pixel(x, y, color);
+            }
+        }
+        Ok(())
+    }
+
+    fn render_draw_rect(&mut self, rect: Option<Rect>, color: Color) -> Result {
+        self.render_set_draw_color(color)?;
+        let target = self.target_surface()?;
+        let rect = rect.unwrap_or(Rect::new(0, 0, target.width(), target.height()));
+        if rect.w == 0 || rect.h == 0 {
+            return Ok(());
+        }
+        let x1 = rect.x + rect.w as i32 - 1;
+        let y1 = rect.y + rect.h as i32 - 1;
+        for x in rect.x..=x1 {
+            target.blend_pixel(x, rect.y, color);
+            if y1 != rect.y {
+                target.blend_pixel(x, y1, color);
+            }
+        }
+        for y in rect.y + 1..y1 {
+            target.blend_pixel(rect.x, y, color);
+            if x1 != rect.x {
+                target.blend_pixel(x1, y, color);
+            }
+        }
+        Ok(())
+    }
+
+    fn render_draw_points(&mut self, points: &[Point], color: Color) -> Result {
+        self.render_set_draw_color(color)?;
+        let target = self.target_surface()?;
+        for point in points {
+            target.blend_pixel(point.x, point.y, color);
+        }
+        Ok(())
+    }
+
+    fn render_draw_lines(&mut self, points: &[Point], color: Color) -> Result {
+        self.render_set_draw_color(color)?;
+        let target = self.target_surface()?;
+        // pixels shared by neighbouring lines are blended only once
+        let mut pixels: Vec<Point> = points
+            .windows(2)
+            .flat_map(|pair| line_pixels(pair[0], pair[1]))
+            .collect();
+        pixels.sort_by_key(|p| (p.y, p.x));
+        pixels.dedup();
+        for pixel in pixels {
+            target.blend_pixel(pixel.x, pixel.y, color);
+        }
+        Ok(())
+    }
+
+    fn render_geometry(&mut self, vertices: &[Vertex]) -> Result {
+        let target = self.target_surface()?;
+        for triangle in vertices.chunks_exact(3) {
+            fill_triangle(target, [triangle[0], triangle[1], triangle[2]]);
+        }
+        Ok(())
+    }
+
+    fn render_font_glyph(&mut self, font: FontId, glyph: char, origin: Point) -> Result {
+        let font = self.font(font)?;
+        let (metrics, coverage) = font.font.rasterize(glyph, font.px);
+        let ascent = font.ascent;
+        let height = font.height as i32;
+        let cell_width =
+            (metrics.advance_width.round() as i32).max(metrics.xmin + metrics.width as i32);
+        let top = ascent - metrics.ymin - metrics.height as i32;
+
+        // like the SDL2 backend, the whole glyph cell is overwritten without blending.
+        let target = self.target_surface()?;
+        for y in 0..height {
+            for x in 0..cell_width {
+                let gx = x - metrics.xmin;
+                let gy = y - top;
+                let alpha = if gx >= 0
+                    && gy >= 0
+                    && (gx as usize) < metrics.width
+                    && (gy as usize) < metrics.height
+                {
+                    coverage[gy as usize * metrics.width + gx as usize]
+                } else {
+                    0
+                };
+                target.set_pixel(origin.x + x, origin.y + y, Color::new(255, 255, 255, alpha));
+            }
+        }
+        Ok(())
+    }
+
+    fn render_output_size(&mut self) -> Result<(u32, u32)> {
+        match self.target {
+            Some(id) => self.texture(id).map(|t| (t.width(), t.height())),
+            None => Ok((self.window_width, self.window_height)),
+        }
+    }
+
+    fn render_read_pixels(&mut self, rect: Option<Rect>) -> Result<Vec<u8>> {
+        // the window is read the way it would be presented, scaled into its viewport
+        let window;
+        let surface = match self.target {
+            Some(id) => self.texture(id)?,
+            None => {
+                window =
+                    self.screen
+                        .scaled(self.viewport(), self.window_width, self.window_height)?;
+                &window
+            }
+        };
+        let rect = rect.unwrap_or(Rect::new(0, 0, surface.width(), surface.height()));
+        if !rect.is_within(surface.width(), surface.height()) {
+            return Err(Error::OutOfBounds);
+        }
+        Ok(surface.read(rect))
+    }
+
+    fn events_pump(&mut self, events: &mut Vec<Event>) {
+        let pushed: Vec<Event> = self.shared.borrow_mut().events.drain(..).collect();
+        for event in pushed {
+            // pushed resizes stand for the user resizing the window
+            if let Event::WindowResized(size) = event {
+                self.window_width = size.width;
+                self.window_height = size.height;
+                // a screen too large to allocate keeps the old one, presenting reports the error
+                let _ = self.resize_screen();
+            }
+            events.push(event);
+        }
+    }
+
+    fn input_mouse_position(&mut self) -> Result<(i32, i32)> {
+        Ok(self.shared.borrow().mouse_position)
+    }
+
+    fn system_get_millis(&mut self) -> Result<u64> {
+        Ok(self.millis)
+    }
+
+    fn system_log(&self, s: &str) {
+        println!("{}", s);
+    }
+}
+
+/// Software equivalent of `SDL_RenderCopyEx` with nearest-neighbour sampling.
+fn copy_surface(src: &Surface, dst: &mut Surface, options: CopyTextureOptions) {
+    let src_rect = options
+        .src
+        .unwrap_or(Rect::new(0, 0, src.width(), src.height()));
+    let dest = options
+        .dest
+        .unwrap_or(Rect::new(0, 0, dst.width(), dst.height()));
+    if src_rect.w == 0 || src_rect.h == 0 || dest.w == 0 || dest.h == 0 {
+        return;
+    }
+
+    let (dest_w, dest_h) = (dest.w as f64, dest.h as f64);
+    let (cx, cy) = options
+        .center
+        .map_or((dest_w / 2.0, dest_h / 2.0), |c| (c.x as f64, c.y as f64));
+    let (pivot_x, pivot_y) = (dest.x as f64 + cx, dest.y as f64 + cy);
+    let (sin, cos) = options.angle.to_radians().sin_cos();
+
+    // bounding box of the rotated destination rect
+    let corners = [(0.0, 0.0), (dest_w, 0.0), (0.0, dest_h), (dest_w, dest_h)];
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+    for (x, y) in corners {
+        let (x, y) = (x - cx, y - cy);
+        let rx = pivot_x + x * cos - y * sin;
+        let ry = pivot_y + x * sin + y * cos;
+        min_x = min_x.min(rx);
+        min_y = min_y.min(ry);
+        max_x = max_x.max(rx);
+        max_y = max_y.max(ry);
+    }
+    let bounds = Rect::new(
+        min_x.floor() as i32,
+        min_y.floor() as i32,
+        (max_x.ceil() - min_x.floor()) as u32,
+        (max_y.ceil() - min_y.floor()) as u32,
+    );
+    let Some((x0, y0, x1, y1)) = dst.clip(Some(bounds)) else {
+        return;
+    };
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            // rotate the pixel center back into the unrotated destination rect
+            let (px, py) = (x as f64 + 0.5 - pivot_x, y as f64 + 0.5 - pivot_y);
+            let lx = cx + px * cos + py * sin;
+            let ly = cy - px * sin + py * cos;
+            if lx < 0.0 || ly < 0.0 || lx >= dest_w || ly >= dest_h {
+                continue;
+            }
+
+            let mut u = ((lx * src_rect.w as f64 / dest_w) as u32).min(src_rect.w - 1);
+            let mut v = ((ly * src_rect.h as f64 / dest_h) as u32).min(src_rect.h - 1);
+            if options.flip_h {
+                u = src_rect.w - 1 - u;
+            }
+            if options.flip_v {
+                v = src_rect.h - 1 - v;
+            }
+
+            let sx = src_rect.x + u as i32;
+            let sy = src_rect.y + v as i32;
+            if sx < 0 || sy < 0 || sx as u32 >= src.width() || sy as u32 >= src.height() {
+                continue;
+            }
+
+            let mut color = src.pixel(sx as u32, sy as u32);
+            if let Some(m) = options.color_mod {
+                color.r = (color.r as u32 * m.r as u32 / 255) as u8;
+                color.g = (color.g as u32 * m.g as u32 / 255) as u8;
+                color.b = (color.b as u32 * m.b as u32 / 255) as u8;
+                color.a = (color.a as u32 * m.a as u32 / 255) as u8;
+            }
+            dst.blend_pixel_with(x, y, color, options.blend_mode);
+        }
+    }
+}
+
+/// The pixels of a line from `from` to `to`, both included.
+fn line_pixels(from: Point, to: Point) -> Vec<Point> {
+    let (dx, dy) = ((to.x - from.x).abs(), -(to.y - from.y).abs());
+    let step_x = if from.x < to.x { 1 } else { -1 };
+    let step_y = if from.y < to.y { 1 } else { -1 };
+    let (mut x, mut y) = (from.x, from.y);
+    let mut error = dx + dy;
+    let mut pixels = Vec::with_capacity((dx - dy) as usize + 1);
+    loop {
+        pixels.push(Point::new(x, y));
+        if x == to.x && y == to.y {
+            return pixels;
+        }
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+/// Fills the pixels whose centers are inside the triangle, blending the colors of its corners
+/// across it. Pixels centered on an edge are only filled for top and left edges, so triangles
+/// sharing an edge never both fill the same pixel.
+fn fill_triangle(dst: &mut Surface, [a, mut b, mut c]: [Vertex; 3]) {
+    let edge = |p: &Vertex, q: &Vertex, x: f32, y: f32| {
+        (q.x - p.x) * (y - p.y) - (q.y - p.y) * (x - p.x)
+    };
+    let mut area = edge(&a, &b, c.x, c.y);
+    if area == 0.0 {
+        return;
+    }
+    if area < 0.0 {
+        std::mem::swap(&mut b, &mut c);
+        area = -area;
+    }
+    let top_left = |p: &Vertex, q: &Vertex| (q.y == p.y && q.x > p.x) || q.y < p.y;
+    let included = [top_left(&b, &c), top_left(&c, &a), top_left(&a, &b)];
+
+    let min_x = a.x.min(b.x).min(c.x).floor();
+    let min_y = a.y.min(b.y).min(c.y).floor();
+    let max_x = a.x.max(b.x).max(c.x).ceil();
+    let max_y = a.y.max(b.y).max(c.y).ceil();
+    let bounds = Rect::new(
+        min_x as i32,
+        min_y as i32,
+        (max_x - min_x) as u32,
+        (max_y - min_y) as u32,
+    );
+    let Some((x0, y0, x1, y1)) = dst.clip(Some(bounds)) else {
+        return;
+    };
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let weights = [edge(&b, &c, px, py), edge(&c, &a, px, py), edge(&a, &b, px, py)];
+            if weights
+                .iter()
+                .zip(included)
+                .any(|(&w, included)| w < 0.0 || (w == 0.0 && !included))
+            {
+                continue;
+            }
+            let mix = |channel: fn(&Color) -> u8| {
+                let value = weights[0] * channel(&a.color) as f32
+                    + weights[1] * channel(&b.color) as f32
+                    + weights[2] * channel(&c.color) as f32;
+                (value / area).round().clamp(0.0, 255.0) as u8
+            };
+            let color = Color::new(mix(|c| c.r), mix(|c| c.g), mix(|c| c.b), mix(|c| c.a));
+            dst.blend_pixel(x, y, color);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{BackendSoft, Surface};
+use panko::canvas::Canvas;
+use panko::{run_event_loop, Application, Context, Error, Result};
+use std::path::{Path, PathBuf};
+
+/// When set to anything but `0`, `Snapshot::run` overwrites the goldens instead of comparing.
+pub const UPDATE_GOLDENS_VAR: &str = "PANKO_UPDATE_GOLDENS";
+
+const DEFAULT_DIRECTORY: &str = "tests/goldens";
+
+/// Renders an `Application` on a `BackendSoft` for a number of frames and compares the last
+/// presented frame against a golden PNG stored at `<directory>/<name>.png`.
+///
+/// On a mismatch the rendered frame is written to `<name>.actual.png` and an image with the
+/// differing pixels painted red to `<name>.diff.png`, both next to the golden.
+pub struct Snapshot {
+    name: String,
+    width: u32,
+    height: u32,
+    frames: u64,
+    tolerance: u8,
+    directory: PathBuf,
+}
+
+impl Snapshot {
+    pub fn new(name: &str, width: u32, height: u32) -> Self {
+        Self {
+            name: String::from(name),
+            width,
+            height,
+            frames: 1,
+            tolerance: 0,
+            directory: PathBuf::from(DEFAULT_DIRECTORY),
+        }
+    }
+
+    /// Number of frames to run before capturing. Defaults to 1.
+    pub fn set_frames(&mut self, frames: u64) {
+        self.frames = frames.max(1);
+    }
+
+    /// Maximum difference allowed per color channel. Defaults to 0.
+    pub fn set_tolerance(&mut self, tolerance: u8) {
+        self.tolerance = tolerance;
+    }
+
+    /// Directory the goldens live in. Defaults to `tests/goldens`, relative to the working
+    /// directory, which for `cargo test` is the package root.
+    pub fn set_directory(&mut self, directory: impl Into<PathBuf>) {
+        self.directory = directory.into();
+    }
+
+    pub fn run<T: Application>(&self, load: impl FnOnce(&mut Context) -> Result<T>) -> Result {
+        let frame = self.render(load)?;
+        self.compare(&frame)
+    }
+
+    /// Runs the application and returns the last presented frame.
+    pub fn render<T: Application>(
+        &self,
+        load: impl FnOnce(&mut Context) -> Result<T>,
+    ) -> Result<Surface> {
+        let backend = BackendSoft::new(self.width, self.height)?;
+        let handle = backend.handle();
+        run_event_loop(backend, |context| {
+            Ok(FrameLimit {
+                app: load(context)?,
+                frames_left: self.frames,
+            })
+        })?;
+        handle
+            .frame()
+            .ok_or(Error::Other(String::from("No frame was presented.")))
+    }
+
+    pub fn compare(&self, frame: &Surface) -> Result {
+        let golden_path = self.path("png");
+
+        if update_goldens() {
+            std::fs::create_dir_all(&self.directory).map_err(|e| Error::Io(e.to_string()))?;
+            return write_png(&golden_path, frame);
+        }
+
+        let Ok(bytes) = std::fs::read(&golden_path) else {
+            return Err(Error::Other(format!(
+                "Golden {} does not exist, run with {}=1 to create it.",
+                golden_path.display(),
+                UPDATE_GOLDENS_VAR
+            )));
+        };
+        let golden = Surface::from_png(&bytes)?;
+
+        let failure = if golden.width() != frame.width() || golden.height() != frame.height() {
+            Some(format!(
+                "Snapshot {} is {}x{} but the golden is {}x{}.",
+                self.name,
+                frame.width(),
+                frame.height(),
+                golden.width(),
+                golden.height()
+            ))
+        } else {
+            let (diff, mismatches) = diff(&golden, frame, self.tolerance);
+            if mismatches > 0 {
+                write_png(&self.path("diff.png"), &diff)?;
+                Some(format!(
+                    "Snapshot {} differs from its golden in {} pixels, see {}.",
+                    self.name,
+                    mismatches,
+                    self.path("diff.png").display()
+                ))
+            } else {
+                None
+            }
+        };
+
+        match failure {
+            Some(message) => {
+                write_png(&self.path("actual.png"), frame)?;
+                Err(Error::Other(message))
+            }
+            None => {
+                // leftovers from a previous failing run
+                let _ = std::fs::remove_file(self.path("actual.png"));
+                let _ = std::fs::remove_file(self.path("diff.png"));
+                Ok(())
+            }
+        }
+    }
+
+    fn path(&self, extension: &str) -> PathBuf {
+        self.directory.join(format!("{}.{}", self.name, extension))
+    }
+}
+
+/// Forwards to the wrapped application and requests a quit once enough frames have run.
+struct FrameLimit<T> {
+    app: T,
+    frames_left: u64,
+}
+
+impl<T: Application> Application for FrameLimit<T> {
+    fn update(&mut self, context: &mut Context, delta_ms: u64) -> Result {
+        self.app.update(context, delta_ms)?;
+        self.frames_left -= 1;
+        if self.frames_left == 0 {
+            context.request_quit();
+        }
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, context: &mut Context, fixed_ms: u64) -> Result {
+        self.app.fixed_update(context, fixed_ms)
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, alpha_secs: f32) -> Result {
+        self.app.draw(canvas, alpha_secs)
+    }
+}
+
+fn update_goldens() -> bool {
+    std::env::var_os(UPDATE_GOLDENS_VAR).is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+fn write_png(path: &Path, surface: &Surface) -> Result {
+    std::fs::write(path, surface.to_png()?).map_err(|e| Error::Io(e.to_string()))
+}
+
+/// Returns an image with the differing pixels in red over a dimmed copy of the golden, and the
+/// number of differing pixels.
+fn diff(golden: &Surface, actual: &Surface, tolerance: u8) -> (Surface, usize) {
+    let mut mismatches = 0;
+    let pixels = golden
+        .pixels()
+        .chunks_exact(4)
+        .zip(actual.pixels().chunks_exact(4))
+        .flat_map(|(g, a)| {
+            if g.iter().zip(a).any(|(g, a)| g.abs_diff(*a) > tolerance) {
+                mismatches += 1;
+                [255, 0, 0, 255]
+            } else {
+                let luma = ((g[0] as u32 + g[1] as u32 + g[2] as u32) / 9) as u8;
+                [luma, luma, luma, 255]
+            }
+        })
+        .collect();
+    let diff = Surface::from_pixels(golden.width(), golden.height(), pixels).unwrap();
+    (diff, mismatches)
+}
@@ -0,0 +1,238 @@
+use panko::image::Image;
+use panko::types::*;
+use panko::{Error, Result};
+
+/// An RGBA8 pixel buffer, stored row by row with 4 bytes per pixel.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Surface {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    /// Drawing outside of this rect is discarded. Clearing and writing pixels ignore it.
+    clip: Option<Rect>,
+}
+
+impl Surface {
+    pub fn new(width: u32, height: u32) -> Result<Self> {
+        Ok(Self {
+            width,
+            height,
+            pixels: vec![0; byte_len(width, height)?],
+            clip: None,
+        })
+    }
+
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<u8>) -> Result<Self> {
+        let expected = byte_len(width, height)?;
+        if pixels.len() != expected {
+            return Err(Error::PixelBufferSize {
+                expected,
+                actual: pixels.len(),
+            });
+        }
+        Ok(Self {
+            width,
+            height,
+            pixels,
+            clip: None,
+        })
+    }
+
+    pub fn from_png(bytes: &[u8]) -> Result<Self> {
+        let Image {
+            width,
+            height,
+            pixels,
+        } = Image::decode_png(bytes)?;
+        Self::from_pixels(width, height, pixels)
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>> {
+        Image {
+            width: self.width,
+            height: self.height,
+            pixels: self.pixels.clone(),
+        }
+        .encode_png()
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[inline]
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        let i = self.index(x, y);
+        Color::new(
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        )
+    }
+
+    pub(crate) fn set_pixel(&mut self, x: i32, y: i32, color: Color) {
+        if !self.contains(x, y) {
+            return;
+        }
+        let i = self.index(x as u32, y as u32);
+        self.pixels[i..i + 4].copy_from_slice(&[color.r, color.g, color.b, color.a]);
+    }
+
+    /// Blends `color` over the pixel at `(x, y)` the same way `SDL_BLENDMODE_BLEND` does.
+    pub(crate) fn blend_pixel(&mut self, x: i32, y: i32, color: Color) {
+        if !self.contains(x, y) {
+            return;
+        }
+        let i = self.index(x as u32, y as u32);
+        let src_a = color.a as u32;
+        let inv_a = 255 - src_a;
+        let dst = &mut self.pixels[i..i + 4];
+        dst[0] = ((color.r as u32 * src_a + dst[0] as u32 * inv_a) / 255) as u8;
+        dst[1] = ((color.g as u32 * src_a + dst[1] as u32 * inv_a) / 255) as u8;
+        dst[2] = ((color.b as u32 * src_a + dst[2] as u32 * inv_a) / 255) as u8;
+        dst[3] = (src_a + dst[3] as u32 * inv_a / 255) as u8;
+    }
+
+    /// Combines `color` with the pixel at `(x, y)` the way SDL does for `mode`.
+    pub(crate) fn blend_pixel_with(&mut self, x: i32, y: i32, color: Color, mode: BlendMode) {
+        if mode == BlendMode::Alpha {
+            self.blend_pixel(x, y, color);
+            return;
+        }
+        if !self.contains(x, y) {
+            return;
+        }
+        let i = self.index(x as u32, y as u32);
+        let dst = &mut self.pixels[i..i + 4];
+        let src = [color.r as u32, color.g as u32, color.b as u32];
+        let src_a = color.a as u32;
+        let inv_a = 255 - src_a;
+        for (dst, src) in dst[..3].iter_mut().zip(src) {
+            let d = *dst as u32;
+            let value = match mode {
+                BlendMode::None => src,
+                BlendMode::Additive => d + src * src_a / 255,
+                BlendMode::Multiply => (src * d + d * inv_a) / 255,
+                BlendMode::Modulate => src * d / 255,
+                BlendMode::Premultiplied => src + d * inv_a / 255,
+                BlendMode::Alpha => unreachable!(),
+            };
+            *dst = value.min(255) as u8;
+        }
+        dst[3] = match mode {
+            BlendMode::None => src_a,
+            BlendMode::Premultiplied => (src_a + dst[3] as u32 * inv_a / 255).min(255),
+            _ => dst[3] as u32,
+        } as u8;
+    }
+
+    /// Copies RGBA8 rows into `rect`, which must be inside the surface.
+    pub(crate) fn write(&mut self, rect: Rect, pixels: &[u8]) {
+        let row_len = rect.w as usize * 4;
+        if row_len == 0 {
+            return;
+        }
+        for (row, src) in pixels.chunks_exact(row_len).enumerate() {
+            let start = ((rect.y as usize + row) * self.width as usize + rect.x as usize) * 4;
+            self.pixels[start..start + row_len].copy_from_slice(src);
+        }
+    }
+
+    /// Copies `rect`, which must be inside the surface, out as RGBA8 rows.
+    pub(crate) fn read(&self, rect: Rect) -> Vec<u8> {
+        let row_len = rect.w as usize * 4;
+        let mut pixels = Vec::with_capacity(row_len * rect.h as usize);
+        for row in 0..rect.h as usize {
+            let start = ((rect.y as usize + row) * self.width as usize + rect.x as usize) * 4;
+            pixels.extend_from_slice(&self.pixels[start..start + row_len]);
+        }
+        pixels
+    }
+
+    pub(crate) fn fill(&mut self, color: Color) {
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+
+    pub(crate) fn set_clip(&mut self, clip: Option<Rect>) {
+        self.clip = clip;
+    }
+
+    pub(crate) fn clip_rect(&self) -> Option<Rect> {
+        self.clip
+    }
+
+    /// Returns the rect clipped to the bounds and clip rect of the surface, or all of it that
+    /// can be drawn to for `None`.
+    pub(crate) fn clip(&self, rect: Option<Rect>) -> Option<(i32, i32, i32, i32)> {
+        let (mut x0, mut y0) = (0, 0);
+        let (mut x1, mut y1) = (self.width as i32, self.height as i32);
+        for rect in [rect, self.clip].into_iter().flatten() {
+            x0 = x0.max(rect.x);
+            y0 = y0.max(rect.y);
+            x1 = x1.min(rect.x + rect.w as i32);
+            y1 = y1.min(rect.y + rect.h as i32);
+        }
+        if x0 >= x1 || y0 >= y1 {
+            return None;
+        }
+        Some((x0, y0, x1, y1))
+    }
+
+    /// Nearest-neighbour scales this surface into a new surface of the given size.
+    pub(crate) fn scaled(&self, viewport: Rect, width: u32, height: u32) -> Result<Surface> {
+        let mut out = Surface::new(width, height)?;
+        if self.width == 0 || self.height == 0 {
+            return Ok(out);
+        }
+        let Some((x0, y0, x1, y1)) = out.clip(Some(viewport)) else {
+            return Ok(out);
+        };
+        for y in y0..y1 {
+            let src_y = (y - viewport.y) as u64 * self.height as u64 / viewport.h as u64;
+            for x in x0..x1 {
+                let src_x = (x - viewport.x) as u64 * self.width as u64 / viewport.w as u64;
+                out.set_pixel(x, y, self.pixel(src_x as u32, src_y as u32));
+            }
+        }
+        Ok(out)
+    }
+
+    #[inline]
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let clipped = self.clip.is_some_and(|clip| {
+            x < clip.x || y < clip.y || x >= clip.x + clip.w as i32 || y >= clip.y + clip.h as i32
+        });
+        x >= 0 && y >= 0 && (x as u32) < self.width && (y as u32) < self.height && !clipped
+    }
+
+    #[inline]
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * 4
+    }
+}
+
+/// The number of bytes of a `width` by `height` surface, or an error if it does not fit in memory.
+fn byte_len(width: u32, height: u32) -> Result<usize> {
+    (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|len| len.checked_mul(4))
+        .ok_or_else(|| {
+            Error::Backend(format!(
+                "Surface of {}x{} pixels is too large.",
+                width, height
+            ))
+        })
+}
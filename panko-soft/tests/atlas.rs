@@ -0,0 +1,58 @@
+use panko::atlas::AtlasRegion;
+use panko::canvas::Canvas;
+use panko::types::*;
+use panko::{Application, Context, Result};
+use panko_soft::snapshot::Snapshot;
+
+struct Sprites(Vec<AtlasRegion>);
+
+impl Application for Sprites {
+    fn update(&mut self, _context: &mut Context, _delta_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, _context: &mut Context, _fixed_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, _alpha_secs: f32) -> Result {
+        canvas.clear(Color::BLACK)?;
+        for (i, region) in self.0.iter().enumerate() {
+            let dest = Rect::new(i as i32 * 2, 0, region.width(), region.height());
+            canvas.copy_texture(
+                region,
+                CopyTextureOptions {
+                    dest: Some(dest),
+                    ..Default::default()
+                },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn solid(color: Color) -> Vec<u8> {
+    [color.r, color.g, color.b, color.a].repeat(4)
+}
+
+#[test]
+fn packed_images_draw_their_own_pixels() {
+    let frame = Snapshot::new("atlas", 6, 2)
+        .render(|context| {
+            let mut atlas = context.create_atlas(8, 8);
+            let mut regions = Vec::new();
+            for color in [Color::RED, Color::GREEN, Color::WHITE] {
+                regions.push(atlas.add_pixels(2, 2, PixelFormat::Rgba8, &solid(color))?);
+            }
+            assert_eq!(atlas.page_count(), 1);
+            Ok(Sprites(regions))
+        })
+        .unwrap();
+
+    assert_eq!(frame.pixel(0, 0), Color::RED);
+    assert_eq!(frame.pixel(1, 1), Color::RED);
+    assert_eq!(frame.pixel(2, 0), Color::GREEN);
+    assert_eq!(frame.pixel(3, 1), Color::GREEN);
+    assert_eq!(frame.pixel(4, 0), Color::WHITE);
+    assert_eq!(frame.pixel(5, 1), Color::WHITE);
+}
@@ -0,0 +1,112 @@
+use panko::canvas::Canvas;
+use panko::texture::TextureSource;
+use panko::types::*;
+use panko::{Application, Context, Result};
+use panko_soft::snapshot::Snapshot;
+
+/// Copies a 1x1 texture over a background with the given options.
+struct Copy<T> {
+    texture: T,
+    background: Color,
+    options: CopyTextureOptions,
+}
+
+impl<T: TextureSource> Application for Copy<T> {
+    fn update(&mut self, _context: &mut Context, _delta_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, _context: &mut Context, _fixed_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, _alpha_secs: f32) -> Result {
+        canvas.clear(Color::new(0, 0, 0, 255))?;
+        canvas.fill_rect(None, self.background)?;
+        canvas.copy_texture(&self.texture, self.options)
+    }
+}
+
+fn blended(color: Color, background: Color, options: CopyTextureOptions) -> Color {
+    Snapshot::new("blend", 1, 1)
+        .render(|context| {
+            let pixels = [color.r, color.g, color.b, color.a];
+            Ok(Copy {
+                texture: context.create_texture_from_pixels(1, 1, PixelFormat::Rgba8, &pixels)?,
+                background,
+                options,
+            })
+        })
+        .unwrap()
+        .pixel(0, 0)
+}
+
+fn with_mode(blend_mode: BlendMode) -> CopyTextureOptions {
+    CopyTextureOptions {
+        blend_mode,
+        ..Default::default()
+    }
+}
+
+const GREY: Color = Color::new(100, 100, 100, 255);
+const HALF_RED: Color = Color::new(200, 0, 0, 128);
+
+#[test]
+fn blend_modes_combine_like_sdl() {
+    assert_eq!(
+        blended(HALF_RED, GREY, with_mode(BlendMode::Alpha)),
+        Color::new(150, 49, 49, 255)
+    );
+    assert_eq!(
+        blended(HALF_RED, GREY, with_mode(BlendMode::None)),
+        HALF_RED
+    );
+    assert_eq!(
+        blended(HALF_RED, GREY, with_mode(BlendMode::Additive)),
+        Color::new(200, 100, 100, 255)
+    );
+    assert_eq!(
+        blended(HALF_RED, GREY, with_mode(BlendMode::Modulate)),
+        Color::new(78, 0, 0, 255)
+    );
+    assert_eq!(
+        blended(HALF_RED, GREY, with_mode(BlendMode::Multiply)),
+        Color::new(128, 49, 49, 255)
+    );
+    // already multiplied by its alpha, red at 200 is twice as strong
+    assert_eq!(
+        blended(HALF_RED, GREY, with_mode(BlendMode::Premultiplied)),
+        Color::new(249, 49, 49, 255)
+    );
+}
+
+#[test]
+fn color_mod_alpha_fades_the_copy() {
+    let faded = CopyTextureOptions {
+        color_mod: Some(Color::new(255, 255, 255, 128)),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        blended(Color::WHITE, Color::new(0, 0, 0, 255), faded),
+        Color::new(128, 128, 128, 255)
+    );
+}
+
+#[test]
+fn translucent_images_are_packed_into_atlases_unchanged() {
+    let pixel = Snapshot::new("blend-atlas", 1, 1)
+        .render(|context| {
+            let mut atlas = context.create_atlas(4, 4);
+            let region = atlas.add_pixels(1, 1, PixelFormat::Rgba8, &[200, 0, 0, 128])?;
+            Ok(Copy {
+                texture: region,
+                background: Color::WHITE,
+                options: with_mode(BlendMode::None),
+            })
+        })
+        .unwrap()
+        .pixel(0, 0);
+
+    assert_eq!(pixel, HALF_RED);
+}
@@ -0,0 +1,128 @@
+use panko::backend::Backend;
+use panko::canvas::Canvas;
+use panko::image::Image;
+use panko::texture::Texture;
+use panko::types::*;
+use panko::{Application, Context, Error, Result};
+use panko_soft::snapshot::Snapshot;
+use panko_soft::BackendSoft;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Draws a red square on a black window and a green target, then runs `capture` at the end of
+/// the frame.
+struct Capture {
+    target: Texture,
+    capture: fn(&Canvas, &mut Texture) -> Result<Image>,
+    captured: Rc<RefCell<Vec<Result<Image>>>>,
+}
+
+impl Application for Capture {
+    fn update(&mut self, _context: &mut Context, _delta_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, _context: &mut Context, _fixed_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, _alpha_secs: f32) -> Result {
+        canvas.with_target(Some(&mut self.target), |canvas| canvas.clear(Color::GREEN))?;
+        canvas.clear(Color::BLACK)?;
+        canvas.draw_rect(Some(Rect::new(1, 1, 1, 1)), Color::RED)?;
+        let image = (self.capture)(canvas, &mut self.target);
+        self.captured.borrow_mut().push(image);
+        Ok(())
+    }
+}
+
+fn capture(capture: fn(&Canvas, &mut Texture) -> Result<Image>) -> Result<Image> {
+    let captured = Rc::new(RefCell::new(Vec::new()));
+    Snapshot::new("capture", 3, 2).render(|context| {
+        Ok(Capture {
+            target: context.create_target(2, 1)?,
+            capture,
+            captured: Rc::clone(&captured),
+        })
+    })?;
+    let image = captured.borrow_mut().pop().unwrap();
+    image
+}
+
+fn pixel(image: &Image, x: u32, y: u32) -> Color {
+    let i = ((y * image.width + x) * 4) as usize;
+    let p = &image.pixels[i..i + 4];
+    Color::new(p[0], p[1], p[2], p[3])
+}
+
+#[test]
+fn window_is_captured_as_drawn() {
+    let image = capture(|canvas, _| canvas.capture()).unwrap();
+
+    assert_eq!((image.width, image.height), (3, 2));
+    assert_eq!(pixel(&image, 1, 1), Color::RED);
+    assert_eq!(pixel(&image, 0, 0), Color::BLACK);
+}
+
+#[test]
+fn window_region_is_read() {
+    let image = capture(|canvas, _| canvas.read_pixels(Some(Rect::new(1, 0, 2, 2)))).unwrap();
+
+    assert_eq!((image.width, image.height), (2, 2));
+    assert_eq!(pixel(&image, 0, 1), Color::RED);
+    assert_eq!(pixel(&image, 1, 1), Color::BLACK);
+}
+
+#[test]
+fn regions_outside_the_target_are_rejected() {
+    let result = capture(|canvas, _| canvas.read_pixels(Some(Rect::new(2, 0, 2, 2))));
+    assert_eq!(result, Err(Error::OutOfBounds));
+
+    // far too wide to add up without overflowing
+    let result = capture(|canvas, _| canvas.read_pixels(Some(Rect::new(2, 0, u32::MAX, 1))));
+    assert_eq!(result, Err(Error::OutOfBounds));
+}
+
+#[test]
+fn target_texture_is_captured() {
+    let image = capture(|canvas, target| {
+        let image = canvas.capture_texture(target)?;
+        // drawing goes back to the window afterwards
+        assert_eq!(canvas.capture()?.width, 3);
+        Ok(image)
+    })
+    .unwrap();
+
+    assert_eq!((image.width, image.height), (2, 1));
+    assert_eq!(pixel(&image, 1, 0), Color::GREEN);
+}
+
+#[test]
+fn captured_image_round_trips_through_png() {
+    let image = capture(|canvas, _| canvas.capture()).unwrap();
+    let path = std::env::temp_dir().join("panko-capture.png");
+    image.save_png(&path).unwrap();
+
+    let decoded = Image::decode_png(&std::fs::read(&path).unwrap()).unwrap();
+    assert_eq!(decoded, image);
+}
+
+#[test]
+fn target_can_not_be_copied_onto_itself() {
+    let mut backend = BackendSoft::new(4, 4).unwrap();
+    let target = backend.texture_create(2, 2).unwrap().id;
+    backend.render_set_target(Some(target)).unwrap();
+
+    let copy = CopyTextureOptions::default();
+    assert_eq!(
+        backend.render_copy_texture(target, copy),
+        Err(Error::SourceIsTarget)
+    );
+    assert_eq!(
+        backend.render_copy_texture_batch(target, &[copy, copy]),
+        Err(Error::SourceIsTarget)
+    );
+
+    backend.render_set_target(None).unwrap();
+    assert_eq!(backend.render_copy_texture(target, copy), Ok(()));
+}
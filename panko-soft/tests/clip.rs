@@ -0,0 +1,70 @@
+use panko::canvas::Canvas;
+use panko::texture::Texture;
+use panko::types::*;
+use panko::{Application, Context, Result};
+use panko_soft::snapshot::Snapshot;
+use panko_soft::Surface;
+
+const OPAQUE_BLACK: Color = Color::new(0, 0, 0, 255);
+
+/// Fills the window and copies a red texture over all of it, both clipped to `clip`.
+struct Clipped {
+    red: Texture,
+    clip: Rect,
+}
+
+impl Application for Clipped {
+    fn update(&mut self, _context: &mut Context, _delta_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, _context: &mut Context, _fixed_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, _alpha_secs: f32) -> Result {
+        canvas.clear(OPAQUE_BLACK)?;
+        canvas.with_clip(self.clip, |canvas| {
+            canvas.fill_rect(Some(Rect::new(0, 0, 4, 8)), Color::WHITE)?;
+            canvas.copy_texture(
+                &self.red,
+                CopyTextureOptions {
+                    dest: Some(Rect::new(4, 0, 4, 8)),
+                    ..Default::default()
+                },
+            )
+        })?;
+        canvas.fill_rect(Some(Rect::new(0, 7, 8, 1)), Color::GREEN)
+    }
+}
+
+fn render(clip: Rect) -> Surface {
+    Snapshot::new("clip", 8, 8)
+        .render(|context| {
+            let red =
+                context.create_texture_from_pixels(1, 1, PixelFormat::Rgba8, &[255, 0, 0, 255])?;
+            Ok(Clipped { red, clip })
+        })
+        .unwrap()
+}
+
+#[test]
+fn drawing_outside_the_clip_is_discarded() {
+    let frame = render(Rect::new(2, 2, 4, 4));
+
+    assert_eq!(frame.pixel(1, 3), OPAQUE_BLACK);
+    assert_eq!(frame.pixel(2, 2), Color::WHITE);
+    assert_eq!(frame.pixel(5, 5), Color::new(255, 0, 0, 255));
+    assert_eq!(frame.pixel(6, 3), OPAQUE_BLACK);
+    assert_eq!(frame.pixel(3, 6), OPAQUE_BLACK);
+    // drawing after the clip is popped reaches the whole window again
+    assert_eq!(frame.pixel(0, 7), Color::GREEN);
+}
+
+#[test]
+fn an_empty_clip_discards_everything() {
+    let frame = render(Rect::new(3, 3, 0, 0));
+
+    assert_eq!(frame.pixel(3, 3), OPAQUE_BLACK);
+    assert_eq!(frame.pixel(5, 3), OPAQUE_BLACK);
+}
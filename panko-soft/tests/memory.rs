@@ -0,0 +1,63 @@
+use panko::canvas::Canvas;
+use panko::texture::Texture;
+use panko::types::*;
+use panko::{Application, Context, Error, Result};
+use panko_soft::snapshot::Snapshot;
+use panko_soft::Surface;
+
+struct Sprite(Texture);
+
+impl Application for Sprite {
+    fn update(&mut self, _context: &mut Context, _delta_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, _context: &mut Context, _fixed_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, _alpha_secs: f32) -> Result {
+        canvas.clear(Color::BLACK)?;
+        canvas.copy_texture(&self.0, CopyTextureOptions::default())
+    }
+}
+
+#[test]
+fn texture_is_decoded_from_memory() {
+    let mut pixels = Vec::new();
+    for color in [Color::RED, Color::GREEN, Color::WHITE, Color::RED] {
+        pixels.extend([color.r, color.g, color.b, color.a]);
+    }
+    let png = Surface::from_pixels(2, 2, pixels).unwrap().to_png().unwrap();
+
+    let frame = Snapshot::new("memory", 2, 2)
+        .render(|context| {
+            let texture = context.load_texture_from_memory(&png)?;
+            assert_eq!((texture.width(), texture.height()), (2, 2));
+            Ok(Sprite(texture))
+        })
+        .unwrap();
+
+    assert_eq!(frame.pixel(0, 0), Color::RED);
+    assert_eq!(frame.pixel(1, 0), Color::GREEN);
+    assert_eq!(frame.pixel(0, 1), Color::WHITE);
+}
+
+#[test]
+fn undecodable_bytes_are_rejected() {
+    let result = Snapshot::new("garbage", 2, 2).render(|context| {
+        let texture = context.load_texture_from_memory(b"not a png")?;
+        Ok(Sprite(texture))
+    });
+
+    assert!(matches!(result, Err(Error::Decode(_))));
+}
+
+#[test]
+fn surfaces_too_large_to_address_are_rejected() {
+    let too_large = |result: Result<Surface>| matches!(result, Err(Error::Backend(_)));
+
+    let side = u32::MAX;
+    assert!(too_large(Surface::new(side, side)));
+    assert!(too_large(Surface::from_pixels(side, side, Vec::new())));
+}
@@ -0,0 +1,42 @@
+use panko::canvas::Canvas;
+use panko::types::*;
+use panko::{Application, Context, Result};
+use panko_soft::snapshot::Snapshot;
+
+/// A red pixel in the top-left corner of a white 4x3 screen.
+struct Corner;
+
+impl Application for Corner {
+    fn update(&mut self, _context: &mut Context, _delta_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, _context: &mut Context, _fixed_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, _alpha_secs: f32) -> Result {
+        canvas.clear(Color::WHITE)?;
+        canvas.draw_rect(Some(Rect::new(0, 0, 1, 1)), Color::RED)
+    }
+}
+
+#[test]
+fn integer_scaling_keeps_pixels_square_and_centered() {
+    let frame = Snapshot::new("resolution", 11, 8)
+        .render(|context| {
+            context.set_resolution(ResolutionPolicy::IntegerScale(Dimensions::new(4, 3)))?;
+            Ok(Corner)
+        })
+        .unwrap();
+
+    // doubled into (1, 1, 8, 6), with a black bar around it
+    for (x, y) in [(1, 1), (2, 1), (1, 2), (2, 2)] {
+        assert_eq!(frame.pixel(x, y), Color::RED);
+    }
+    assert_eq!(frame.pixel(3, 3), Color::WHITE);
+    assert_eq!(frame.pixel(8, 6), Color::WHITE);
+    for (x, y) in [(0, 0), (9, 1), (10, 7), (4, 7)] {
+        assert_eq!(frame.pixel(x, y), Color::BLACK);
+    }
+}
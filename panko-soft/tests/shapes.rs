@@ -0,0 +1,132 @@
+use panko::canvas::Canvas;
+use panko::types::*;
+use panko::{Application, Context, Result};
+use panko_soft::snapshot::Snapshot;
+use panko_soft::Surface;
+
+const OPAQUE_BLACK: Color = Color::new(0, 0, 0, 255);
+
+/// Draws `draw` over an opaque black window.
+struct Shapes(fn(&Canvas) -> Result);
+
+impl Application for Shapes {
+    fn update(&mut self, _context: &mut Context, _delta_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, _context: &mut Context, _fixed_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, _alpha_secs: f32) -> Result {
+        canvas.clear(OPAQUE_BLACK)?;
+        (self.0)(canvas)
+    }
+}
+
+fn render(width: u32, height: u32, draw: fn(&Canvas) -> Result) -> Surface {
+    Snapshot::new("shapes", width, height)
+        .render(|_| Ok(Shapes(draw)))
+        .unwrap()
+}
+
+/// The pixels of the frame that are not black, as rows of `#` and `.`.
+fn coverage(frame: &Surface) -> Vec<String> {
+    (0..frame.height())
+        .map(|y| {
+            (0..frame.width())
+                .map(|x| {
+                    if frame.pixel(x, y) == OPAQUE_BLACK {
+                        '.'
+                    } else {
+                        '#'
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn filled_circles_are_round_and_centered_on_their_pixel() {
+    let frame = render(7, 7, |canvas| {
+        canvas.fill_circle(Point::new(3, 3), 3.5, Color::WHITE)
+    });
+
+    assert_eq!(
+        coverage(&frame),
+        ["..###..", ".#####.", "#######", "#######", "#######", ".#####.", "..###..",]
+    );
+}
+
+#[test]
+fn thick_lines_cover_their_thickness() {
+    let frame = render(6, 5, |canvas| {
+        canvas.draw_line(Point::new(1, 2), Point::new(4, 2), 3.0, Color::WHITE)
+    });
+
+    assert_eq!(
+        coverage(&frame),
+        ["......", ".####.", ".####.", ".####.", "......"]
+    );
+}
+
+#[test]
+fn polygon_outlines_close_on_their_first_point() {
+    let frame = render(5, 4, |canvas| {
+        let points = [Point::new(0, 0), Point::new(4, 0), Point::new(4, 3)];
+        canvas.draw_polygon(&points, Color::WHITE)
+    });
+
+    assert_eq!(coverage(&frame), ["#####", ".##.#", "...##", "....#"]);
+}
+
+#[test]
+fn concave_polygons_fill_only_their_inside() {
+    let frame = render(4, 4, |canvas| {
+        let points = [
+            Point::new(0, 0),
+            Point::new(2, 0),
+            Point::new(2, 2),
+            Point::new(4, 2),
+            Point::new(4, 4),
+            Point::new(0, 4),
+        ];
+        canvas.fill_polygon(&points, Color::WHITE)
+    });
+
+    assert_eq!(coverage(&frame), ["##..", "##..", "####", "####"]);
+}
+
+#[test]
+fn triangle_colors_blend_between_the_corners() {
+    let frame = render(8, 8, |canvas| {
+        let red = Color::RED;
+        let green = Color::GREEN;
+        canvas.draw_triangles(&[
+            Vertex::new(0.0, 0.0, red),
+            Vertex::new(8.0, 0.0, green),
+            Vertex::new(0.0, 8.0, red),
+            Vertex::new(8.0, 0.0, green),
+            Vertex::new(8.0, 8.0, green),
+            Vertex::new(0.0, 8.0, red),
+        ])
+    });
+
+    // the two triangles share their diagonal without a gap
+    assert!(coverage(&frame).iter().all(|row| row == "########"));
+    let left = frame.pixel(0, 4);
+    let right = frame.pixel(7, 4);
+    assert!(left.r > 200 && left.g < 40);
+    assert!(right.g > 200 && right.r < 40);
+}
+
+#[test]
+fn translucent_polylines_blend_shared_pixels_once() {
+    let frame = render(3, 3, |canvas| {
+        let points = [Point::new(0, 0), Point::new(2, 0), Point::new(2, 2)];
+        canvas.draw_polyline(&points, 1.0, Color::new(255, 255, 255, 128))
+    });
+
+    assert_eq!(frame.pixel(2, 0), frame.pixel(1, 0));
+}
@@ -0,0 +1,112 @@
+use panko::canvas::Canvas;
+use panko::texture::{Texture, TextureKind};
+use panko::types::*;
+use panko::{Application, Context, Error, Result};
+use panko_soft::snapshot::Snapshot;
+
+/// Draws the texture at the origin after `update` rewrote it.
+struct Streamed {
+    texture: Texture,
+    update: fn(&Texture) -> Result,
+}
+
+impl Application for Streamed {
+    fn update(&mut self, _context: &mut Context, _delta_ms: u64) -> Result {
+        (self.update)(&self.texture)
+    }
+
+    fn fixed_update(&mut self, _context: &mut Context, _fixed_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, _alpha_secs: f32) -> Result {
+        canvas.clear(Color::BLACK)?;
+        canvas.copy_texture(&self.texture, CopyTextureOptions::default())
+    }
+}
+
+fn render(format: PixelFormat, pixels: &'static [u8], update: fn(&Texture) -> Result) -> Result {
+    Snapshot::new("streaming", 2, 2)
+        .render(|context| {
+            let texture = context.create_texture_from_pixels(2, 2, format, pixels)?;
+            assert!(matches!(texture.kind(), TextureKind::Streaming));
+            Ok(Streamed { texture, update })
+        })
+        .map(|_| ())
+}
+
+#[test]
+fn texture_is_created_and_updated_from_pixels() {
+    #[rustfmt::skip]
+    const BGRA: &[u8] = &[
+        0, 0, 255, 255,   0, 255, 0, 255,
+        255, 0, 0, 255,   255, 255, 255, 255,
+    ];
+    let frame = Snapshot::new("streaming", 2, 2)
+        .render(|context| {
+            let texture = context.create_texture_from_pixels(2, 2, PixelFormat::Bgra8, BGRA)?;
+            Ok(Streamed {
+                texture,
+                update: |texture| texture.update(Some(Rect::new(1, 1, 1, 1)), &[0, 0, 0, 255]),
+            })
+        })
+        .unwrap();
+
+    assert_eq!(frame.pixel(0, 0), Color::RED);
+    assert_eq!(frame.pixel(1, 0), Color::GREEN);
+    assert_eq!(frame.pixel(0, 1), Color::new(0, 0, 255, 255));
+    assert_eq!(frame.pixel(1, 1), Color::new(0, 0, 0, 255));
+}
+
+#[test]
+fn rgb_pixels_are_opaque() {
+    const RGB: &[u8] = &[10, 20, 30, 10, 20, 30, 10, 20, 30, 10, 20, 30];
+    let frame = Snapshot::new("streaming", 2, 2)
+        .render(|context| {
+            let texture = context.create_texture_from_pixels(2, 2, PixelFormat::Rgb8, RGB)?;
+            Ok(Streamed {
+                texture,
+                update: |texture| texture.update(None, &[40, 50, 60].repeat(4)),
+            })
+        })
+        .unwrap();
+
+    assert_eq!(frame.pixel(1, 1), Color::new(40, 50, 60, 255));
+}
+
+#[test]
+fn bad_updates_are_rejected() {
+    const RGBA: &[u8] = &[0; 16];
+    assert_eq!(
+        render(PixelFormat::Rgba8, RGBA, |t| t.update(Some(Rect::new(1, 1, 2, 1)), &[0; 8])),
+        Err(Error::OutOfBounds)
+    );
+    assert_eq!(
+        render(PixelFormat::Rgba8, RGBA, |t| t.update(None, &[0; 3])),
+        Err(Error::PixelBufferSize {
+            expected: 16,
+            actual: 3
+        })
+    );
+    assert_eq!(
+        render(PixelFormat::Rgba8, &[0; 15], |_| Ok(())),
+        Err(Error::PixelBufferSize {
+            expected: 16,
+            actual: 15
+        })
+    );
+}
+
+#[test]
+fn only_streaming_textures_can_be_updated() {
+    let result = Snapshot::new("streaming", 2, 2).render(|context| {
+        let target = context.create_target(2, 2)?;
+        target.update(None, &[0; 16])?;
+        Ok(Streamed {
+            texture: target,
+            update: |_| Ok(()),
+        })
+    });
+
+    assert!(matches!(result, Err(Error::NotStreaming)));
+}
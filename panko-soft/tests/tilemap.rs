@@ -0,0 +1,76 @@
+use panko::canvas::Canvas;
+use panko::image::Image;
+use panko::tilemap::Tilemap;
+use panko::types::*;
+use panko::{Application, Context, Result};
+use panko_soft::snapshot::Snapshot;
+
+const FLIP_H: u32 = 0x8000_0000;
+const FLIP_V: u32 = 0x4000_0000;
+const FLIP_D: u32 = 0x2000_0000;
+
+const BLUE: Color = Color::new(0, 0, 255, 255);
+
+struct Map(Tilemap);
+
+impl Application for Map {
+    fn update(&mut self, _context: &mut Context, _delta_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, _context: &mut Context, _fixed_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, _alpha_secs: f32) -> Result {
+        canvas.clear(Color::BLACK)?;
+        self.0.draw(canvas, Rect::new(0, 0, 8, 2))
+    }
+}
+
+/// A single 2x2 tile, red and green on top of blue and white.
+fn tileset() -> Image {
+    let pixels = [Color::RED, Color::GREEN, BLUE, Color::WHITE]
+        .iter()
+        .flat_map(|c| [c.r, c.g, c.b, c.a])
+        .collect();
+    Image {
+        width: 2,
+        height: 2,
+        pixels,
+    }
+}
+
+#[test]
+fn flipped_tiles_match_tiled() {
+    let dir = std::env::temp_dir().join("panko-soft-tilemap");
+    std::fs::create_dir_all(&dir).unwrap();
+    tileset().save_png(dir.join("tiles.png")).unwrap();
+    let data = [
+        1,
+        1 | FLIP_D,
+        1 | FLIP_D | FLIP_H,
+        1 | FLIP_D | FLIP_H | FLIP_V,
+    ];
+    let map = format!(
+        r#"{{ "width": 4, "height": 1, "tilewidth": 2, "tileheight": 2,
+  "tilesets": [{{ "firstgid": 1, "tilewidth": 2, "tileheight": 2, "tilecount": 1, "columns": 1, "image": "tiles.png" }}],
+  "layers": [{{ "type": "tilelayer", "name": "tiles", "width": 4, "height": 1, "data": {:?} }}] }}"#,
+        data
+    );
+    std::fs::write(dir.join("map.json"), map).unwrap();
+
+    let frame = Snapshot::new("tilemap", 8, 2)
+        .render(|context| {
+            context.vfs().mount_directory(&dir);
+            Ok(Map(context.load_tilemap("map.json")?))
+        })
+        .unwrap();
+
+    let tile = |i: u32| [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(x, y)| frame.pixel(i * 2 + x, y));
+    assert_eq!(tile(0), [Color::RED, Color::GREEN, BLUE, Color::WHITE]);
+    // swapping the axes first, then mirroring
+    assert_eq!(tile(1), [Color::RED, BLUE, Color::GREEN, Color::WHITE]);
+    assert_eq!(tile(2), [BLUE, Color::RED, Color::WHITE, Color::GREEN]);
+    assert_eq!(tile(3), [Color::WHITE, Color::GREEN, BLUE, Color::RED]);
+}
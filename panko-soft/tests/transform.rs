@@ -0,0 +1,70 @@
+use panko::canvas::Canvas;
+use panko::texture::Texture;
+use panko::transform::Transform2D;
+use panko::types::*;
+use panko::{Application, Context, Result};
+use panko_soft::snapshot::Snapshot;
+
+const OPAQUE_BLACK: Color = Color::new(0, 0, 0, 255);
+
+/// Draws the same shapes through a transform on the left half of the window, and into a target
+/// copied to the right half.
+struct Halves {
+    target: Texture,
+}
+
+fn shapes(canvas: &Canvas) -> Result {
+    canvas.clear(OPAQUE_BLACK)?;
+    let transform = Transform2D::translation(1.0, 1.0).then(Transform2D::scaling(2.0));
+    canvas.with_transform(transform, |canvas| {
+        canvas.fill_rect(Some(Rect::new(0, 0, 1, 1)), Color::WHITE)?;
+        canvas.fill_polygon(
+            &[Point::new(2, 1), Point::new(3, 1), Point::new(3, 2)],
+            Color::RED,
+        )
+    })
+}
+
+impl Application for Halves {
+    fn update(&mut self, _context: &mut Context, _delta_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, _context: &mut Context, _fixed_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, _alpha_secs: f32) -> Result {
+        shapes(canvas)?;
+        canvas.with_target(Some(&mut self.target), shapes)?;
+        canvas.copy_texture(
+            &self.target,
+            CopyTextureOptions {
+                dest: Some(Rect::new(8, 0, 8, 8)),
+                ..Default::default()
+            },
+        )
+    }
+}
+
+#[test]
+fn targets_are_transformed_like_the_screen() {
+    let frame = Snapshot::new("transform", 16, 8)
+        .render(|context| {
+            Ok(Halves {
+                target: context.create_target(8, 8)?,
+            })
+        })
+        .unwrap();
+
+    for y in 0..8 {
+        for x in 0..8 {
+            assert_eq!(frame.pixel(x, y), frame.pixel(x + 8, y), "at ({x}, {y})");
+        }
+    }
+    assert_eq!(frame.pixel(1, 1), Color::WHITE);
+    assert_eq!(frame.pixel(2, 2), Color::WHITE);
+    assert_eq!(frame.pixel(3, 3), OPAQUE_BLACK);
+    assert_eq!(frame.pixel(6, 4), Color::new(255, 0, 0, 255));
+    assert_eq!(frame.pixel(5, 4), OPAQUE_BLACK);
+}
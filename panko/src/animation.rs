@@ -0,0 +1,425 @@
+use crate::assets::Handle;
+use crate::canvas::Canvas;
+use crate::texture::Texture;
+use crate::types::{CopyTextureOptions, Point, Rect};
+use crate::{Error, Result};
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+/// The order frames of a tag are played in, as named by Aseprite.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum AnimationDirection {
+    #[default]
+    Forward,
+    Reverse,
+    /// Forward, then back, without repeating the frames at either end.
+    PingPong,
+    /// Like `PingPong`, but starting backwards from the last frame.
+    PingPongReverse,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AnimationFrame {
+    /// Where the frame is in the sheet texture.
+    pub rect: Rect,
+    /// Where the frame is drawn relative to the sprite position, for sheets exported with
+    /// trimmed frames.
+    pub offset: Point,
+    pub duration_ms: u32,
+}
+
+/// A named range of frames, inclusive on both ends.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnimationTag {
+    pub name: String,
+    pub from: usize,
+    pub to: usize,
+    pub direction: AnimationDirection,
+    /// How many times the tag is played before stopping on its last frame, or `None` to loop
+    /// forever. Each way of a ping-pong counts once.
+    pub repeat: Option<u32>,
+}
+
+/// The frames and tags of a sprite sheet, shared by every `AnimatedSprite` playing them.
+pub struct Animation {
+    texture: Handle<Texture>,
+    frames: Vec<AnimationFrame>,
+    tags: Vec<AnimationTag>,
+}
+
+impl Animation {
+    pub fn new(
+        texture: Handle<Texture>,
+        frames: Vec<AnimationFrame>,
+        tags: Vec<AnimationTag>,
+    ) -> Self {
+        Self {
+            texture,
+            frames,
+            tags,
+        }
+    }
+
+    /// Reads the frames and tags of a sheet exported by Aseprite as JSON, in either the hash or
+    /// the array layout. The sheet itself is `texture`.
+    pub fn from_aseprite(texture: Handle<Texture>, json: &[u8]) -> Result<Self> {
+        let sheet = AsepriteSheet::parse(json)?;
+        Ok(Self::new(texture, sheet.frames, sheet.tags))
+    }
+
+    #[inline]
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    #[inline]
+    pub fn frames(&self) -> &[AnimationFrame] {
+        &self.frames
+    }
+
+    #[inline]
+    pub fn tags(&self) -> &[AnimationTag] {
+        &self.tags
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&AnimationTag> {
+        self.tags.iter().find(|t| t.name == name)
+    }
+}
+
+/// Plays an `Animation`, advanced by the time given to `Application::update`.
+pub struct AnimatedSprite {
+    animation: Rc<Animation>,
+    from: usize,
+    to: usize,
+    direction: AnimationDirection,
+    repeat: Option<u32>,
+    frame: usize,
+    /// +1 while going forward, -1 while going back.
+    step: isize,
+    passes: u32,
+    elapsed_ms: u64,
+    finished: bool,
+}
+
+impl AnimatedSprite {
+    /// Loops over every frame of the animation, forward.
+    pub fn new(animation: Rc<Animation>) -> Self {
+        let to = animation.frames.len().saturating_sub(1);
+        let mut sprite = Self {
+            animation,
+            from: 0,
+            to,
+            direction: AnimationDirection::Forward,
+            repeat: None,
+            frame: 0,
+            step: 1,
+            passes: 0,
+            elapsed_ms: 0,
+            finished: false,
+        };
+        sprite.restart();
+        sprite
+    }
+
+    /// Starts playing the tag from its first frame.
+    pub fn play(&mut self, tag: &str) -> Result {
+        let tag = self
+            .animation
+            .tag(tag)
+            .ok_or_else(|| Error::Other(format!("Animation has no tag '{}'.", tag)))?;
+        self.from = tag.from;
+        self.to = tag.to;
+        self.direction = tag.direction;
+        self.repeat = tag.repeat;
+        self.restart();
+        Ok(())
+    }
+
+    /// Plays the current frames again from the start.
+    pub fn restart(&mut self) {
+        let backwards = matches!(
+            self.direction,
+            AnimationDirection::Reverse | AnimationDirection::PingPongReverse
+        );
+        self.frame = if backwards { self.to } else { self.from };
+        self.step = if backwards { -1 } else { 1 };
+        self.passes = 0;
+        self.elapsed_ms = 0;
+        self.finished = false;
+    }
+
+    pub fn update(&mut self, delta_ms: u64) {
+        if self.finished || self.animation.frames.is_empty() {
+            return;
+        }
+        self.elapsed_ms += delta_ms;
+        loop {
+            // zero-length frames would never let the loop end
+            let duration = self.animation.frames[self.frame].duration_ms.max(1) as u64;
+            if self.elapsed_ms < duration {
+                break;
+            }
+            self.elapsed_ms -= duration;
+            if !self.advance() {
+                self.elapsed_ms = 0;
+                break;
+            }
+        }
+    }
+
+    #[inline]
+    pub fn frame_index(&self) -> usize {
+        self.frame
+    }
+
+    pub fn frame(&self) -> Option<&AnimationFrame> {
+        self.animation.frames.get(self.frame)
+    }
+
+    /// True once a tag with a repeat count has played through.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    #[inline]
+    pub fn animation(&self) -> &Rc<Animation> {
+        &self.animation
+    }
+
+    /// Options drawing the current frame with its top-left corner, before trimming, at
+    /// `position`. Flip, rotation and color can be set on them before copying.
+    pub fn copy_options(&self, position: Point) -> CopyTextureOptions {
+        let Some(frame) = self.frame() else {
+            return CopyTextureOptions::default();
+        };
+        CopyTextureOptions {
+            src: Some(frame.rect),
+            dest: Some(Rect::new(
+                position.x + frame.offset.x,
+                position.y + frame.offset.y,
+                frame.rect.w,
+                frame.rect.h,
+            )),
+            ..Default::default()
+        }
+    }
+
+    pub fn draw(&self, canvas: &Canvas, position: Point) -> Result {
+        if self.frame().is_none() {
+            return Ok(());
+        }
+        canvas.copy_texture(self.animation.texture(), self.copy_options(position))
+    }
+
+    /// Moves to the next frame, returning false if the animation finished instead.
+    fn advance(&mut self) -> bool {
+        let next = self.frame as isize + self.step;
+        if next >= self.from as isize && next <= self.to as isize {
+            self.frame = next as usize;
+            return true;
+        }
+
+        self.passes += 1;
+        if self.repeat.is_some_and(|repeat| self.passes >= repeat) {
+            self.finished = true;
+            return false;
+        }
+        match self.direction {
+            AnimationDirection::Forward => self.frame = self.from,
+            AnimationDirection::Reverse => self.frame = self.to,
+            AnimationDirection::PingPong | AnimationDirection::PingPongReverse => {
+                self.step = -self.step;
+                let next = self.frame as isize + self.step;
+                if next >= self.from as isize && next <= self.to as isize {
+                    self.frame = next as usize;
+                }
+            }
+        }
+        true
+    }
+}
+
+/// What is read from an Aseprite JSON export.
+pub(crate) struct AsepriteSheet {
+    /// The sheet image, relative to the JSON file.
+    pub(crate) image: Option<String>,
+    pub(crate) frames: Vec<AnimationFrame>,
+    pub(crate) tags: Vec<AnimationTag>,
+}
+
+impl AsepriteSheet {
+    pub(crate) fn parse(json: &[u8]) -> Result<Self> {
+        let json: AsepriteJson = serde_json::from_slice(json)
+            .map_err(|e| Error::Decode(format!("Invalid Aseprite JSON: {}", e)))?;
+        let frames = match json.frames {
+            AsepriteFrames::Array(frames) | AsepriteFrames::Hash(frames) => frames,
+        };
+
+        let frames = frames
+            .into_iter()
+            .map(|frame| {
+                if frame.rotated {
+                    return Err(Error::Decode(String::from(
+                        "Rotated Aseprite frames are not supported.",
+                    )));
+                }
+                Ok(AnimationFrame {
+                    rect: frame.frame.into(),
+                    offset: Point::new(frame.sprite_source_size.x, frame.sprite_source_size.y),
+                    duration_ms: frame.duration,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let tags = json
+            .meta
+            .frame_tags
+            .into_iter()
+            .map(|tag| {
+                if tag.from > tag.to || tag.to >= frames.len() {
+                    return Err(Error::Decode(format!(
+                        "Tag '{}' is outside the frames.",
+                        tag.name
+                    )));
+                }
+                let direction = match tag.direction.as_str() {
+                    "forward" => AnimationDirection::Forward,
+                    "reverse" => AnimationDirection::Reverse,
+                    "pingpong" => AnimationDirection::PingPong,
+                    "pingpong_reverse" => AnimationDirection::PingPongReverse,
+                    other => {
+                        return Err(Error::Decode(format!(
+                            "Unknown animation direction '{}'.",
+                            other
+                        )))
+                    }
+                };
+                let repeat = match tag.repeat {
+                    Some(AsepriteRepeat::Number(repeat)) => Some(repeat),
+                    Some(AsepriteRepeat::Text(repeat)) => Some(repeat.parse().map_err(|_| {
+                        Error::Decode(format!("Invalid repeat count '{}'.", repeat))
+                    })?),
+                    None => None,
+                };
+                Ok(AnimationTag {
+                    name: tag.name,
+                    from: tag.from,
+                    to: tag.to,
+                    direction,
+                    // Aseprite writes 0 for tags that loop forever
+                    repeat: repeat.filter(|&r| r > 0),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            image: json.meta.image,
+            frames,
+            tags,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct AsepriteJson {
+    frames: AsepriteFrames,
+    #[serde(default)]
+    meta: AsepriteMeta,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AsepriteFrames {
+    Array(Vec<AsepriteFrame>),
+    /// Keyed by frame name, in frame order.
+    Hash(#[serde(deserialize_with = "ordered_values")] Vec<AsepriteFrame>),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AsepriteFrame {
+    frame: AsepriteRect,
+    #[serde(default)]
+    rotated: bool,
+    #[serde(default)]
+    sprite_source_size: AsepriteRect,
+    duration: u32,
+}
+
+#[derive(Copy, Clone, Default, Deserialize)]
+struct AsepriteRect {
+    x: i32,
+    y: i32,
+    w: u32,
+    h: u32,
+}
+
+impl From<AsepriteRect> for Rect {
+    fn from(rect: AsepriteRect) -> Self {
+        Rect::new(rect.x, rect.y, rect.w, rect.h)
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AsepriteMeta {
+    image: Option<String>,
+    #[serde(default)]
+    frame_tags: Vec<AsepriteTag>,
+}
+
+#[derive(Deserialize)]
+struct AsepriteTag {
+    name: String,
+    from: usize,
+    to: usize,
+    #[serde(default = "forward")]
+    direction: String,
+    repeat: Option<AsepriteRepeat>,
+}
+
+/// Aseprite writes the repeat count as a string.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AsepriteRepeat {
+    Number(u32),
+    Text(String),
+}
+
+fn forward() -> String {
+    String::from("forward")
+}
+
+/// The values of a JSON object in the order they appear in the file.
+fn ordered_values<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> core::result::Result<Vec<AsepriteFrame>, D::Error> {
+    struct Values;
+
+    impl<'de> Visitor<'de> for Values {
+        type Value = Vec<AsepriteFrame>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "an object of frames")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(
+            self,
+            mut map: A,
+        ) -> core::result::Result<Self::Value, A::Error> {
+            let mut frames = Vec::new();
+            while let Some((_, frame)) = map.next_entry::<serde::de::IgnoredAny, AsepriteFrame>()? {
+                frames.push(frame);
+            }
+            Ok(frames)
+        }
+    }
+
+    deserializer.deserialize_map(Values)
+}
@@ -0,0 +1,277 @@
+//! A packed asset archive: many files in one, each optionally deflate-compressed.
+//!
+//! Layout, all integers little-endian:
+//!
+//! ```text
+//! magic        b"PNKA"
+//! version      u32
+//! entry count  u32
+//! entries      path length u16, path (UTF-8, `/`-separated), offset u64, stored length u64,
+//!              size u64, flags u8 (bit 0: deflate)
+//! data         the stored bytes of every entry, at the offsets given by the index
+//! ```
+//!
+//! Offsets count from the start of the archive.
+
+use crate::vfs::{normalize, Mount};
+use crate::{Error, Result};
+use alloc::string::String;
+use alloc::vec::Vec;
+use hashbrown::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+const MAGIC: &[u8; 4] = b"PNKA";
+const VERSION: u32 = 1;
+const FLAG_DEFLATE: u8 = 1;
+const COMPRESSION_LEVEL: u8 = 8;
+
+/// Where an archive's entry data is read from. The file is locked around each seek and read,
+/// as loading threads may read entries at the same time.
+enum Source {
+    File(Mutex<File>),
+    Memory(Vec<u8>),
+}
+
+struct Entry {
+    offset: u64,
+    stored_len: u64,
+    size: u64,
+    flags: u8,
+}
+
+/// A read-only archive, mountable in a `Vfs`. Only the index is read up front, entries are read
+/// from the file as they are requested.
+pub struct Archive {
+    source: Source,
+    entries: HashMap<String, Entry>,
+}
+
+impl Archive {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut file = File::open(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                Error::AssetNotFound(path.to_string_lossy().into_owned())
+            }
+            _ => Error::Io(e.to_string()),
+        })?;
+        let len = file
+            .metadata()
+            .map_err(|e| Error::Io(e.to_string()))?
+            .len();
+        let entries = read_index(&mut file, len)?;
+        Ok(Self {
+            source: Source::File(Mutex::new(file)),
+            entries,
+        })
+    }
+
+    /// An archive held in memory, such as one embedded with `include_bytes!`.
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Result<Self> {
+        let bytes = bytes.into();
+        let entries = read_index(&mut bytes.as_slice(), bytes.len() as u64)?;
+        Ok(Self {
+            source: Source::Memory(bytes),
+            entries,
+        })
+    }
+
+    /// Paths of every file in the archive, in no particular order.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Reads an entry whose bounds `read_index` checked against the archive.
+    fn read_entry(&self, entry: &Entry) -> Result<Vec<u8>> {
+        let stored = match &self.source {
+            Source::File(file) => {
+                let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+                let mut stored = vec![0; entry.stored_len as usize];
+                file.seek(SeekFrom::Start(entry.offset))
+                    .and_then(|_| file.read_exact(&mut stored))
+                    .map_err(|e| Error::Io(e.to_string()))?;
+                stored
+            }
+            Source::Memory(bytes) => {
+                let start = entry.offset as usize;
+                bytes[start..start + entry.stored_len as usize].to_vec()
+            }
+        };
+
+        if entry.flags & FLAG_DEFLATE == 0 {
+            return Ok(stored);
+        }
+        let bytes =
+            miniz_oxide::inflate::decompress_to_vec_with_limit(&stored, entry.size as usize)
+                .map_err(|e| corrupt(&format!("{:?}", e.status)))?;
+        if bytes.len() as u64 != entry.size {
+            return Err(corrupt("entry has the wrong size"));
+        }
+        Ok(bytes)
+    }
+}
+
+impl Mount for Archive {
+    fn read(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        match self.entries.get(path) {
+            Some(entry) => self.read_entry(entry).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Builds an archive in memory.
+#[derive(Default)]
+pub struct ArchiveWriter {
+    files: Vec<WrittenFile>,
+}
+
+struct WrittenFile {
+    path: String,
+    stored: Vec<u8>,
+    size: usize,
+    flags: u8,
+}
+
+impl ArchiveWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file, replacing any earlier one with the same path. Compressed files are stored
+    /// uncompressed if deflating does not make them smaller.
+    pub fn add(&mut self, path: &str, bytes: &[u8], compress: bool) -> Result {
+        let path = normalize(path)?;
+        if path.len() > u16::MAX as usize {
+            return Err(Error::Other(format!("Path '{}' is too long.", path)));
+        }
+
+        let (stored, flags) = if compress {
+            let deflated = miniz_oxide::deflate::compress_to_vec(bytes, COMPRESSION_LEVEL);
+            if deflated.len() < bytes.len() {
+                (deflated, FLAG_DEFLATE)
+            } else {
+                (bytes.to_vec(), 0)
+            }
+        } else {
+            (bytes.to_vec(), 0)
+        };
+
+        self.files.retain(|f| f.path != path);
+        self.files.push(WrittenFile {
+            path,
+            stored,
+            size: bytes.len(),
+            flags,
+        });
+        Ok(())
+    }
+
+    pub fn write(&self, mut out: impl Write) -> Result {
+        out.write_all(&self.to_bytes())
+            .map_err(|e| Error::Io(e.to_string()))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let index_len = 12
+            + self
+                .files
+                .iter()
+                .map(|f| 2 + f.path.len() + 8 * 3 + 1)
+                .sum::<usize>();
+
+        let mut bytes = Vec::with_capacity(index_len);
+        bytes.extend(MAGIC);
+        bytes.extend(VERSION.to_le_bytes());
+        bytes.extend((self.files.len() as u32).to_le_bytes());
+
+        let mut offset = index_len as u64;
+        for file in self.files.iter() {
+            bytes.extend((file.path.len() as u16).to_le_bytes());
+            bytes.extend(file.path.as_bytes());
+            bytes.extend(offset.to_le_bytes());
+            bytes.extend((file.stored.len() as u64).to_le_bytes());
+            bytes.extend((file.size as u64).to_le_bytes());
+            bytes.push(file.flags);
+            offset += file.stored.len() as u64;
+        }
+
+        for file in self.files.iter() {
+            bytes.extend(&file.stored);
+        }
+        bytes
+    }
+}
+
+/// Reads the index of an archive `len` bytes long. Counts and lengths in it are only trusted
+/// once checked against `len`, so a corrupt archive fails instead of allocating all memory.
+fn read_index(reader: &mut impl Read, len: u64) -> Result<HashMap<String, Entry>> {
+    let mut magic = [0; 4];
+    read(reader, &mut magic)?;
+    if &magic != MAGIC {
+        return Err(corrupt("not a panko archive"));
+    }
+    let version = u32::from_le_bytes(read_array(reader)?);
+    if version != VERSION {
+        return Err(corrupt(&format!("unsupported version {}", version)));
+    }
+
+    let count = u32::from_le_bytes(read_array(reader)?);
+    let mut entries = HashMap::new();
+    for _ in 0..count {
+        let path_len = u16::from_le_bytes(read_array(reader)?);
+        let mut path = Vec::new();
+        reader
+            .take(path_len as u64)
+            .read_to_end(&mut path)
+            .map_err(|e| Error::Io(e.to_string()))?;
+        if path.len() != path_len as usize {
+            return Err(corrupt("index is truncated"));
+        }
+        let path = String::from_utf8(path).map_err(|_| corrupt("path is not UTF-8"))?;
+        let entry = Entry {
+            offset: u64::from_le_bytes(read_array(reader)?),
+            stored_len: u64::from_le_bytes(read_array(reader)?),
+            size: u64::from_le_bytes(read_array(reader)?),
+            flags: read_array::<1>(reader)?[0],
+        };
+        let end = entry.offset.checked_add(entry.stored_len);
+        if end.is_none_or(|end| end > len) {
+            return Err(corrupt(&format!("entry '{}' is out of bounds", path)));
+        }
+        if entry.flags & FLAG_DEFLATE == 0 && entry.size != entry.stored_len {
+            return Err(corrupt(&format!("entry '{}' has the wrong size", path)));
+        }
+        entries.insert(path, entry);
+    }
+    Ok(entries)
+}
+
+fn read(reader: &mut impl Read, buf: &mut [u8]) -> Result {
+    reader
+        .read_exact(buf)
+        .map_err(|_| corrupt("index is truncated"))
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> Result<[u8; N]> {
+    let mut buf = [0; N];
+    read(reader, &mut buf)?;
+    Ok(buf)
+}
+
+fn corrupt(reason: &str) -> Error {
+    Error::Decode(format!("Corrupt archive: {}.", reason))
+}
@@ -0,0 +1,415 @@
+use crate::animation::{Animation, AsepriteSheet};
+use crate::backend::DecodedFont;
+use crate::font::Font;
+#[cfg(feature = "hot-reload")]
+use crate::hot_reload::HotReload;
+use crate::image::Image;
+use crate::loader::{AssetKind, Done, LoadProgress, Loaded, Loader, Pending};
+use crate::texture::{Texture, TextureSource};
+use crate::types::{Rect, TextureId};
+use crate::tiled;
+use crate::tilemap::Tilemap;
+use crate::vfs::{normalize, Resolved, Vfs};
+use crate::{BackendRef, BackendWeakRef, Error, Result};
+use alloc::rc::{Rc, Weak};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Deref;
+use hashbrown::HashMap;
+
+/// A cheap, cloneable, shared handle to an asset. The asset is released once the last handle to
+/// it is dropped.
+pub struct Handle<T>(Rc<T>);
+
+impl<T> Handle<T> {
+    /// Returns true if both handles point to the same asset.
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        Rc::ptr_eq(&a.0, &b.0)
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
+
+impl<T> Deref for Handle<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: TextureSource> TextureSource for Handle<T> {
+    fn texture_id(&self) -> TextureId {
+        self.0.texture_id()
+    }
+
+    fn size(&self) -> (u32, u32) {
+        self.0.size()
+    }
+
+    fn region(&self) -> Option<Rect> {
+        self.0.region()
+    }
+}
+
+/// Loads textures and fonts, sharing a single instance of each between everyone asking for the
+/// same path (and scale, for fonts) for as long as any handle to it is alive.
+///
+/// Paths are resolved through the `Vfs` once anything is mounted in it.
+pub struct Assets {
+    backend: BackendWeakRef,
+    vfs: Vfs,
+    textures: HashMap<String, Weak<Texture>>,
+    fonts: HashMap<(String, u8), Weak<Font>>,
+    loader: Loader,
+    loading: HashMap<u64, Loading>,
+    #[cfg(feature = "hot-reload")]
+    hot_reload: HotReload,
+}
+
+/// An asset whose file is being read and decoded by the `Loader`.
+enum Loading {
+    Texture(String, Pending<Texture>),
+    Font(String, u8, Pending<Font>),
+}
+
+impl Assets {
+    pub(crate) fn new(backend: &BackendRef) -> Self {
+        Self {
+            backend: Rc::downgrade(backend),
+            vfs: Vfs::new(),
+            textures: HashMap::new(),
+            fonts: HashMap::new(),
+            loader: Loader::new(),
+            loading: HashMap::new(),
+            #[cfg(feature = "hot-reload")]
+            hot_reload: HotReload::new(),
+        }
+    }
+
+    pub fn texture(&mut self, path: &str) -> Result<Handle<Texture>> {
+        if let Some(texture) = self.cached_texture(path) {
+            return Ok(texture);
+        }
+        let texture = self.load_texture(path)?;
+        Ok(self.cache_texture(path, texture))
+    }
+
+    pub fn font(&mut self, path: &str, scale: u8) -> Result<Handle<Font>> {
+        if let Some(font) = self.cached_font(path, scale) {
+            return Ok(font);
+        }
+        let font = self.load_font(path, scale)?;
+        Ok(self.cache_font(path, scale, font))
+    }
+
+    /// Like `texture`, but the file is read and decoded on a background thread. The texture is
+    /// created from its pixels at the start of a later frame.
+    pub fn texture_async(&mut self, path: &str) -> Pending<Texture> {
+        if let Some(texture) = self.cached_texture(path) {
+            return Pending::ready(Ok(texture));
+        }
+        let id = match self.request(path, AssetKind::Texture) {
+            Ok(id) => id,
+            Err(error) => return Pending::ready(Err(error)),
+        };
+        let pending = Pending::new();
+        self.loading
+            .insert(id, Loading::Texture(String::from(path), pending.clone()));
+        pending
+    }
+
+    /// Like `font`, but the file is read and parsed, and the font readied at `scale`, on a
+    /// background thread. The font is created at the start of a later frame.
+    pub fn font_async(&mut self, path: &str, scale: u8) -> Pending<Font> {
+        if let Some(font) = self.cached_font(path, scale) {
+            return Pending::ready(Ok(font));
+        }
+        let id = match self.request(path, AssetKind::Font(scale)) {
+            Ok(id) => id,
+            Err(error) => return Pending::ready(Err(error)),
+        };
+        let pending = Pending::new();
+        self.loading
+            .insert(id, Loading::Font(String::from(path), scale, pending.clone()));
+        pending
+    }
+
+    /// Progress of the background loads, for loading screens.
+    pub fn progress(&self) -> LoadProgress {
+        self.loader.progress()
+    }
+
+    /// Paths and errors of the background loads that failed, since the last time every load
+    /// had finished.
+    pub fn errors(&self) -> &[(String, Error)] {
+        self.loader.errors()
+    }
+
+    /// Blocks until every background load has finished.
+    pub fn wait(&mut self) {
+        while !self.loading.is_empty() {
+            let Some(done) = self.loader.wait() else {
+                return;
+            };
+            self.finish(done);
+        }
+    }
+
+    /// Creates the backend resources of the assets the loader threads finished decoding.
+    pub(crate) fn upload_loaded(&mut self) {
+        for done in self.loader.finished() {
+            self.finish(done);
+        }
+    }
+
+    /// Number of distinct textures currently alive through handles.
+    pub fn texture_count(&self) -> usize {
+        self.textures
+            .values()
+            .filter(|t| t.strong_count() > 0)
+            .count()
+    }
+
+    /// Number of distinct fonts currently alive through handles.
+    pub fn font_count(&self) -> usize {
+        self.fonts.values().filter(|f| f.strong_count() > 0).count()
+    }
+
+    pub fn vfs(&mut self) -> &mut Vfs {
+        &mut self.vfs
+    }
+
+    /// Reads a whole file the way textures and fonts are found: through the `Vfs`, or straight
+    /// from disk while nothing is mounted.
+    pub fn read(&self, path: &str) -> Result<Vec<u8>> {
+        self.vfs.read_located(path).map(|(bytes, _)| bytes)
+    }
+
+    /// Loads an Aseprite JSON export and, through `texture`, the sheet image it names next to it.
+    pub fn animation(&mut self, path: &str) -> Result<Animation> {
+        let sheet = AsepriteSheet::parse(&self.read(path)?)?;
+        let image = sheet.image.ok_or_else(|| {
+            Error::Decode(format!("Aseprite JSON '{}' does not name its image.", path))
+        })?;
+        let texture = self.texture(&sibling_path(path, &image))?;
+        Ok(Animation::new(texture, sheet.frames, sheet.tags))
+    }
+
+    /// Loads a Tiled map saved as TMX or JSON, with the tilesets it uses and their images. Paths
+    /// in the map are relative to it.
+    pub fn tilemap(&mut self, path: &str) -> Result<Tilemap> {
+        let bytes = self.read(path)?;
+        let mut map = tiled::parse(path, &bytes, &mut |file| self.read(file))?;
+        for tileset in map.tilesets.iter_mut() {
+            let texture = self.texture(&tileset.image)?;
+            tileset.columns = tiled::tileset_columns(
+                tileset.columns,
+                texture.width(),
+                tileset.tile_width,
+                tileset.margin,
+                tileset.spacing,
+            );
+            map.textures.push(texture);
+        }
+        Ok(map)
+    }
+
+    /// Loads a texture that is not shared with anyone else.
+    pub(crate) fn load_texture(&mut self, path: &str) -> Result<Texture> {
+        let backend = self.backend()?;
+        if self.vfs.is_empty() {
+            let texture = Texture::new_static(&backend, path)?;
+            self.watch_texture(path, &texture);
+            return Ok(texture);
+        }
+        match self.vfs.resolve(path)? {
+            Resolved::Disk(path) => {
+                let texture = Texture::new_static(&backend, &path)?;
+                self.watch_texture(&path, &texture);
+                Ok(texture)
+            }
+            Resolved::Bytes(bytes) => Texture::new_static_from_memory(&backend, &bytes),
+        }
+    }
+
+    /// Loads a font that is not shared with anyone else.
+    pub(crate) fn load_font(&mut self, path: &str, scale: u8) -> Result<Font> {
+        let backend = self.backend()?;
+        if self.vfs.is_empty() {
+            let font = Font::new(&backend, path, scale)?;
+            self.watch_font(path, scale, &font);
+            return Ok(font);
+        }
+        match self.vfs.resolve(path)? {
+            Resolved::Disk(path) => {
+                let font = Font::new(&backend, &path, scale)?;
+                self.watch_font(&path, scale, &font);
+                Ok(font)
+            }
+            Resolved::Bytes(bytes) => Font::from_memory(&backend, &bytes, scale),
+        }
+    }
+
+    /// Queues `path` on the loader, to be decoded by the backend's decoder.
+    fn request(&mut self, path: &str, kind: AssetKind) -> Result<u64> {
+        let decoder = self.backend()?.borrow().decoder();
+        Ok(self.loader.request(path, &self.vfs, kind, decoder))
+    }
+
+    fn finish(&mut self, done: Done) {
+        let Some(loading) = self.loading.remove(&done.id) else {
+            return;
+        };
+        match loading {
+            Loading::Texture(path, pending) => {
+                let result = done.result.and_then(|(loaded, disk_path)| match loaded {
+                    Loaded::Image(image) => self.upload_texture(&path, image, disk_path),
+                    Loaded::Font(_) => Err(not_requested(&path)),
+                });
+                self.on_finished(&path, result.as_ref().err());
+                pending.resolve(result);
+            }
+            Loading::Font(path, scale, pending) => {
+                let result = done.result.and_then(|(loaded, disk_path)| match loaded {
+                    Loaded::Font(font) => self.upload_font(&path, scale, font, disk_path),
+                    Loaded::Image(_) => Err(not_requested(&path)),
+                });
+                self.on_finished(&path, result.as_ref().err());
+                pending.resolve(result);
+            }
+        }
+    }
+
+    fn on_finished(&mut self, path: &str, error: Option<&Error>) {
+        match error {
+            Some(error) => self.loader.on_failed(path, error.clone()),
+            None => self.loader.on_loaded(),
+        }
+    }
+
+    fn upload_texture(
+        &mut self,
+        path: &str,
+        image: Image,
+        disk_path: Option<String>,
+    ) -> Result<Handle<Texture>> {
+        // loaded synchronously while this one was being decoded
+        if let Some(texture) = self.cached_texture(path) {
+            return Ok(texture);
+        }
+        let Image {
+            width,
+            height,
+            pixels,
+        } = image;
+        let texture = Texture::new_static_from_pixels(
+            &self.backend()?,
+            width,
+            height,
+            Image::FORMAT,
+            &pixels,
+        )?;
+        if let Some(disk_path) = disk_path {
+            self.watch_texture(&disk_path, &texture);
+        }
+        Ok(self.cache_texture(path, texture))
+    }
+
+    fn upload_font(
+        &mut self,
+        path: &str,
+        scale: u8,
+        font: DecodedFont,
+        disk_path: Option<String>,
+    ) -> Result<Handle<Font>> {
+        if let Some(font) = self.cached_font(path, scale) {
+            return Ok(font);
+        }
+        let font = Font::from_decoded(&self.backend()?, font)?;
+        if let Some(disk_path) = disk_path {
+            self.watch_font(&disk_path, scale, &font);
+        }
+        Ok(self.cache_font(path, scale, font))
+    }
+
+    fn cached_texture(&self, path: &str) -> Option<Handle<Texture>> {
+        self.textures
+            .get(&cache_key(path))
+            .and_then(Weak::upgrade)
+            .map(Handle)
+    }
+
+    fn cache_texture(&mut self, path: &str, texture: Texture) -> Handle<Texture> {
+        let texture = Rc::new(texture);
+        self.textures.retain(|_, t| t.strong_count() > 0);
+        self.textures.insert(cache_key(path), Rc::downgrade(&texture));
+        Handle(texture)
+    }
+
+    fn cached_font(&self, path: &str, scale: u8) -> Option<Handle<Font>> {
+        let key = (cache_key(path), scale);
+        self.fonts.get(&key).and_then(Weak::upgrade).map(Handle)
+    }
+
+    fn cache_font(&mut self, path: &str, scale: u8, font: Font) -> Handle<Font> {
+        let font = Rc::new(font);
+        self.fonts.retain(|_, f| f.strong_count() > 0);
+        self.fonts
+            .insert((cache_key(path), scale), Rc::downgrade(&font));
+        Handle(font)
+    }
+
+    /// Reloads the texture from `path` whenever the file changes, with the `hot-reload` feature.
+    #[cfg_attr(not(feature = "hot-reload"), allow(unused_variables))]
+    fn watch_texture(&mut self, path: &str, texture: &Texture) {
+        #[cfg(feature = "hot-reload")]
+        self.hot_reload.watch_texture(path, texture);
+    }
+
+    /// Reloads the font from `path` whenever the file changes, with the `hot-reload` feature.
+    #[cfg_attr(not(feature = "hot-reload"), allow(unused_variables))]
+    fn watch_font(&mut self, path: &str, scale: u8, font: &Font) {
+        #[cfg(feature = "hot-reload")]
+        self.hot_reload.watch_font(path, scale, font);
+    }
+
+    #[cfg(feature = "hot-reload")]
+    pub(crate) fn hot_reload(&mut self) -> &mut HotReload {
+        &mut self.hot_reload
+    }
+
+    fn backend(&self) -> Result<BackendRef> {
+        self.backend
+            .upgrade()
+            .ok_or(Error::Backend(String::from("Backend was already dropped.")))
+    }
+}
+
+/// The key `path` is cached under, the same for every spelling of it: `a/b.png`, `./a/b.png`
+/// and `a//b.png` share one asset.
+fn cache_key(path: &str) -> String {
+    let root = if path.starts_with(['/', '\\']) { "/" } else { "" };
+    match normalize(path) {
+        Ok(normalized) => format!("{}{}", root, normalized),
+        // climbs above where it starts, which only a path on disk can
+        Err(_) => String::from(path),
+    }
+}
+
+/// The error for a loader job that decoded another kind of asset than it was asked for.
+fn not_requested(path: &str) -> Error {
+    Error::Other(format!("'{}' was decoded as another kind of asset.", path))
+}
+
+/// `path` relative to the directory `file` is in.
+pub(crate) fn sibling_path(file: &str, path: &str) -> String {
+    match file.rfind(['/', '\\']) {
+        Some(i) => format!("{}/{}", &file[..i], path),
+        None => String::from(path),
+    }
+}
@@ -0,0 +1,280 @@
+use crate::image::Image;
+use crate::texture::{Texture, TextureSource};
+use crate::types::{BlendMode, CopyTextureOptions, PixelFormat, Rect, TextureId};
+use crate::{BackendRef, BackendWeakRef, Error, Result};
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const DEFAULT_PADDING: u32 = 1;
+
+/// Packs many small images into a few large target textures, so drawing them does not switch
+/// textures between sprites. Pages are created as needed, each `page_width` by `page_height`.
+///
+/// Images are added outside of drawing: adding renders into a page and leaves the window as the
+/// render target.
+pub struct TextureAtlas {
+    backend: BackendWeakRef,
+    page_width: u32,
+    page_height: u32,
+    padding: u32,
+    pages: Vec<AtlasPage>,
+}
+
+struct AtlasPage {
+    texture: Rc<Texture>,
+    free: MaxRects,
+}
+
+/// A packed image: a region of one of the atlas pages, drawable with `Canvas::copy_texture`.
+/// Keeps its page alive after the atlas is dropped.
+#[derive(Clone)]
+pub struct AtlasRegion {
+    page: Rc<Texture>,
+    rect: Rect,
+}
+
+impl TextureAtlas {
+    pub(crate) fn new(backend: &BackendRef, page_width: u32, page_height: u32) -> Self {
+        Self {
+            backend: Rc::downgrade(backend),
+            page_width,
+            page_height,
+            padding: DEFAULT_PADDING,
+            pages: Vec::new(),
+        }
+    }
+
+    /// Empty pixels left between packed images, so filtering does not bleed neighbours into
+    /// each other. Defaults to 1. Only affects images added afterwards.
+    pub fn set_padding(&mut self, padding: u32) {
+        self.padding = padding;
+    }
+
+    #[inline]
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn add_pixels(
+        &mut self,
+        w: u32,
+        h: u32,
+        format: PixelFormat,
+        pixels: &[u8],
+    ) -> Result<AtlasRegion> {
+        let texture = Texture::new_static_from_pixels(&self.backend()?, w, h, format, pixels)?;
+        self.add_texture(&texture)
+    }
+
+    pub fn add_image(&mut self, image: &Image) -> Result<AtlasRegion> {
+        self.add_pixels(image.width, image.height, Image::FORMAT, &image.pixels)
+    }
+
+    /// Copies the whole texture into the atlas. The texture can be dropped afterwards.
+    pub fn add_texture(&mut self, texture: &Texture) -> Result<AtlasRegion> {
+        let (w, h) = (texture.width(), texture.height());
+        let (page, rect) = self.allocate(w, h)?;
+        let page = Rc::clone(&self.pages[page].texture);
+
+        let backend = self.backend()?;
+        let mut backend = backend.borrow_mut();
+        backend.render_set_target(Some(page.id))?;
+        let copied = backend.render_copy_texture(
+            texture.id,
+            CopyTextureOptions {
+                dest: Some(rect),
+                // the page starts out transparent, blending would darken translucent pixels
+                blend_mode: BlendMode::None,
+                ..Default::default()
+            },
+        );
+        backend.render_set_target(None)?;
+        copied?;
+
+        Ok(AtlasRegion { page, rect })
+    }
+
+    /// Finds room for a `w` by `h` image, in an existing page or a new one.
+    fn allocate(&mut self, w: u32, h: u32) -> Result<(usize, Rect)> {
+        let padded_w = w + self.padding;
+        let padded_h = h + self.padding;
+        if w > self.page_width || h > self.page_height {
+            // would never fit, not even in a fresh page
+            return Err(Error::AtlasFull);
+        }
+
+        let found = self
+            .pages
+            .iter_mut()
+            .enumerate()
+            .find_map(|(i, page)| page.free.insert(padded_w, padded_h).map(|rect| (i, rect)));
+        let (index, rect) = match found {
+            Some(found) => found,
+            None => {
+                let mut page = AtlasPage {
+                    texture: Rc::new(Texture::new_target(
+                        &self.backend()?,
+                        self.page_width,
+                        self.page_height,
+                    )?),
+                    // the padding after the last column and row is not needed
+                    free: MaxRects::new(
+                        self.page_width + self.padding,
+                        self.page_height + self.padding,
+                    ),
+                };
+                let rect = page
+                    .free
+                    .insert(padded_w, padded_h)
+                    .ok_or(Error::AtlasFull)?;
+                self.pages.push(page);
+                (self.pages.len() - 1, rect)
+            }
+        };
+        Ok((index, Rect::new(rect.x, rect.y, w, h)))
+    }
+
+    fn backend(&self) -> Result<BackendRef> {
+        self.backend
+            .upgrade()
+            .ok_or(Error::Backend(String::from("Backend was already dropped.")))
+    }
+}
+
+impl AtlasRegion {
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.rect.w
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.rect.h
+    }
+
+    /// Where the image is inside its page.
+    #[inline]
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    #[inline]
+    pub fn page(&self) -> &Texture {
+        &self.page
+    }
+}
+
+impl TextureSource for AtlasRegion {
+    fn texture_id(&self) -> TextureId {
+        self.page.id
+    }
+
+    fn size(&self) -> (u32, u32) {
+        (self.rect.w, self.rect.h)
+    }
+
+    fn region(&self) -> Option<Rect> {
+        Some(self.rect)
+    }
+}
+
+/// The maximal rectangles bin packer: tracks every maximal free rectangle of a page, which may
+/// overlap, and places each new rectangle where it leaves the shortest leftover side.
+struct MaxRects {
+    free: Vec<Rect>,
+}
+
+impl MaxRects {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            free: vec![Rect::new(0, 0, width, height)],
+        }
+    }
+
+    fn insert(&mut self, w: u32, h: u32) -> Option<Rect> {
+        let (_, placed) = self
+            .free
+            .iter()
+            .filter(|free| w <= free.w && h <= free.h)
+            .map(|free| {
+                let (dw, dh) = (free.w - w, free.h - h);
+                ((dw.min(dh), dw.max(dh)), Rect::new(free.x, free.y, w, h))
+            })
+            .min_by_key(|(fit, _)| *fit)?;
+
+        let mut split = Vec::new();
+        self.free.retain(|free| {
+            if !overlaps(free, &placed) {
+                return true;
+            }
+            split_around(free, &placed, &mut split);
+            false
+        });
+        self.free.extend(split);
+        self.prune();
+        Some(placed)
+    }
+
+    /// Removes free rectangles contained in another one.
+    fn prune(&mut self) {
+        let mut i = 0;
+        while i < self.free.len() {
+            let rect = self.free[i];
+            let contained =
+                self.free.iter().enumerate().any(|(j, other)| {
+                    j != i && contains(other, &rect) && (rect != *other || j < i)
+                });
+            if contained {
+                self.free.swap_remove(i);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    a.x < b.x + b.w as i32
+        && b.x < a.x + a.w as i32
+        && a.y < b.y + b.h as i32
+        && b.y < a.y + a.h as i32
+}
+
+fn contains(outer: &Rect, inner: &Rect) -> bool {
+    inner.x >= outer.x
+        && inner.y >= outer.y
+        && inner.x + inner.w as i32 <= outer.x + outer.w as i32
+        && inner.y + inner.h as i32 <= outer.y + outer.h as i32
+}
+
+/// Pushes the parts of `free` left uncovered by `used`, each as large as possible.
+fn split_around(free: &Rect, used: &Rect, out: &mut Vec<Rect>) {
+    let free_right = free.x + free.w as i32;
+    let free_bottom = free.y + free.h as i32;
+    let used_right = used.x + used.w as i32;
+    let used_bottom = used.y + used.h as i32;
+
+    if used.x > free.x {
+        out.push(Rect::new(free.x, free.y, (used.x - free.x) as u32, free.h));
+    }
+    if used_right < free_right {
+        out.push(Rect::new(
+            used_right,
+            free.y,
+            (free_right - used_right) as u32,
+            free.h,
+        ));
+    }
+    if used.y > free.y {
+        out.push(Rect::new(free.x, free.y, free.w, (used.y - free.y) as u32));
+    }
+    if used_bottom < free_bottom {
+        out.push(Rect::new(
+            free.x,
+            used_bottom,
+            free.w,
+            (free_bottom - used_bottom) as u32,
+        ));
+    }
+}
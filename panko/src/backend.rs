@@ -1,27 +1,84 @@
+use crate::image::Image;
 use crate::Result;
 use crate::types::*;
+use alloc::boxed::Box;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::any::Any;
 
 pub trait Backend {
     fn window_set_config(&mut self, config: WindowConfig) -> Result;
+    /// Size of the window, in the units of `input_mouse_position`.
+    fn window_size(&mut self) -> Result<(u32, u32)>;
 
     fn texture_create(&mut self, w: u32, h: u32) -> Result<TextureData>;
     fn texture_load(&mut self, path: &str) -> Result<TextureData>;
+    /// Decodes an encoded image, in any format `texture_load` accepts, from memory.
+    fn texture_load_from_memory(&mut self, bytes: &[u8]) -> Result<TextureData>;
+    /// Creates a texture that can later be rewritten with `texture_update`.
+    fn texture_create_from_pixels(
+        &mut self,
+        w: u32,
+        h: u32,
+        format: PixelFormat,
+        pixels: &[u8],
+    ) -> Result<TextureData>;
+    /// Overwrites `rect`, or the whole texture, with pixels in the format it was created with.
+    fn texture_update(&mut self, id: TextureId, rect: Option<Rect>, pixels: &[u8]) -> Result;
     fn texture_destroy(&mut self, id: TextureId) -> Result;
+    /// Replaces the contents of a loaded texture with the file at `path`, keeping its id.
+    fn texture_reload(&mut self, id: TextureId, path: &str) -> Result<TextureData>;
 
     fn font_load(&mut self, path: &str, scale: u8) -> Result<FontData>;
+    /// Opens a font file, in any format `font_load` accepts, from memory.
+    fn font_load_from_memory(&mut self, bytes: &[u8], scale: u8) -> Result<FontData>;
     fn font_destroy(&mut self, id: FontId) -> Result;
+    /// Replaces a loaded font with the file at `path`, keeping its id.
+    fn font_reload(&mut self, id: FontId, path: &str, scale: u8) -> Result<FontData>;
     fn font_glyph_metrics(&mut self, font: FontId, glyph: char) -> Result<GlyphMetrics>;
+    /// Creates a font parsed on another thread by this backend's `decoder`.
+    fn font_create_decoded(&mut self, font: DecodedFont) -> Result<FontData>;
+    /// Decodes textures and fonts for this backend on loader threads.
+    fn decoder(&self) -> Arc<dyn Decoder>;
 
-    fn render_set_logical_size(&mut self, w: u32, h: u32) -> Result;
+    /// Draws the window at a logical size scaled into a viewport, or at its own size with
+    /// `None`. Drawing to the window is in logical pixels until this is called again.
+    fn render_set_logical_screen(&mut self, screen: Option<LogicalScreen>) -> Result;
     fn render_set_target(&mut self, target: Option<TextureId>) -> Result;
+    /// Discards drawing to the current target outside of `rect`, or stops clipping with `None`.
+    /// Render targets start unclipped every time they are set, while the window gets back the
+    /// clip it had.
+    fn render_set_clip_rect(&mut self, rect: Option<Rect>) -> Result;
     fn render_set_draw_color(&mut self, color: Color) -> Result;
     fn render_clear(&mut self) -> Result;
     fn render_present(&mut self) -> Result;
+    /// Draws whatever the backend has held back so far. `render_present` draws it as well, but
+    /// this is the call that reports its errors.
+    fn render_flush(&mut self) -> Result;
+    /// Fails with the error `render_copy_texture` would give for copying `texture` to the current
+    /// target, without drawing anything.
+    fn render_check_copy(&mut self, texture: TextureId) -> Result;
     fn render_copy_texture(&mut self, texture: TextureId, options: CopyTextureOptions) -> Result;
+    /// Draws copies of one texture, all with the same blend mode, in order. Backends may draw
+    /// batches another way than single copies, so edge pixels can differ slightly.
+    fn render_copy_texture_batch(
+        &mut self,
+        texture: TextureId,
+        copies: &[CopyTextureOptions],
+    ) -> Result;
     fn render_fill_rect(&mut self, rect: Option<Rect>, color: Color) -> Result;
     fn render_draw_rect(&mut self, rect: Option<Rect>, color: Color) -> Result;
+    fn render_draw_points(&mut self, points: &[Point], color: Color) -> Result;
+    /// Draws 1 pixel wide lines joining each point to the next one, both ends included.
+    fn render_draw_lines(&mut self, points: &[Point], color: Color) -> Result;
+    /// Fills the triangles made by each three vertices, blending the colors of their corners.
+    fn render_geometry(&mut self, vertices: &[Vertex]) -> Result;
     fn render_font_glyph(&mut self, font: FontId, glyph: char, origin: Point) -> Result;
+    /// Size in pixels of the current render target: the texture, or the whole window.
+    fn render_output_size(&mut self) -> Result<(u32, u32)>;
+    /// Reads `rect`, or the whole current render target, as RGBA8 rows. For the window `rect` is
+    /// in window pixels, regardless of the logical size.
+    fn render_read_pixels(&mut self, rect: Option<Rect>) -> Result<Vec<u8>>;
 
     fn events_pump(&mut self, events: &mut Vec<Event>);
 
@@ -30,3 +87,18 @@ pub trait Backend {
     fn system_get_millis(&mut self) -> Result<u64>;
     fn system_log(&self, s: &str);
 }
+
+/// Does the decoding part of loading textures and fonts, away from the main thread.
+pub trait Decoder: Send + Sync {
+    /// Decodes an image in any format `Backend::texture_load` accepts.
+    fn decode_image(&self, bytes: &[u8]) -> Result<Image>;
+    /// Parses a font in any format `Backend::font_load` accepts and readies it at `scale`, for
+    /// `Backend::font_create_decoded`.
+    fn decode_font(&self, bytes: Vec<u8>, scale: u8) -> Result<DecodedFont>;
+}
+
+/// A font parsed by a `Decoder`, which only the backend of that decoder can create.
+pub struct DecodedFont {
+    pub scale: u8,
+    pub font: Box<dyn Any + Send>,
+}
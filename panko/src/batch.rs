@@ -0,0 +1,225 @@
+use crate::backend::{Backend, DecodedFont, Decoder};
+use crate::types::*;
+use crate::Result;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+/// Wraps the backend of a `Context` and gathers consecutive copies of the same texture with the
+/// same blend mode, handing them to `render_copy_texture_batch` in one go.
+///
+/// Any other call that draws, changes the target or touches a resource submits the copies
+/// gathered so far first, so drawing happens in the order it was asked for. Copies are checked
+/// when they are gathered and fail right away like single copies do. Should submitting fail
+/// anyway, the call that submitted them still does its own work and then reports the error.
+pub(crate) struct Batched<B> {
+    backend: B,
+    texture: Option<TextureId>,
+    copies: Vec<CopyTextureOptions>,
+}
+
+impl<B: Backend> Batched<B> {
+    pub(crate) fn new(backend: B) -> Self {
+        Self {
+            backend,
+            texture: None,
+            copies: Vec::new(),
+        }
+    }
+
+    fn flush(&mut self) -> Result {
+        let Some(texture) = self.texture.take() else {
+            return Ok(());
+        };
+        let result = match self.copies.as_slice() {
+            [options] => self.backend.render_copy_texture(texture, *options),
+            copies => self.backend.render_copy_texture_batch(texture, copies),
+        };
+        self.copies.clear();
+        result
+    }
+
+    /// Submits the copies gathered so far, then makes `call` whether that worked or not, so its
+    /// work is never skipped. An error from submitting is reported over the result of `call`.
+    fn after_flush<T>(&mut self, call: impl FnOnce(&mut B) -> Result<T>) -> Result<T> {
+        let flushed = self.flush();
+        let result = call(&mut self.backend);
+        flushed.and(result)
+    }
+}
+
+impl<B: Backend> Backend for Batched<B> {
+    fn window_set_config(&mut self, config: WindowConfig) -> Result {
+        self.after_flush(|backend| backend.window_set_config(config))
+    }
+
+    fn window_size(&mut self) -> Result<(u32, u32)> {
+        self.backend.window_size()
+    }
+
+    fn texture_create(&mut self, w: u32, h: u32) -> Result<TextureData> {
+        self.after_flush(|backend| backend.texture_create(w, h))
+    }
+
+    fn texture_load(&mut self, path: &str) -> Result<TextureData> {
+        self.after_flush(|backend| backend.texture_load(path))
+    }
+
+    fn texture_load_from_memory(&mut self, bytes: &[u8]) -> Result<TextureData> {
+        self.after_flush(|backend| backend.texture_load_from_memory(bytes))
+    }
+
+    fn texture_create_from_pixels(
+        &mut self,
+        w: u32,
+        h: u32,
+        format: PixelFormat,
+        pixels: &[u8],
+    ) -> Result<TextureData> {
+        self.after_flush(|backend| backend.texture_create_from_pixels(w, h, format, pixels))
+    }
+
+    fn texture_update(&mut self, id: TextureId, rect: Option<Rect>, pixels: &[u8]) -> Result {
+        self.after_flush(|backend| backend.texture_update(id, rect, pixels))
+    }
+
+    fn texture_destroy(&mut self, id: TextureId) -> Result {
+        self.after_flush(|backend| backend.texture_destroy(id))
+    }
+
+    fn texture_reload(&mut self, id: TextureId, path: &str) -> Result<TextureData> {
+        self.after_flush(|backend| backend.texture_reload(id, path))
+    }
+
+    fn font_load(&mut self, path: &str, scale: u8) -> Result<FontData> {
+        self.after_flush(|backend| backend.font_load(path, scale))
+    }
+
+    fn font_load_from_memory(&mut self, bytes: &[u8], scale: u8) -> Result<FontData> {
+        self.after_flush(|backend| backend.font_load_from_memory(bytes, scale))
+    }
+
+    fn font_destroy(&mut self, id: FontId) -> Result {
+        self.after_flush(|backend| backend.font_destroy(id))
+    }
+
+    fn font_reload(&mut self, id: FontId, path: &str, scale: u8) -> Result<FontData> {
+        self.after_flush(|backend| backend.font_reload(id, path, scale))
+    }
+
+    fn font_glyph_metrics(&mut self, font: FontId, glyph: char) -> Result<GlyphMetrics> {
+        self.backend.font_glyph_metrics(font, glyph)
+    }
+
+    fn font_create_decoded(&mut self, font: DecodedFont) -> Result<FontData> {
+        self.after_flush(|backend| backend.font_create_decoded(font))
+    }
+
+    fn decoder(&self) -> Arc<dyn Decoder> {
+        self.backend.decoder()
+    }
+
+    fn render_set_logical_screen(&mut self, screen: Option<LogicalScreen>) -> Result {
+        self.after_flush(|backend| backend.render_set_logical_screen(screen))
+    }
+
+    fn render_set_target(&mut self, target: Option<TextureId>) -> Result {
+        self.after_flush(|backend| backend.render_set_target(target))
+    }
+
+    fn render_set_clip_rect(&mut self, rect: Option<Rect>) -> Result {
+        self.after_flush(|backend| backend.render_set_clip_rect(rect))
+    }
+
+    fn render_set_draw_color(&mut self, color: Color) -> Result {
+        self.after_flush(|backend| backend.render_set_draw_color(color))
+    }
+
+    fn render_clear(&mut self) -> Result {
+        self.after_flush(|backend| backend.render_clear())
+    }
+
+    fn render_present(&mut self) -> Result {
+        self.after_flush(|backend| backend.render_present())
+    }
+
+    fn render_flush(&mut self) -> Result {
+        self.after_flush(|backend| backend.render_flush())
+    }
+
+    fn render_check_copy(&mut self, texture: TextureId) -> Result {
+        self.backend.render_check_copy(texture)
+    }
+
+    fn render_copy_texture(&mut self, texture: TextureId, options: CopyTextureOptions) -> Result {
+        // the copy is only drawn later, so it fails now if it would fail then
+        self.backend.render_check_copy(texture)?;
+        let same_state = self.texture == Some(texture)
+            && self.copies.last().map(|last| last.blend_mode) == Some(options.blend_mode);
+        let mut flushed = Ok(());
+        if !same_state {
+            flushed = self.flush();
+            self.texture = Some(texture);
+        }
+        self.copies.push(options);
+        flushed
+    }
+
+    fn render_copy_texture_batch(
+        &mut self,
+        texture: TextureId,
+        copies: &[CopyTextureOptions],
+    ) -> Result {
+        for options in copies {
+            self.render_copy_texture(texture, *options)?;
+        }
+        Ok(())
+    }
+
+    fn render_fill_rect(&mut self, rect: Option<Rect>, color: Color) -> Result {
+        self.after_flush(|backend| backend.render_fill_rect(rect, color))
+    }
+
+    fn render_draw_rect(&mut self, rect: Option<Rect>, color: Color) -> Result {
+        self.after_flush(|backend| backend.render_draw_rect(rect, color))
+    }
+
+    fn render_draw_points(&mut self, points: &[Point], color: Color) -> Result {
+        self.after_flush(|backend| backend.render_draw_points(points, color))
+    }
+
+    fn render_draw_lines(&mut self, points: &[Point], color: Color) -> Result {
+        self.after_flush(|backend| backend.render_draw_lines(points, color))
+    }
+
+    fn render_geometry(&mut self, vertices: &[Vertex]) -> Result {
+        self.after_flush(|backend| backend.render_geometry(vertices))
+    }
+
+    fn render_font_glyph(&mut self, font: FontId, glyph: char, origin: Point) -> Result {
+        self.after_flush(|backend| backend.render_font_glyph(font, glyph, origin))
+    }
+
+    fn render_output_size(&mut self) -> Result<(u32, u32)> {
+        self.backend.render_output_size()
+    }
+
+    fn render_read_pixels(&mut self, rect: Option<Rect>) -> Result<Vec<u8>> {
+        self.after_flush(|backend| backend.render_read_pixels(rect))
+    }
+
+    fn events_pump(&mut self, events: &mut Vec<Event>) {
+        self.backend.events_pump(events)
+    }
+
+    fn input_mouse_position(&mut self) -> Result<(i32, i32)> {
+        self.backend.input_mouse_position()
+    }
+
+    fn system_get_millis(&mut self) -> Result<u64> {
+        self.backend.system_get_millis()
+    }
+
+    fn system_log(&self, s: &str) {
+        self.backend.system_log(s)
+    }
+}
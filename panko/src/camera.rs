@@ -0,0 +1,108 @@
+use crate::transform::Transform2D;
+use crate::types::{Point, Rect};
+
+/// Looks at the world through a rect of the screen: `(x, y)` is the world point shown at the
+/// center of `viewport`, `zoom` scales the world and `rotation` turns it clockwise, in degrees,
+/// around that center.
+///
+/// Drawing with `Canvas::with_camera` moves and scales the rects given to `copy_texture`,
+/// `draw_rect` and text drawing from the world to the screen, as its `transform` does. Drawing
+/// is clipped to the viewport.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera2D {
+    pub x: f32,
+    pub y: f32,
+    pub zoom: f32,
+    pub rotation: f32,
+    pub viewport: Rect,
+}
+
+impl Camera2D {
+    /// A camera that shows the world unchanged inside `viewport`.
+    pub fn new(viewport: Rect) -> Self {
+        Self {
+            x: viewport.x as f32 + viewport.w as f32 / 2.0,
+            y: viewport.y as f32 + viewport.h as f32 / 2.0,
+            zoom: 1.0,
+            rotation: 0.0,
+            viewport,
+        }
+    }
+
+    /// Centers the camera on a world point.
+    pub fn look_at(&mut self, point: Point) {
+        self.x = point.x as f32;
+        self.y = point.y as f32;
+    }
+
+    pub fn world_to_screen(&self, point: Point) -> Point {
+        let (x, y) = self.screen_point(point.x as f32, point.y as f32);
+        Point::new(x.round() as i32, y.round() as i32)
+    }
+
+    /// The world pixel under a pixel of the screen, like the one under the mouse.
+    pub fn screen_to_world(&self, point: Point) -> Point {
+        let (x, y) = self.world_point(point.x as f32 + 0.5, point.y as f32 + 0.5);
+        Point::new(x.floor() as i32, y.floor() as i32)
+    }
+
+    /// The part of the world inside the viewport, enlarged to hold all of it when rotated.
+    pub fn visible_rect(&self) -> Rect {
+        let (left, top) = (self.viewport.x as f32, self.viewport.y as f32);
+        let (right, bottom) = (left + self.viewport.w as f32, top + self.viewport.h as f32);
+        let corners = [(left, top), (right, top), (left, bottom), (right, bottom)]
+            .map(|(x, y)| self.world_point(x, y));
+        let min_x = corners.iter().map(|c| c.0).fold(f32::INFINITY, f32::min);
+        let min_y = corners.iter().map(|c| c.1).fold(f32::INFINITY, f32::min);
+        let max_x = corners
+            .iter()
+            .map(|c| c.0)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let max_y = corners
+            .iter()
+            .map(|c| c.1)
+            .fold(f32::NEG_INFINITY, f32::max);
+        Rect::new(
+            min_x.floor() as i32,
+            min_y.floor() as i32,
+            (max_x.ceil() - min_x.floor()) as u32,
+            (max_y.ceil() - min_y.floor()) as u32,
+        )
+    }
+
+    /// The transform from the world to the screen.
+    pub fn transform(&self) -> Transform2D {
+        let (cx, cy) = self.viewport_center();
+        let turn = Transform2D {
+            x: cx,
+            y: cy,
+            scale: self.zoom,
+            rotation: self.rotation,
+        };
+        turn.then(Transform2D::translation(-self.x, -self.y))
+    }
+
+    fn screen_point(&self, x: f32, y: f32) -> (f32, f32) {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let (dx, dy) = ((x - self.x) * self.zoom, (y - self.y) * self.zoom);
+        let (cx, cy) = self.viewport_center();
+        (cx + dx * cos - dy * sin, cy + dx * sin + dy * cos)
+    }
+
+    fn world_point(&self, x: f32, y: f32) -> (f32, f32) {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let (cx, cy) = self.viewport_center();
+        let (dx, dy) = (x - cx, y - cy);
+        (
+            self.x + (dx * cos + dy * sin) / self.zoom,
+            self.y + (dy * cos - dx * sin) / self.zoom,
+        )
+    }
+
+    fn viewport_center(&self) -> (f32, f32) {
+        (
+            self.viewport.x as f32 + self.viewport.w as f32 / 2.0,
+            self.viewport.y as f32 + self.viewport.h as f32 / 2.0,
+        )
+    }
+}
@@ -1,16 +1,38 @@
+use crate::backend::Backend;
+use crate::camera::Camera2D;
 use crate::font::Font;
-use crate::texture::Texture;
-use crate::types::CopyTextureOptions;
+use crate::image::Image;
+use crate::texture::{Texture, TextureSource};
+use crate::nine_slice;
+use crate::shapes::{self, Position};
+use crate::transform::Transform2D;
+use crate::types::{CopyTextureOptions, DrawOrder, Insets, NineSliceOptions, Vertex};
 use crate::{
-    BackendRef, Color, FontId, GlyphMetrics, Point, Rect, Result, TextAlign, TextCrossAlign,
-    TextPadding,
+    BackendRef, Color, Error, FontId, GlyphMetrics, Point, Rect, ResourceKind, Result, TextAlign,
+    TextCrossAlign, TextPadding, TextureId,
 };
+use alloc::boxed::Box;
 use alloc::rc::Rc;
-use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
 
 pub struct Canvas<'a> {
     backend: BackendRef,
     target: Option<&'a mut Texture>,
+    camera: Cell<Option<Camera2D>>,
+    clips: RefCell<Vec<Rect>>,
+    transforms: RefCell<Vec<Transform2D>>,
+    layer: Cell<Option<DrawOrder>>,
+    deferred: RefCell<Vec<Deferred>>,
+}
+
+type Draw = Box<dyn FnOnce(&mut dyn Backend) -> Result>;
+
+/// Drawing made inside `with_layer`, kept until the layers are drawn.
+struct Deferred {
+    order: DrawOrder,
+    clip: Option<Rect>,
+    draw: Draw,
 }
 
 impl<'a> Canvas<'a> {
@@ -19,36 +41,360 @@ impl<'a> Canvas<'a> {
         backend
             .borrow_mut()
             .render_set_target(target.as_ref().map(|t| t.id))?;
-        Ok(Self { target, backend })
+        Ok(Self {
+            target,
+            backend,
+            camera: Cell::new(None),
+            clips: RefCell::new(Vec::new()),
+            transforms: RefCell::new(Vec::new()),
+            layer: Cell::new(None),
+            deferred: RefCell::new(Vec::new()),
+        })
     }
 
     pub fn clear(&self, color: Color) -> Result {
-        self.backend.borrow_mut().render_fill_rect(None, color)
+        self.render(move |backend| backend.render_fill_rect(None, color))
+    }
+
+    /// Holds back drawing made inside `cb` and draws it sorted by `order` after everything else
+    /// this canvas draws in the frame, when `Application::draw` returns or, for a canvas given
+    /// by `with_target`, when its callback returns. Each draw keeps the clip it was made with.
+    /// Replaces the order of an enclosing `with_layer` until `cb` returns.
+    pub fn with_layer(&self, order: DrawOrder, cb: impl FnOnce(&Canvas) -> Result) -> Result {
+        let previous = self.layer.replace(Some(order));
+        let result = cb(self);
+        self.layer.set(previous);
+        result
+    }
+
+    /// The order of the enclosing `with_layer`, if any.
+    #[inline]
+    pub fn layer(&self) -> Option<DrawOrder> {
+        self.layer.get()
+    }
+
+    /// Draws what was held back by `with_layer` so far, such as before reading pixels back.
+    pub fn draw_layers(&self) -> Result {
+        let mut deferred = self.deferred.take();
+        // a stable sort, so draws with the same order keep the order they were made in
+        deferred.sort_by_key(|deferred| deferred.order);
+        let clip = self.clip();
+        let mut current = clip;
+        let mut backend = self.backend.borrow_mut();
+        for deferred in deferred {
+            if deferred.clip != current {
+                backend.render_set_clip_rect(deferred.clip)?;
+                current = deferred.clip;
+            }
+            (deferred.draw)(&mut *backend)?;
+        }
+        if current != clip {
+            backend.render_set_clip_rect(clip)?;
+        }
+        Ok(())
+    }
+
+    /// Draws through `camera`: rects given to the canvas inside `cb` are in the world, and
+    /// drawing is clipped to the camera's viewport as with `push_clip`. Replaces the camera of
+    /// an enclosing `with_camera` until `cb` returns. Targets drawn to with `with_target` are
+    /// not seen through the camera.
+    pub fn with_camera(&self, camera: &Camera2D, cb: impl FnOnce(&Canvas) -> Result) -> Result {
+        // the viewport is already on the screen, so it is not moved like the rects of push_clip
+        self.push_screen_clip(camera.viewport)?;
+        let previous = self.camera.replace(Some(*camera));
+        let result = cb(self);
+        self.camera.set(previous);
+        self.pop_clip()?;
+        result
+    }
+
+    /// The camera of the enclosing `with_camera`, if any.
+    #[inline]
+    pub fn camera(&self) -> Option<Camera2D> {
+        self.camera.get()
+    }
+
+    /// Draws in local coordinates until the matching `pop_transform`: everything given to the
+    /// canvas is placed by `transform` inside the transforms already pushed, and then seen
+    /// through the camera, if any. Each canvas given by `with_target` starts without transforms.
+    pub fn push_transform(&self, transform: Transform2D) {
+        let current = self.transform();
+        self.transforms.borrow_mut().push(current.then(transform));
+    }
+
+    /// Goes back to the transform that was in place before the last `push_transform`.
+    pub fn pop_transform(&self) {
+        self.transforms.borrow_mut().pop();
     }
 
+    /// Draws inside `cb` through `transform`, as between `push_transform` and `pop_transform`.
+    pub fn with_transform(
+        &self,
+        transform: Transform2D,
+        cb: impl FnOnce(&Canvas) -> Result,
+    ) -> Result {
+        self.push_transform(transform);
+        let result = cb(self);
+        self.pop_transform();
+        result
+    }
+
+    /// All the transforms pushed so far combined, leaving out the camera.
+    pub fn transform(&self) -> Transform2D {
+        let transforms = self.transforms.borrow();
+        transforms.last().copied().unwrap_or_default()
+    }
+
+    /// Discards drawing outside of `rect` until the matching `pop_clip`. The rect is moved by
+    /// the transforms and the camera like other rects, and is intersected with the clip already
+    /// pushed, so nested clips only ever shrink.
+    pub fn push_clip(&self, rect: Rect) -> Result {
+        self.push_screen_clip(self.screen_rect(Some(rect)).unwrap_or(rect))
+    }
+
+    fn push_screen_clip(&self, mut rect: Rect) -> Result {
+        if let Some(clip) = self.clip() {
+            rect = rect.intersection(clip);
+        }
+        self.clips.borrow_mut().push(rect);
+        self.backend.borrow_mut().render_set_clip_rect(Some(rect))
+    }
+
+    /// Goes back to the clip that was in place before the last `push_clip`.
+    pub fn pop_clip(&self) -> Result {
+        self.clips.borrow_mut().pop();
+        let clip = self.clip();
+        self.backend.borrow_mut().render_set_clip_rect(clip)
+    }
+
+    /// Draws inside `cb` clipped to `rect`, as between `push_clip` and `pop_clip`.
+    pub fn with_clip(&self, rect: Rect, cb: impl FnOnce(&Canvas) -> Result) -> Result {
+        self.push_clip(rect)?;
+        let result = cb(self);
+        self.pop_clip()?;
+        result
+    }
+
+    /// The clip in screen pixels that drawing is limited to, if any.
+    pub fn clip(&self) -> Option<Rect> {
+        self.clips.borrow().last().copied()
+    }
+
+    /// Draws to `target`, or to the window for `None`, inside `cb`. The canvas given to `cb`
+    /// starts without a clip, and this canvas's clip is set again once it returns.
     pub fn with_target(
         &self,
         target: Option<&mut Texture>,
         cb: impl FnOnce(&Canvas) -> Result,
     ) -> Result {
-        let canvas = Canvas::new(&self.backend, target)?;
-        cb(&canvas)?;
-        self.backend
-            .borrow_mut()
-            .render_set_target(self.target.as_ref().map(|t| t.id))?;
-        Ok(())
+        self.in_target(target, |canvas| {
+            cb(canvas)?;
+            canvas.draw_layers()
+        })
     }
 
-    pub fn copy_texture(&self, texture: &Texture, options: CopyTextureOptions) -> Result {
-        self.backend
-            .borrow_mut()
-            .render_copy_texture(texture.id, options)
+    pub fn copy_texture(
+        &self,
+        texture: &(impl TextureSource + ?Sized),
+        mut options: CopyTextureOptions,
+    ) -> Result {
+        if let Some(region) = texture.region() {
+            options.src = Some(match options.src {
+                Some(src) => Rect::new(region.x + src.x, region.y + src.y, src.w, src.h),
+                None => region,
+            });
+        }
+        if let Some(transform) = self.to_screen() {
+            transform.apply_to_copy(&mut options);
+        }
+        self.copy(texture.texture_id(), options)
     }
 
+    /// Draws the texture into `dest` with its corners unscaled, and its edges and center
+    /// stretched or tiled between them. Corners shrink when `dest` is too small to fit them.
+    pub fn draw_nine_slice(
+        &self,
+        texture: &(impl TextureSource + ?Sized),
+        insets: Insets,
+        dest: Rect,
+        options: NineSliceOptions,
+    ) -> Result {
+        for (src, dest) in nine_slice::pieces(texture.size(), insets, dest, &options) {
+            self.copy_texture(
+                texture,
+                CopyTextureOptions {
+                    src: Some(src),
+                    dest: Some(dest),
+                    color_mod: options.color_mod,
+                    ..Default::default()
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Outlines `rect`, or the whole target, with lines 1 pixel thick on the screen. Through a
+    /// turned camera or transform the outline turns with it.
     pub fn draw_rect(&self, rect: Option<Rect>, color: Color) -> Result {
-        self.backend
-            .borrow_mut()
-            .render_draw_rect(rect, color)
+        if let Some(rect) = rect.filter(|_| self.turned()) {
+            // the outline runs through the centers of the rect's edge pixels, as unturned
+            let (left, top) = (rect.x as f32 + 0.5, rect.y as f32 + 0.5);
+            let (right, bottom) = (left + rect.w as f32 - 1.0, top + rect.h as f32 - 1.0);
+            let outline = [
+                (left, top),
+                (right, top),
+                (right, bottom),
+                (left, bottom),
+                (left, top),
+            ];
+            return self.stroke(&outline, 1.0 / self.zoom(), color);
+        }
+        let rect = self.screen_rect(rect);
+        self.render(move |backend| backend.render_draw_rect(rect, color))
+    }
+
+    /// Fills `rect`, or the whole target, blending `color` over what is already drawn. Like
+    /// `draw_rect`, rects turn with a turned camera or transform.
+    pub fn fill_rect(&self, rect: Option<Rect>, color: Color) -> Result {
+        if let Some(rect) = rect.filter(|_| self.turned()) {
+            let (left, top) = (rect.x as f32, rect.y as f32);
+            let (right, bottom) = (left + rect.w as f32, top + rect.h as f32);
+            let corners = [(left, top), (right, top), (right, bottom), (left, bottom)];
+            return self.fill(&shapes::triangulate(&corners), color);
+        }
+        let rect = self.screen_rect(rect);
+        self.render(move |backend| backend.render_fill_rect(rect, color))
+    }
+
+    pub fn draw_point(&self, point: Point, color: Color) -> Result {
+        self.draw_points(&[point], color)
+    }
+
+    pub fn draw_points(&self, points: &[Point], color: Color) -> Result {
+        let points: Vec<Point> = points
+            .iter()
+            .map(|&point| self.screen_pixel(pixel_center(point)))
+            .collect();
+        self.render(move |backend| backend.render_draw_points(&points, color))
+    }
+
+    /// Draws a line between the pixels `from` and `to`, both included. Lines up to 1 pixel
+    /// thick are drawn as single pixel lines.
+    pub fn draw_line(&self, from: Point, to: Point, thickness: f32, color: Color) -> Result {
+        self.draw_polyline(&[from, to], thickness, color)
+    }
+
+    /// Draws lines joining each point to the next one, beveled where they meet.
+    pub fn draw_polyline(&self, points: &[Point], thickness: f32, color: Color) -> Result {
+        let points: Vec<Position> = points.iter().map(|&point| pixel_center(point)).collect();
+        self.stroke(&points, thickness, color)
+    }
+
+    /// Outlines the circle around the center of the `center` pixel. Thicker outlines can be
+    /// drawn with `draw_arc`.
+    pub fn draw_circle(&self, center: Point, radius: f32, color: Color) -> Result {
+        self.draw_ellipse(center, radius, radius, color)
+    }
+
+    /// Fills the pixels whose centers are within `radius` of the center of the `center` pixel.
+    pub fn fill_circle(&self, center: Point, radius: f32, color: Color) -> Result {
+        self.fill_ellipse(center, radius, radius, color)
+    }
+
+    pub fn draw_ellipse(
+        &self,
+        center: Point,
+        radius_x: f32,
+        radius_y: f32,
+        color: Color,
+    ) -> Result {
+        let outline = self.arc_points(pixel_center(center), radius_x, radius_y, 0.0, 360.0);
+        self.stroke(&outline, 1.0, color)
+    }
+
+    pub fn fill_ellipse(
+        &self,
+        center: Point,
+        radius_x: f32,
+        radius_y: f32,
+        color: Color,
+    ) -> Result {
+        let center = pixel_center(center);
+        let outline = self.arc_points(center, radius_x, radius_y, 0.0, 360.0);
+        self.fill(&shapes::fan(center, &outline), color)
+    }
+
+    /// Draws the part of a circle from `start` to `end`, in degrees clockwise from the right.
+    /// Thick arcs are centered on the circle, so a full turn draws a ring.
+    pub fn draw_arc(
+        &self,
+        center: Point,
+        radius: f32,
+        start: f32,
+        end: f32,
+        thickness: f32,
+        color: Color,
+    ) -> Result {
+        let center = pixel_center(center);
+        let zoom = self.zoom();
+        if thickness * zoom <= 1.0 {
+            let outline = self.arc_points(center, radius, radius, start, end);
+            return self.stroke(&outline, thickness, color);
+        }
+        let half = thickness / 2.0;
+        let inner_radius = (radius - half).max(0.0);
+        let segments = shapes::segments((radius + half) * zoom, end - start);
+        let inner = shapes::arc(center, inner_radius, inner_radius, start, end, segments);
+        let outer = shapes::arc(center, radius + half, radius + half, start, end, segments);
+        self.fill(&shapes::band(&inner, &outer), color)
+    }
+
+    /// Fills the slice of a circle from `start` to `end`, in degrees clockwise from the right.
+    pub fn fill_arc(
+        &self,
+        center: Point,
+        radius: f32,
+        start: f32,
+        end: f32,
+        color: Color,
+    ) -> Result {
+        let center = pixel_center(center);
+        let outline = self.arc_points(center, radius, radius, start, end);
+        self.fill(&shapes::fan(center, &outline), color)
+    }
+
+    /// Outlines the polygon through the pixels at `points`, closing it back to the first one.
+    pub fn draw_polygon(&self, points: &[Point], color: Color) -> Result {
+        let mut outline: Vec<Position> = points.iter().map(|&point| pixel_center(point)).collect();
+        if let Some(&first) = outline.first() {
+            outline.push(first);
+        }
+        self.stroke(&outline, 1.0, color)
+    }
+
+    /// Fills a polygon, convex or concave but not crossing itself. Unlike the other shapes,
+    /// `points` are corners on the pixel grid, as with rects: the square through `(0, 0)` and
+    /// `(4, 4)` covers 4 by 4 pixels.
+    pub fn fill_polygon(&self, points: &[Point], color: Color) -> Result {
+        let points: Vec<Position> = points
+            .iter()
+            .map(|point| (point.x as f32, point.y as f32))
+            .collect();
+        self.fill(&shapes::triangulate(&points), color)
+    }
+
+    /// Fills the triangles made by each three vertices, blending the colors of their corners.
+    /// Vertices are positions on the pixel grid, like the corners of rects. Vertices left over
+    /// after the last whole triangle are ignored.
+    pub fn draw_triangles(&self, vertices: &[Vertex]) -> Result {
+        let vertices: Vec<Vertex> = vertices[..vertices.len() / 3 * 3]
+            .iter()
+            .map(|vertex| {
+                let (x, y) = self.screen_position((vertex.x, vertex.y));
+                Vertex::new(x, y, vertex.color)
+            })
+            .collect();
+        self.render(move |backend| backend.render_geometry(&vertices))
     }
 
     pub fn draw_text(&self, font: &Font, text: &str, position: Point, color: Color) -> Result {
@@ -72,12 +418,15 @@ impl<'a> Canvas<'a> {
         &self,
         font: &Font,
         index: usize,
-        options: CopyTextureOptions,
+        mut options: CopyTextureOptions,
     ) -> Result {
-        let atlas_id = font.atlas(index).ok_or(String::from("Atlas not found."))?;
-        self.backend
-            .borrow_mut()
-            .render_copy_texture(atlas_id, options)
+        if let Some(transform) = self.to_screen() {
+            transform.apply_to_copy(&mut options);
+        }
+        let atlas_id = font
+            .atlas(index)
+            .ok_or(Error::InvalidResource(ResourceKind::Texture))?;
+        self.copy(atlas_id, options)
     }
 
     pub fn text_width(&self, font: &Font, text: &str) -> Result<u32> {
@@ -88,21 +437,188 @@ impl<'a> Canvas<'a> {
         font.register_text(text, self)
     }
 
+    /// Reads back `rect`, or the whole target, as drawn so far. For the window `rect` is in
+    /// window pixels and the image has the window's resolution.
+    pub fn read_pixels(&self, rect: Option<Rect>) -> Result<Image> {
+        let mut backend = self.backend.borrow_mut();
+        let (width, height) = match rect {
+            Some(rect) => (rect.w, rect.h),
+            None => backend.render_output_size()?,
+        };
+        let pixels = backend.render_read_pixels(rect)?;
+        Ok(Image {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Captures everything drawn to this canvas so far in the frame.
+    pub fn capture(&self) -> Result<Image> {
+        self.read_pixels(None)
+    }
+
+    /// Captures the contents of a render target texture.
+    pub fn capture_texture(&self, texture: &mut Texture) -> Result<Image> {
+        self.in_target(Some(texture), |canvas| canvas.capture())
+    }
+
     pub(crate) fn render_glyph(&self, font_id: FontId, glyph: char, position: Point) -> Result {
-        self.backend
-            .borrow_mut()
-            .render_font_glyph(font_id, glyph, position)
+        self.render(move |backend| backend.render_font_glyph(font_id, glyph, position))
     }
 
     pub(crate) fn glyph_metrics(&self, font_id: FontId, glyph: char) -> Result<GlyphMetrics> {
         self.backend.borrow_mut().font_glyph_metrics(font_id, glyph)
     }
+
+    /// Runs `cb` on a canvas for `target`, unclipped, then switches back to this canvas's target
+    /// and clip.
+    fn in_target<T>(
+        &self,
+        target: Option<&mut Texture>,
+        cb: impl FnOnce(&Canvas) -> Result<T>,
+    ) -> Result<T> {
+        let clip = self.clip();
+        if clip.is_some() {
+            self.backend.borrow_mut().render_set_clip_rect(None)?;
+        }
+        let canvas = Canvas::new(&self.backend, target)?;
+        let value = cb(&canvas)?;
+        drop(canvas);
+        self.backend
+            .borrow_mut()
+            .render_set_target(self.target.as_ref().map(|t| t.id))?;
+        if clip.is_some() {
+            self.backend.borrow_mut().render_set_clip_rect(clip)?;
+        }
+        Ok(value)
+    }
+
+    /// Sends drawing to the backend, or holds it back inside `with_layer`.
+    fn render(&self, draw: impl FnOnce(&mut dyn Backend) -> Result + 'static) -> Result {
+        match self.layer.get() {
+            Some(order) => {
+                self.deferred.borrow_mut().push(Deferred {
+                    order,
+                    clip: self.clip(),
+                    draw: Box::new(draw),
+                });
+                Ok(())
+            }
+            None => draw(&mut *self.backend.borrow_mut()),
+        }
+    }
+
+    /// Copies `texture`, checking it already when the copy is held back, so that a copy from a
+    /// texture that can not be drawn fails where it was made rather than with the layers.
+    fn copy(&self, texture: TextureId, options: CopyTextureOptions) -> Result {
+        if self.layer.get().is_some() {
+            self.backend.borrow_mut().render_check_copy(texture)?;
+        }
+        self.render(move |backend| backend.render_copy_texture(texture, options))
+    }
+
+    /// The transforms pushed so far inside the camera, or `None` when drawing is not moved.
+    fn to_screen(&self) -> Option<Transform2D> {
+        let local = self.transforms.borrow().last().copied();
+        match (self.camera.get(), local) {
+            (Some(camera), Some(local)) => Some(camera.transform().then(local)),
+            (Some(camera), None) => Some(camera.transform()),
+            (None, local) => local,
+        }
+    }
+
+    fn screen_rect(&self, rect: Option<Rect>) -> Option<Rect> {
+        match (rect, self.to_screen()) {
+            (Some(rect), Some(transform)) => Some(transform.rect(rect, None)),
+            (rect, _) => rect,
+        }
+    }
+
+    fn screen_position(&self, (x, y): Position) -> Position {
+        match self.to_screen() {
+            Some(transform) => transform.point(x, y),
+            None => (x, y),
+        }
+    }
+
+    /// The screen pixel a position falls in.
+    fn screen_pixel(&self, position: Position) -> Point {
+        let (x, y) = self.screen_position(position);
+        Point::new(x.floor() as i32, y.floor() as i32)
+    }
+
+    /// Whether the camera and transforms turn what is drawn, so rects no longer keep their axes.
+    fn turned(&self) -> bool {
+        self.to_screen()
+            .is_some_and(|transform| transform.rotation != 0.0)
+    }
+
+    fn zoom(&self) -> f32 {
+        self.to_screen().map_or(1.0, |transform| transform.scale)
+    }
+
+    /// Points along an ellipse, with as many segments as it needs at its size on the screen.
+    fn arc_points(
+        &self,
+        center: Position,
+        radius_x: f32,
+        radius_y: f32,
+        start: f32,
+        end: f32,
+    ) -> Vec<Position> {
+        let segments = shapes::segments(radius_x.max(radius_y) * self.zoom(), end - start);
+        shapes::arc(center, radius_x, radius_y, start, end, segments)
+    }
+
+    /// Draws lines through the points, thickened on the screen so they keep their thickness
+    /// through a rotated camera.
+    fn stroke(&self, points: &[Position], thickness: f32, color: Color) -> Result {
+        let thickness = thickness * self.zoom();
+        if thickness <= 1.0 {
+            let points: Vec<Point> = points
+                .iter()
+                .map(|&point| self.screen_pixel(point))
+                .collect();
+            return self.render(move |backend| backend.render_draw_lines(&points, color));
+        }
+        let points: Vec<Position> = points
+            .iter()
+            .map(|&point| self.screen_position(point))
+            .collect();
+        let vertices: Vec<Vertex> = shapes::thick_polyline(&points, thickness)
+            .into_iter()
+            .map(|(x, y)| Vertex::new(x, y, color))
+            .collect();
+        self.render(move |backend| backend.render_geometry(&vertices))
+    }
+
+    /// Fills triangles given in the world.
+    fn fill(&self, triangles: &[Position], color: Color) -> Result {
+        let vertices: Vec<Vertex> = triangles
+            .iter()
+            .map(|&position| {
+                let (x, y) = self.screen_position(position);
+                Vertex::new(x, y, color)
+            })
+            .collect();
+        self.render(move |backend| backend.render_geometry(&vertices))
+    }
 }
 
 impl<'a> Drop for Canvas<'a> {
     fn drop(&mut self) {
+        // layers are drawn where the frame or target ends, so their errors are not lost here
+        if self.clip().is_some() {
+            let _ = self.backend.borrow_mut().render_set_clip_rect(None);
+        }
         if self.target.is_none() {
             let _ = self.backend.borrow_mut().render_present();
         }
     }
 }
+
+/// The center of a pixel.
+fn pixel_center(point: Point) -> Position {
+    (point.x as f32 + 0.5, point.y as f32 + 0.5)
+}
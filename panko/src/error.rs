@@ -0,0 +1,73 @@
+use alloc::string::String;
+use core::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ResourceKind {
+    Texture,
+    Font,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// No asset exists at the given path.
+    AssetNotFound(String),
+    /// The asset exists but could not be read.
+    Io(String),
+    /// The asset was read but is not a valid image or font.
+    Decode(String),
+    /// The id does not belong to any resource created by the backend.
+    InvalidResource(ResourceKind),
+    /// The id belongs to a resource that has already been destroyed.
+    StaleResource(ResourceKind),
+    /// A pixel buffer does not have the size its dimensions require.
+    PixelBufferSize { expected: usize, actual: usize },
+    /// A region is not entirely inside the texture it refers to.
+    OutOfBounds,
+    /// Only streaming textures, created from pixels, can be updated.
+    NotStreaming,
+    /// A texture can not be copied while it is the render target.
+    SourceIsTarget,
+    /// A glyph or image does not fit in an empty atlas.
+    AtlasFull,
+    /// The backend failed, with the message it reported.
+    Backend(String),
+    /// Errors raised by application code.
+    Other(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AssetNotFound(path) => write!(f, "Asset '{}' does not exist.", path),
+            Self::Io(message) => write!(f, "Unable to read asset: {}", message),
+            Self::Decode(message) => write!(f, "Unable to decode asset: {}", message),
+            Self::InvalidResource(kind) => write!(f, "{:?} was never created.", kind),
+            Self::StaleResource(kind) => write!(f, "{:?} was already deleted.", kind),
+            Self::PixelBufferSize { expected, actual } => write!(
+                f,
+                "Pixel buffer has {} bytes, expected {}.",
+                actual, expected
+            ),
+            Self::OutOfBounds => write!(f, "Region is outside the texture."),
+            Self::NotStreaming => write!(f, "Texture was not created from pixels."),
+            Self::SourceIsTarget => write!(f, "Texture is the render target it is copied to."),
+            Self::AtlasFull => write!(f, "Does not fit in an empty atlas."),
+            Self::Backend(message) => write!(f, "{}", message),
+            Self::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Self::Other(message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Self::Other(String::from(message))
+    }
+}
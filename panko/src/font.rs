@@ -1,13 +1,15 @@
+use crate::backend::DecodedFont;
 use crate::canvas::Canvas;
 use crate::text::BoundedLines;
 use crate::types::{FontId, GlyphMetrics};
 use crate::{
-    BackendRef, BackendWeakRef, Color, CopyTextureOptions, FontData, Point, Rect, Result,
+    BackendRef, BackendWeakRef, Color, CopyTextureOptions, Error, FontData, Point, Rect, Result,
     TextAlign, TextCrossAlign, TextPadding, Texture, TextureId,
 };
 use alloc::rc::Rc;
 use alloc::vec::Vec;
-use core::cell::RefCell;
+use core::cell::{Cell, RefCell};
+use core::iter::Peekable;
 use core::ops::Range;
 use core::str::Chars;
 use hashbrown::HashMap;
@@ -19,7 +21,19 @@ pub struct Font(RefCell<FontInner>);
 
 impl Font {
     pub(crate) fn new(backend: &BackendRef, path: &str, scale: u8) -> Result<Self> {
-        Ok(Self(RefCell::new(FontInner::new(backend, path, scale)?)))
+        let data = backend.borrow_mut().font_load(path, scale)?;
+        Ok(Self(RefCell::new(FontInner::new(backend, data, scale)?)))
+    }
+
+    pub(crate) fn from_memory(backend: &BackendRef, bytes: &[u8], scale: u8) -> Result<Self> {
+        let data = backend.borrow_mut().font_load_from_memory(bytes, scale)?;
+        Ok(Self(RefCell::new(FontInner::new(backend, data, scale)?)))
+    }
+
+    pub(crate) fn from_decoded(backend: &BackendRef, font: DecodedFont) -> Result<Self> {
+        let scale = font.scale;
+        let data = backend.borrow_mut().font_create_decoded(font)?;
+        Ok(Self(RefCell::new(FontInner::new(backend, data, scale)?)))
     }
 
     pub(crate) fn draw_text(
@@ -64,6 +78,17 @@ impl Font {
     pub(crate) fn line_width(&self, text: &str, canvas: &Canvas) -> Result<u32> {
         self.0.borrow_mut().line_width(text, canvas)
     }
+
+    #[cfg_attr(not(feature = "hot-reload"), allow(dead_code))]
+    pub(crate) fn id(&self) -> FontId {
+        self.0.borrow().id
+    }
+
+    /// Set to the new glyph height once the backend font is reloaded.
+    #[cfg_attr(not(feature = "hot-reload"), allow(dead_code))]
+    pub(crate) fn shared_reload(&self) -> Rc<Cell<Option<u32>>> {
+        Rc::clone(&self.0.borrow().reloaded)
+    }
 }
 
 struct FontInner {
@@ -73,11 +98,12 @@ struct FontInner {
     backend: BackendWeakRef,
     atlases: Vec<FontAtlas>,
     entries: HashMap<char, FontGlyphEntry>,
+    reloaded: Rc<Cell<Option<u32>>>,
 }
 
 impl FontInner {
-    fn new(backend: &BackendRef, path: &str, scale: u8) -> Result<Self> {
-        let FontData { id, glyphs_height } = backend.borrow_mut().font_load(path, scale)?;
+    fn new(backend: &BackendRef, data: FontData, scale: u8) -> Result<Self> {
+        let FontData { id, glyphs_height } = data;
         let backend = Rc::downgrade(backend);
         let atlases = vec![FontAtlas::new(
             &backend,
@@ -92,6 +118,7 @@ impl FontInner {
             backend,
             atlases,
             entries: HashMap::new(),
+            reloaded: Rc::new(Cell::new(None)),
         })
     }
 
@@ -176,7 +203,20 @@ impl FontInner {
     }
 
     fn register_glyphs(&mut self, text: &str, canvas: &Canvas<'_>) -> Result {
-        let mut glyphs = text.chars();
+        if let Some(glyphs_height) = self.reloaded.take() {
+            // the glyphs were rendered with the old font
+            self.glyphs_height = glyphs_height;
+            self.entries.clear();
+            self.atlases.clear();
+            self.atlases.push(FontAtlas::new(
+                &self.backend,
+                ATLAS_WIDTH,
+                ATLAS_HEIGHT,
+                glyphs_height,
+            )?);
+        }
+
+        let mut glyphs = text.chars().peekable();
         let mut atlas_index = self.atlases.len() - 1;
         let mut atlas = &mut self.atlases[atlas_index];
         loop {
@@ -204,6 +244,15 @@ impl FontInner {
     }
 }
 
+impl Drop for FontInner {
+    fn drop(&mut self) {
+        // the atlases are textures and release themselves
+        if let Some(backend) = self.backend.upgrade() {
+            let _ = backend.borrow_mut().font_destroy(self.id);
+        }
+    }
+}
+
 struct FontGlyphEntry {
     atlas_index: usize,
     rect: Rect,
@@ -237,24 +286,29 @@ fn register_glyphs(
     atlas: &mut FontAtlas,
     canvas: &Canvas,
     entries: &mut HashMap<char, FontGlyphEntry>,
-    glyphs: &mut Chars,
+    glyphs: &mut Peekable<Chars>,
 ) -> Result<bool> {
     let mut finished = false;
     let atlas_width = atlas.texture.width();
     let atlas_height = atlas.texture.height();
     canvas.with_target(Some(&mut atlas.texture), |canvas| {
-        while let Some(glyph) = glyphs.next() {
+        while let Some(&glyph) = glyphs.peek() {
             if entries.contains_key(&glyph) {
+                glyphs.next();
                 continue;
             }
             let metrics = canvas.glyph_metrics(font_id, glyph)?;
+            if metrics.advance > atlas_width || atlas.glyph_height > atlas_height {
+                // would never fit, not even in a fresh atlas
+                return Err(Error::AtlasFull);
+            }
 
             if atlas.x_cursor + metrics.advance > atlas_width {
                 // go to next line
                 atlas.x_cursor = 0;
                 atlas.y_cursor += atlas.glyph_height;
                 if atlas.y_cursor + atlas.glyph_height > atlas_height {
-                    // atlas is full
+                    // atlas is full, the glyph is left in the iterator for the next one
                     return Ok(());
                 }
             }
@@ -281,6 +335,7 @@ fn register_glyphs(
             );
 
             atlas.x_cursor += metrics.advance;
+            glyphs.next();
         }
         finished = true;
         Ok(())
@@ -0,0 +1,131 @@
+use crate::font::Font;
+use crate::texture::Texture;
+use crate::types::{FontData, FontId, TextureData, TextureId};
+use crate::{BackendRef, Result};
+use alloc::rc::{Rc, Weak};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::Cell;
+use std::time::SystemTime;
+
+/// How often the watched files are checked for changes.
+pub(crate) const POLL_INTERVAL_MILLIS: u64 = 250;
+
+/// Watches the files textures and fonts were loaded from, by polling their modification times,
+/// and reloads the backend resource behind them when a file changes.
+pub(crate) struct HotReload {
+    watched: Vec<Watched>,
+    last_poll: Option<u64>,
+}
+
+struct Watched {
+    path: String,
+    modified: Option<SystemTime>,
+    asset: WatchedAsset,
+}
+
+enum WatchedAsset {
+    Texture(TextureId, Weak<Cell<(u32, u32)>>),
+    Font(FontId, u8, Weak<Cell<Option<u32>>>),
+}
+
+impl WatchedAsset {
+    fn is_alive(&self) -> bool {
+        match self {
+            Self::Texture(_, size) => size.strong_count() > 0,
+            Self::Font(_, _, reloaded) => reloaded.strong_count() > 0,
+        }
+    }
+}
+
+impl HotReload {
+    pub(crate) fn new() -> Self {
+        Self {
+            watched: Vec::new(),
+            last_poll: None,
+        }
+    }
+
+    pub(crate) fn watch_texture(&mut self, path: &str, texture: &Texture) {
+        self.watch(
+            path,
+            WatchedAsset::Texture(texture.id, Rc::downgrade(texture.shared_size())),
+        );
+    }
+
+    pub(crate) fn watch_font(&mut self, path: &str, scale: u8, font: &Font) {
+        self.watch(
+            path,
+            WatchedAsset::Font(font.id(), scale, Rc::downgrade(&font.shared_reload())),
+        );
+    }
+
+    /// Reloads changed files at most once every `POLL_INTERVAL_MILLIS`.
+    pub(crate) fn poll(&mut self, backend: &BackendRef, millis: u64) -> usize {
+        if self
+            .last_poll
+            .is_some_and(|last| millis.saturating_sub(last) < POLL_INTERVAL_MILLIS)
+        {
+            return 0;
+        }
+        self.last_poll = Some(millis);
+        self.reload_changed(backend)
+    }
+
+    /// Reloads every asset whose file changed since it was loaded or last reloaded, returning how
+    /// many were reloaded. Failures are logged and leave the previous resource in place.
+    pub(crate) fn reload_changed(&mut self, backend: &BackendRef) -> usize {
+        self.watched.retain(|w| w.asset.is_alive());
+
+        let mut reloaded = 0;
+        for watched in self.watched.iter_mut() {
+            let modified = modified(&watched.path);
+            if modified == watched.modified {
+                continue;
+            }
+            // a file caught halfway through being written is retried on its next change
+            watched.modified = modified;
+            match watched.reload(backend) {
+                Ok(()) => reloaded += 1,
+                Err(error) => backend
+                    .borrow()
+                    .system_log(&format!("Unable to reload '{}': {}", watched.path, error)),
+            }
+        }
+        reloaded
+    }
+
+    fn watch(&mut self, path: &str, asset: WatchedAsset) {
+        self.watched.push(Watched {
+            path: String::from(path),
+            modified: modified(path),
+            asset,
+        });
+    }
+}
+
+impl Watched {
+    fn reload(&self, backend: &BackendRef) -> Result {
+        match &self.asset {
+            WatchedAsset::Texture(id, size) => {
+                let TextureData { width, height, .. } =
+                    backend.borrow_mut().texture_reload(*id, &self.path)?;
+                if let Some(size) = size.upgrade() {
+                    size.set((width, height));
+                }
+            }
+            WatchedAsset::Font(id, scale, reloaded) => {
+                let FontData { glyphs_height, .. } =
+                    backend.borrow_mut().font_reload(*id, &self.path, *scale)?;
+                if let Some(reloaded) = reloaded.upgrade() {
+                    reloaded.set(Some(glyphs_height));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
@@ -0,0 +1,77 @@
+use crate::types::PixelFormat;
+use crate::{Error, Result};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use std::path::Path;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// A decoded image, RGBA8 row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub const FORMAT: PixelFormat = PixelFormat::Rgba8;
+
+    #[inline]
+    pub fn is_png(bytes: &[u8]) -> bool {
+        bytes.starts_with(PNG_SIGNATURE)
+    }
+
+    pub fn decode_png(bytes: &[u8]) -> Result<Self> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder
+            .read_info()
+            .map_err(|e| Error::Decode(e.to_string()))?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buf)
+            .map_err(|e| Error::Decode(e.to_string()))?;
+        let buf = &buf[..info.buffer_size()];
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buf.to_vec(),
+            png::ColorType::Rgb => PixelFormat::Rgb8.to_rgba8(buf),
+            png::ColorType::GrayscaleAlpha => buf
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => buf.iter().flat_map(|&p| [p, p, p, 255]).collect(),
+            png::ColorType::Indexed => {
+                return Err(Error::Decode(String::from("Unsupported PNG color type.")))
+            }
+        };
+
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+
+    pub fn encode_png(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| {
+                writer.write_image_data(&self.pixels)?;
+                writer.finish()
+            })
+            .map_err(|e| Error::Io(e.to_string()))?;
+        Ok(bytes)
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result {
+        let path = path.as_ref();
+        std::fs::write(path, self.encode_png()?)
+            .map_err(|e| Error::Io(format!("{}: {}", path.display(), e)))
+    }
+}
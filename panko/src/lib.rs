@@ -1,26 +1,55 @@
 #[macro_use]
 extern crate alloc;
 
+pub mod animation;
+pub mod archive;
+pub mod assets;
+pub mod atlas;
 pub mod backend;
+mod batch;
+pub mod camera;
 pub mod canvas;
+pub mod error;
 pub mod font;
+#[cfg(feature = "hot-reload")]
+mod hot_reload;
+pub mod image;
 pub mod input;
+pub mod loader;
+mod nine_slice;
+pub mod recording;
+mod shapes;
+pub mod slots;
 mod text;
 pub mod texture;
+mod tiled;
+pub mod tilemap;
+mod tracker;
+pub mod transform;
 pub mod types;
+pub mod vfs;
 
 use alloc::rc::{Rc, Weak};
-use alloc::string::String;
 use alloc::vec::Vec;
+use animation::Animation;
+use assets::{Assets, Handle};
+use atlas::{AtlasRegion, TextureAtlas};
 use backend::*;
+use batch::Batched;
 use canvas::Canvas;
 use core::cell::RefCell;
+pub use error::{Error, ResourceKind};
 use font::Font;
+use image::Image;
 use input::InputState;
+use loader::{LoadProgress, Pending};
 use texture::*;
+use tilemap::Tilemap;
+use tracker::{LiveResources, Tracked};
 use types::*;
+use vfs::Vfs;
 
-pub type Result<T = ()> = core::result::Result<T, String>;
+pub type Result<T = ()> = core::result::Result<T, Error>;
 pub(crate) type BackendRef = Rc<RefCell<dyn Backend>>;
 pub(crate) type BackendWeakRef = Weak<RefCell<dyn Backend>>;
 
@@ -32,35 +61,159 @@ pub trait Application {
 
 pub struct Context {
     pub(crate) backend: BackendRef,
+    live: Rc<RefCell<LiveResources>>,
+    assets: Assets,
     input: InputState,
     events: Vec<Event>,
+    resolution: ResolutionPolicy,
+    logical: Option<LogicalScreen>,
     quit: bool,
 }
 
 impl Context {
     pub fn new(context: impl Backend + 'static) -> Self {
+        let live = Rc::new(RefCell::new(LiveResources::default()));
+        let backend = Tracked::new(Batched::new(context), Rc::clone(&live));
+        let backend: BackendRef = Rc::new(RefCell::new(backend));
         Self {
-            backend: Rc::new(RefCell::new(context)),
+            assets: Assets::new(&backend),
+            backend,
+            live,
             events: Vec::with_capacity(16),
             input: InputState::default(),
+            resolution: ResolutionPolicy::Window,
+            logical: None,
             quit: false,
         }
     }
 
     pub fn set_window_config(&mut self, config: WindowConfig) -> Result {
-        self.backend.borrow_mut().window_set_config(config)
+        self.backend.borrow_mut().window_set_config(config)?;
+        self.apply_resolution()
     }
 
-    pub fn load_texture(&mut self, path: &str) -> Result<Texture> {
-        Texture::new_static(&self.backend, path)
+    /// Changes how drawing is sized and fitted into the window. The mouse position is reported
+    /// in the same coordinates drawing uses, and the policy is applied again whenever the window
+    /// is resized.
+    pub fn set_resolution(&mut self, policy: ResolutionPolicy) -> Result {
+        self.resolution = policy;
+        self.apply_resolution()
+    }
+
+    pub fn resolution(&self) -> ResolutionPolicy {
+        self.resolution
+    }
+
+    /// The size drawing to the screen is done at: the logical size of the resolution policy, or
+    /// the size of the window.
+    pub fn screen_size(&mut self) -> Result<Dimensions> {
+        match self.logical {
+            Some(logical) => Ok(logical.size),
+            None => {
+                let (width, height) = self.backend.borrow_mut().window_size()?;
+                Ok(Dimensions::new(width, height))
+            }
+        }
+    }
+
+    /// Loads a texture, or shares the one already loaded from `path`, see `Assets::texture`.
+    pub fn load_texture(&mut self, path: &str) -> Result<Handle<Texture>> {
+        self.assets.texture(path)
+    }
+
+    /// Decodes a texture from an encoded image, such as one embedded with `include_bytes!`.
+    pub fn load_texture_from_memory(&mut self, bytes: &[u8]) -> Result<Texture> {
+        Texture::new_static_from_memory(&self.backend, bytes)
+    }
+
+    /// Creates a streaming texture, which can be rewritten every frame with `Texture::update`.
+    pub fn create_texture_from_pixels(
+        &mut self,
+        w: u32,
+        h: u32,
+        format: PixelFormat,
+        pixels: &[u8],
+    ) -> Result<Texture> {
+        Texture::new_streaming(&self.backend, w, h, format, pixels)
     }
 
     pub fn create_target(&mut self, w: u32, h: u32) -> Result<Texture> {
         Texture::new_target(&self.backend, w, h)
     }
 
-    pub fn load_font(&mut self, path: &str, scale: u8) -> Result<Font> {
-        Font::new(&self.backend, path, scale)
+    /// Captures the contents of a render target texture, such as a thumbnail drawn for a save
+    /// slot. To capture the window use `Canvas::capture` while drawing.
+    pub fn capture_texture(&mut self, texture: &mut Texture) -> Result<Image> {
+        let image = Canvas::new(&self.backend, Some(texture))?.capture();
+        self.backend.borrow_mut().render_set_target(None)?;
+        image
+    }
+
+    /// An empty atlas whose pages are `page_width` by `page_height` target textures.
+    pub fn create_atlas(&mut self, page_width: u32, page_height: u32) -> TextureAtlas {
+        TextureAtlas::new(&self.backend, page_width, page_height)
+    }
+
+    /// Loads an image file, through the mounted `Vfs`, straight into the atlas.
+    pub fn load_into_atlas(
+        &mut self,
+        atlas: &mut TextureAtlas,
+        path: &str,
+    ) -> Result<AtlasRegion> {
+        let texture = self.assets.load_texture(path)?;
+        atlas.add_texture(&texture)
+    }
+
+    /// Loads an Aseprite JSON export along with its sheet, ready for `AnimatedSprite::new`.
+    pub fn load_animation(&mut self, path: &str) -> Result<Rc<Animation>> {
+        self.assets.animation(path).map(Rc::new)
+    }
+
+    /// Loads a map made in the Tiled editor, see `Assets::tilemap`.
+    pub fn load_tilemap(&mut self, path: &str) -> Result<Tilemap> {
+        self.assets.tilemap(path)
+    }
+
+    /// Loads a font, or shares the one already loaded from `path` at `scale`, see `Assets::font`.
+    pub fn load_font(&mut self, path: &str, scale: u8) -> Result<Handle<Font>> {
+        self.assets.font(path, scale)
+    }
+
+    /// Starts loading a texture in the background, see `Assets::texture_async`.
+    pub fn load_texture_async(&mut self, path: &str) -> Pending<Texture> {
+        self.assets.texture_async(path)
+    }
+
+    /// Starts loading a font in the background, see `Assets::font_async`.
+    pub fn load_font_async(&mut self, path: &str, scale: u8) -> Pending<Font> {
+        self.assets.font_async(path, scale)
+    }
+
+    /// How far along the background loads are, to draw a loading bar.
+    pub fn loading_progress(&self) -> LoadProgress {
+        self.assets.progress()
+    }
+
+    /// Opens a font from the bytes of a font file, such as one embedded with `include_bytes!`.
+    pub fn load_font_from_memory(&mut self, bytes: &[u8], scale: u8) -> Result<Font> {
+        Font::from_memory(&self.backend, bytes, scale)
+    }
+
+    /// Shared, path-keyed textures and fonts.
+    pub fn assets(&mut self) -> &mut Assets {
+        &mut self.assets
+    }
+
+    /// Mount points asset paths are resolved against.
+    pub fn vfs(&mut self) -> &mut Vfs {
+        self.assets.vfs()
+    }
+
+    /// Reloads every loaded texture and font whose file changed on disk, returning how many were
+    /// reloaded. The event loop already does this a few times per second.
+    #[cfg(feature = "hot-reload")]
+    pub fn reload_changed_assets(&mut self) -> usize {
+        self.assets.hot_reload().reload_changed(&self.backend)
     }
 
     pub fn request_quit(&mut self) {
@@ -77,11 +230,21 @@ impl Context {
     }
 
     fn update_mouse_position(&mut self) -> Result {
-        let pos = self.backend.borrow_mut().input_mouse_position()?;
+        let mut pos = self.backend.borrow_mut().input_mouse_position()?;
+        if let Some(logical) = self.logical {
+            pos = logical.window_to_logical(pos.0, pos.1);
+        }
         self.input.mouse.set_position(pos.0, pos.1);
         Ok(())
     }
 
+    fn apply_resolution(&mut self) -> Result {
+        let mut backend = self.backend.borrow_mut();
+        let (width, height) = backend.window_size()?;
+        self.logical = self.resolution.logical_screen(width, height);
+        backend.render_set_logical_screen(self.logical)
+    }
+
     fn canvas(&self) -> Result<Canvas> {
         self.backend.borrow_mut().render_clear()?;
         Canvas::new(&self.backend, None)
@@ -92,6 +255,17 @@ impl Context {
     }
 }
 
+impl Drop for Context {
+    fn drop(&mut self) {
+        if cfg!(debug_assertions) {
+            let backend = self.backend.borrow();
+            for leak in self.live.borrow().report() {
+                backend.system_log(&leak);
+            }
+        }
+    }
+}
+
 pub fn run_event_loop<T: Application>(
     backend: impl Backend + 'static,
     load: impl FnOnce(&mut Context) -> Result<T>,
@@ -112,10 +286,18 @@ pub fn run_event_loop<T: Application>(
         let delta_millis = millis_now - millis_before;
         acc_millis += delta_millis;
 
-        context.update_mouse_position()?;
+        #[cfg(feature = "hot-reload")]
+        context
+            .assets
+            .hot_reload()
+            .poll(&context.backend, millis_now);
+
+        context.assets.upload_loaded();
+
         context.input.keyboard.clear_memory();
         context.input.mouse.clear_memory();
         context.refresh_events();
+        let mut resized = false;
         for event in context.events.iter() {
             #[allow(unreachable_patterns)]
             match event {
@@ -124,10 +306,15 @@ pub fn run_event_loop<T: Application>(
                 Event::MouseDown => context.input.mouse.on_down(),
                 Event::MouseUp => context.input.mouse.on_up(),
                 Event::MouseDoubleClick => context.input.mouse.on_double_click(),
+                Event::WindowResized(_) => resized = true,
                 Event::Close => break 'game_loop,
                 _ => {}
             }
         }
+        if resized {
+            context.apply_resolution()?;
+        }
+        context.update_mouse_position()?;
 
         app.update(&mut context, delta_millis)?;
 
@@ -138,7 +325,13 @@ pub fn run_event_loop<T: Application>(
 
         let alpha = acc_millis as f32 / FIXED_TIMESTEP_MILLIS as f32;
 
-        app.draw(&mut context.canvas()?, alpha)?;
+        let mut canvas = context.canvas()?;
+        app.draw(&mut canvas, alpha)?;
+        canvas.draw_layers()?;
+        let flushed = context.backend.borrow_mut().render_flush();
+        // the frame is presented even if its last copies failed
+        drop(canvas);
+        flushed?;
 
         if context.quit {
             break 'game_loop;
@@ -0,0 +1,238 @@
+use crate::assets::Handle;
+use crate::backend::{DecodedFont, Decoder};
+use crate::image::Image;
+use crate::vfs::Vfs;
+use crate::{Error, Result};
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+const MAX_WORKERS: usize = 4;
+
+/// An asset being loaded in the background. Becomes ready once its file was read and decoded by
+/// a loader thread and uploaded to the backend, at the start of a frame.
+pub struct Pending<T>(Rc<RefCell<Option<Result<Handle<T>>>>>);
+
+impl<T> Pending<T> {
+    pub(crate) fn new() -> Self {
+        Self(Rc::new(RefCell::new(None)))
+    }
+
+    pub(crate) fn ready(result: Result<Handle<T>>) -> Self {
+        let pending = Self::new();
+        pending.resolve(result);
+        pending
+    }
+
+    pub(crate) fn resolve(&self, result: Result<Handle<T>>) {
+        *self.0.borrow_mut() = Some(result);
+    }
+
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.0.borrow().is_some()
+    }
+
+    /// The asset once it finished loading, or the error that stopped it.
+    pub fn get(&self) -> Option<Result<Handle<T>>> {
+        self.0.borrow().clone()
+    }
+}
+
+impl<T> Clone for Pending<T> {
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
+
+/// Progress of the assets requested since the last time every request had finished.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoadProgress {
+    pub total: usize,
+    pub loaded: usize,
+    pub failed: usize,
+}
+
+impl LoadProgress {
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        self.loaded + self.failed == self.total
+    }
+
+    /// From 0 to 1, counting failed assets as done. 1 when nothing is loading.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        (self.loaded + self.failed) as f32 / self.total as f32
+    }
+}
+
+#[derive(Copy, Clone)]
+pub(crate) enum AssetKind {
+    Texture,
+    Font(u8),
+}
+
+pub(crate) struct Job {
+    pub(crate) id: u64,
+    pub(crate) path: String,
+    pub(crate) vfs: Vfs,
+    pub(crate) kind: AssetKind,
+    pub(crate) decoder: Arc<dyn Decoder>,
+}
+
+/// What was decoded for a job, and where it was read from when that was a plain file.
+pub(crate) struct Done {
+    pub(crate) id: u64,
+    pub(crate) result: Result<(Loaded, Option<String>)>,
+}
+
+pub(crate) enum Loaded {
+    Image(Image),
+    Font(DecodedFont),
+}
+
+/// Reads and decodes asset files on a small pool of threads, started on the first request.
+pub(crate) struct Loader {
+    jobs: Option<Sender<Job>>,
+    done: Option<Receiver<Done>>,
+    workers: Vec<JoinHandle<()>>,
+    next_id: u64,
+    progress: LoadProgress,
+    errors: Vec<(String, Error)>,
+}
+
+impl Loader {
+    pub(crate) fn new() -> Self {
+        Self {
+            jobs: None,
+            done: None,
+            workers: Vec::new(),
+            next_id: 0,
+            progress: LoadProgress::default(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Queues the file, returning the id its `Done` will carry.
+    pub(crate) fn request(
+        &mut self,
+        path: &str,
+        vfs: &Vfs,
+        kind: AssetKind,
+        decoder: Arc<dyn Decoder>,
+    ) -> u64 {
+        if self.progress.is_done() {
+            self.progress = LoadProgress::default();
+            self.errors.clear();
+        }
+        self.progress.total += 1;
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let job = Job {
+            id,
+            path: String::from(path),
+            vfs: vfs.clone(),
+            kind,
+            decoder,
+        };
+        // workers only stop once the sender is dropped
+        let _ = self.jobs().send(job);
+        id
+    }
+
+    /// Decoded files, ready to be uploaded.
+    pub(crate) fn finished(&self) -> Vec<Done> {
+        match &self.done {
+            Some(done) => done.try_iter().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Blocks until the next file is decoded, or returns `None` if nothing was ever requested.
+    pub(crate) fn wait(&self) -> Option<Done> {
+        self.done.as_ref()?.recv().ok()
+    }
+
+    pub(crate) fn on_loaded(&mut self) {
+        self.progress.loaded += 1;
+    }
+
+    pub(crate) fn on_failed(&mut self, path: &str, error: Error) {
+        self.progress.failed += 1;
+        self.errors.push((String::from(path), error));
+    }
+
+    pub(crate) fn progress(&self) -> LoadProgress {
+        self.progress
+    }
+
+    pub(crate) fn errors(&self) -> &[(String, Error)] {
+        &self.errors
+    }
+
+    fn jobs(&mut self) -> &Sender<Job> {
+        if self.jobs.is_none() {
+            let (jobs, job_receiver) = channel::<Job>();
+            let (done_sender, done) = channel();
+            let job_receiver = Arc::new(Mutex::new(job_receiver));
+            let count = std::thread::available_parallelism()
+                .map_or(1, |n| n.get())
+                .min(MAX_WORKERS);
+            for i in 0..count {
+                let jobs = Arc::clone(&job_receiver);
+                let done = done_sender.clone();
+                let worker = std::thread::Builder::new()
+                    .name(format!("panko-loader-{}", i))
+                    .spawn(move || work(&jobs, &done))
+                    .expect("Unable to spawn an asset loading thread.");
+                self.workers.push(worker);
+            }
+            self.jobs = Some(jobs);
+            self.done = Some(done);
+        }
+        self.jobs.as_ref().unwrap()
+    }
+}
+
+impl Drop for Loader {
+    fn drop(&mut self) {
+        // closing the queue lets the workers finish their current file and return
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn work(jobs: &Mutex<Receiver<Job>>, done: &Sender<Done>) {
+    loop {
+        let job = match jobs.lock() {
+            Ok(jobs) => jobs.recv(),
+            Err(_) => return,
+        };
+        let Ok(job) = job else {
+            return;
+        };
+        let result = job
+            .vfs
+            .read_located(&job.path)
+            .and_then(|(bytes, disk_path)| Ok((decode(&job, bytes)?, disk_path)));
+        if done.send(Done { id: job.id, result }).is_err() {
+            return;
+        }
+    }
+}
+
+fn decode(job: &Job, bytes: Vec<u8>) -> Result<Loaded> {
+    Ok(match job.kind {
+        AssetKind::Texture => Loaded::Image(job.decoder.decode_image(&bytes)?),
+        AssetKind::Font(scale) => Loaded::Font(job.decoder.decode_font(bytes, scale)?),
+    })
+}
@@ -0,0 +1,86 @@
+use crate::types::{Insets, NineSliceOptions, Rect, SliceFill};
+use alloc::vec::Vec;
+
+/// A run along one axis: where it starts and how long it is.
+type Span = (i32, u32);
+
+/// The `(src, dest)` rects of every piece of a nine-slice of a `size` texture drawn into `dest`.
+/// Empty pieces are left out.
+pub(crate) fn pieces(
+    size: (u32, u32),
+    insets: Insets,
+    dest: Rect,
+    options: &NineSliceOptions,
+) -> Vec<(Rect, Rect)> {
+    let src_columns = slices(size.0, insets.left, insets.right);
+    let src_rows = slices(size.1, insets.top, insets.bottom);
+    let (left, right) = fit(insets.left, insets.right, dest.w);
+    let (top, bottom) = fit(insets.top, insets.bottom, dest.h);
+    let dest_columns = offset(slices(dest.w, left, right), dest.x);
+    let dest_rows = offset(slices(dest.h, top, bottom), dest.y);
+
+    let mut pieces = Vec::new();
+    for row in 0..3 {
+        for column in 0..3 {
+            let fill = match (row == 1, column == 1) {
+                (true, true) => options.center,
+                (true, false) | (false, true) => options.edges,
+                (false, false) => SliceFill::Stretch,
+            };
+            let tile = fill == SliceFill::Tile;
+            let xs = chunks(src_columns[column], dest_columns[column], tile && column == 1);
+            let ys = chunks(src_rows[row], dest_rows[row], tile && row == 1);
+            for &(src_y, dest_y) in ys.iter() {
+                for &(src_x, dest_x) in xs.iter() {
+                    if src_x.1 == 0 || src_y.1 == 0 || dest_x.1 == 0 || dest_y.1 == 0 {
+                        continue;
+                    }
+                    pieces.push((
+                        Rect::new(src_x.0, src_y.0, src_x.1, src_y.1),
+                        Rect::new(dest_x.0, dest_y.0, dest_x.1, dest_y.1),
+                    ));
+                }
+            }
+        }
+    }
+    pieces
+}
+
+/// Splits `len` into its start border, middle and end border.
+fn slices(len: u32, start: u32, end: u32) -> [Span; 3] {
+    let start = start.min(len);
+    let end = end.min(len - start);
+    [
+        (0, start),
+        (start as i32, len - start - end),
+        ((len - end) as i32, end),
+    ]
+}
+
+/// Shrinks the borders proportionally when they do not both fit in `len`.
+fn fit(start: u32, end: u32, len: u32) -> (u32, u32) {
+    if start + end <= len {
+        return (start, end);
+    }
+    let start = (len as u64 * start as u64 / (start + end) as u64) as u32;
+    (start, len - start)
+}
+
+fn offset(spans: [Span; 3], by: i32) -> [Span; 3] {
+    spans.map(|(start, len)| (start + by, len))
+}
+
+/// The `(src, dest)` spans filling `dest` from `src`: one stretched span, or `src` repeated.
+fn chunks(src: Span, dest: Span, tile: bool) -> Vec<(Span, Span)> {
+    if !tile || src.1 == 0 {
+        return vec![(src, dest)];
+    }
+    let mut chunks = Vec::new();
+    let mut done = 0;
+    while done < dest.1 {
+        let len = src.1.min(dest.1 - done);
+        chunks.push(((src.0, len), (dest.0 + done as i32, len)));
+        done += len;
+    }
+    chunks
+}
@@ -0,0 +1,575 @@
+use crate::backend::{Backend, DecodedFont, Decoder};
+use crate::image::Image;
+use crate::slots::Slots;
+use crate::types::*;
+use crate::{Error, ResourceKind, Result};
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use hashbrown::HashMap;
+
+const DEFAULT_TEXTURE_SIZE: (u32, u32) = (32, 32);
+const DEFAULT_WINDOW_SIZE: (u32, u32) = (640, 480);
+const DEFAULT_GLYPHS_HEIGHT: u32 = 16;
+const DEFAULT_GLYPH_ADVANCE: u32 = 8;
+const DEFAULT_FRAME_MILLIS: u64 = 16;
+
+/// A single call made against a `RecordingBackend`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    WindowSetConfig(WindowConfig),
+    TextureCreate(TextureData),
+    TextureLoad {
+        path: String,
+        data: TextureData,
+    },
+    TextureLoadFromMemory {
+        len: usize,
+        data: TextureData,
+    },
+    TextureCreateFromPixels {
+        format: PixelFormat,
+        len: usize,
+        data: TextureData,
+    },
+    TextureUpdate {
+        texture: TextureId,
+        rect: Option<Rect>,
+        len: usize,
+    },
+    TextureDestroy(TextureId),
+    TextureReload {
+        id: TextureId,
+        path: String,
+    },
+    FontLoad {
+        path: String,
+        scale: u8,
+        data: FontData,
+    },
+    FontLoadFromMemory {
+        len: usize,
+        scale: u8,
+        data: FontData,
+    },
+    /// A font decoded on a loader thread, from a file `len` bytes long.
+    FontCreateDecoded {
+        len: usize,
+        scale: u8,
+        data: FontData,
+    },
+    FontDestroy(FontId),
+    FontReload {
+        id: FontId,
+        path: String,
+        scale: u8,
+    },
+    FontGlyphMetrics {
+        font: FontId,
+        glyph: char,
+    },
+    RenderSetLogicalScreen(Option<LogicalScreen>),
+    RenderSetTarget(Option<TextureId>),
+    RenderSetClipRect(Option<Rect>),
+    RenderSetDrawColor(Color),
+    RenderClear,
+    RenderPresent,
+    RenderCopyTexture {
+        texture: TextureId,
+        options: CopyTextureOptions,
+    },
+    RenderCopyTextureBatch {
+        texture: TextureId,
+        copies: Vec<CopyTextureOptions>,
+    },
+    RenderFillRect {
+        rect: Option<Rect>,
+        color: Color,
+    },
+    RenderDrawRect {
+        rect: Option<Rect>,
+        color: Color,
+    },
+    RenderDrawPoints {
+        points: Vec<Point>,
+        color: Color,
+    },
+    RenderDrawLines {
+        points: Vec<Point>,
+        color: Color,
+    },
+    RenderGeometry(Vec<Vertex>),
+    RenderFontGlyph {
+        font: FontId,
+        glyph: char,
+        origin: Point,
+    },
+    RenderOutputSize,
+    RenderReadPixels(Option<Rect>),
+    Log(String),
+}
+
+/// A backend that renders nothing and instead logs every call it receives as a `Command`.
+///
+/// Loaded textures get a fake size (see `set_texture_size`) and fonts report glyph metrics from
+/// a configurable function, so layout code can be exercised without any real assets. Read pixels
+/// are all zero.
+pub struct RecordingBackend {
+    log: CommandLog,
+    textures: Slots<(u32, u32)>,
+    target: Option<TextureId>,
+    window_size: (u32, u32),
+    fonts: Slots<()>,
+    texture_sizes: HashMap<String, (u32, u32)>,
+    default_texture_size: (u32, u32),
+    glyphs_height: u32,
+    glyph_metrics: Box<dyn Fn(char) -> GlyphMetrics>,
+    events: VecDeque<Event>,
+    mouse_position: (i32, i32),
+    millis: u64,
+}
+
+/// A shared view of the commands recorded by a `RecordingBackend`, usable after the backend has
+/// been moved into a `Context`.
+#[derive(Clone, Default)]
+pub struct CommandLog(Rc<RefCell<Vec<Command>>>);
+
+impl RecordingBackend {
+    pub fn new() -> Self {
+        Self {
+            log: CommandLog::default(),
+            textures: Slots::new(ResourceKind::Texture),
+            target: None,
+            window_size: DEFAULT_WINDOW_SIZE,
+            fonts: Slots::new(ResourceKind::Font),
+            texture_sizes: HashMap::new(),
+            default_texture_size: DEFAULT_TEXTURE_SIZE,
+            glyphs_height: DEFAULT_GLYPHS_HEIGHT,
+            glyph_metrics: Box::new(|_| {
+                monospace_metrics(DEFAULT_GLYPH_ADVANCE, DEFAULT_GLYPHS_HEIGHT)
+            }),
+            events: VecDeque::new(),
+            mouse_position: (0, 0),
+            millis: 0,
+        }
+    }
+
+    pub fn log(&self) -> CommandLog {
+        self.log.clone()
+    }
+
+    /// Size reported by `texture_load` for the given path.
+    pub fn set_texture_size(&mut self, path: &str, w: u32, h: u32) {
+        self.texture_sizes.insert(String::from(path), (w, h));
+    }
+
+    /// Size reported by `texture_load` for paths without an explicit size, and by
+    /// `texture_load_from_memory`.
+    pub fn set_default_texture_size(&mut self, w: u32, h: u32) {
+        self.default_texture_size = (w, h);
+    }
+
+    pub fn set_glyphs_height(&mut self, height: u32) {
+        self.glyphs_height = height;
+    }
+
+    pub fn set_glyph_metrics(&mut self, metrics: impl Fn(char) -> GlyphMetrics + 'static) {
+        self.glyph_metrics = Box::new(metrics);
+    }
+
+    pub fn set_mouse_position(&mut self, x: i32, y: i32) {
+        self.mouse_position = (x, y);
+    }
+
+    /// Queues an event to be returned by the next `events_pump`.
+    pub fn push_event(&mut self, event: Event) {
+        self.events.push_back(event);
+    }
+
+    fn record(&self, command: Command) {
+        self.log.0.borrow_mut().push(command);
+    }
+
+    /// Fails like SDL does for textures that can not be copied from.
+    fn check_source(&self, id: TextureId) -> Result {
+        self.textures.get(id.0)?;
+        if self.target == Some(id) {
+            return Err(Error::SourceIsTarget);
+        }
+        Ok(())
+    }
+
+    fn texture_size(&self, path: &str) -> (u32, u32) {
+        self.texture_sizes
+            .get(path)
+            .copied()
+            .unwrap_or(self.default_texture_size)
+    }
+
+    fn push_texture(&mut self, width: u32, height: u32) -> TextureData {
+        let id = TextureId(self.textures.insert((width, height)));
+        TextureData { id, width, height }
+    }
+}
+
+impl Default for RecordingBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandLog {
+    pub fn commands(&self) -> Vec<Command> {
+        self.0.borrow().clone()
+    }
+
+    /// Returns the recorded commands and clears the log.
+    pub fn take(&self) -> Vec<Command> {
+        core::mem::take(&mut *self.0.borrow_mut())
+    }
+
+    pub fn clear(&self) {
+        self.0.borrow_mut().clear();
+    }
+}
+
+/// Decodes PNGs for real. Anything else becomes a transparent image of the default texture size,
+/// and fonts are only measured.
+struct RecordingDecoder {
+    texture_size: (u32, u32),
+}
+
+impl Decoder for RecordingDecoder {
+    fn decode_image(&self, bytes: &[u8]) -> Result<Image> {
+        if Image::is_png(bytes) {
+            return Image::decode_png(bytes);
+        }
+        let (width, height) = self.texture_size;
+        Ok(Image {
+            width,
+            height,
+            pixels: vec![0; Image::FORMAT.buffer_size(width, height)],
+        })
+    }
+
+    fn decode_font(&self, bytes: Vec<u8>, scale: u8) -> Result<DecodedFont> {
+        Ok(DecodedFont {
+            scale,
+            font: Box::new(bytes.len()),
+        })
+    }
+}
+
+/// Glyph metrics for a monospace font where every glyph fills its advance and `height`.
+pub fn monospace_metrics(advance: u32, height: u32) -> GlyphMetrics {
+    GlyphMetrics {
+        min_x: 0,
+        max_x: advance as i32,
+        min_y: 0,
+        max_y: height as i32,
+        advance,
+    }
+}
+
+impl Backend for RecordingBackend {
+    fn window_set_config(&mut self, config: WindowConfig) -> Result {
+        self.record(Command::WindowSetConfig(config));
+        if let WindowConfig::Bordered { size, .. } | WindowConfig::Borderless(size) = config {
+            self.window_size = (size.width, size.height);
+        }
+        Ok(())
+    }
+
+    fn window_size(&mut self) -> Result<(u32, u32)> {
+        Ok(self.window_size)
+    }
+
+    fn texture_create(&mut self, w: u32, h: u32) -> Result<TextureData> {
+        let data = self.push_texture(w, h);
+        self.record(Command::TextureCreate(data));
+        Ok(data)
+    }
+
+    fn texture_load(&mut self, path: &str) -> Result<TextureData> {
+        let (w, h) = self.texture_size(path);
+        let data = self.push_texture(w, h);
+        self.record(Command::TextureLoad {
+            path: path.to_string(),
+            data,
+        });
+        Ok(data)
+    }
+
+    fn texture_load_from_memory(&mut self, bytes: &[u8]) -> Result<TextureData> {
+        let (w, h) = self.default_texture_size;
+        let data = self.push_texture(w, h);
+        self.record(Command::TextureLoadFromMemory {
+            len: bytes.len(),
+            data,
+        });
+        Ok(data)
+    }
+
+    fn texture_create_from_pixels(
+        &mut self,
+        w: u32,
+        h: u32,
+        format: PixelFormat,
+        pixels: &[u8],
+    ) -> Result<TextureData> {
+        let data = self.push_texture(w, h);
+        self.record(Command::TextureCreateFromPixels {
+            format,
+            len: pixels.len(),
+            data,
+        });
+        Ok(data)
+    }
+
+    fn texture_update(&mut self, id: TextureId, rect: Option<Rect>, pixels: &[u8]) -> Result {
+        self.record(Command::TextureUpdate {
+            texture: id,
+            rect,
+            len: pixels.len(),
+        });
+        self.textures.get(id.0)?;
+        Ok(())
+    }
+
+    fn texture_destroy(&mut self, id: TextureId) -> Result {
+        self.textures.remove(id.0);
+        self.record(Command::TextureDestroy(id));
+        Ok(())
+    }
+
+    fn texture_reload(&mut self, id: TextureId, path: &str) -> Result<TextureData> {
+        self.record(Command::TextureReload {
+            id,
+            path: path.to_string(),
+        });
+        let (width, height) = self.texture_size(path);
+        *self.textures.get_mut(id.0)? = (width, height);
+        Ok(TextureData { id, width, height })
+    }
+
+    fn font_load(&mut self, path: &str, scale: u8) -> Result<FontData> {
+        let data = FontData {
+            id: FontId(self.fonts.insert(())),
+            glyphs_height: self.glyphs_height,
+        };
+        self.record(Command::FontLoad {
+            path: path.to_string(),
+            scale,
+            data,
+        });
+        Ok(data)
+    }
+
+    fn font_load_from_memory(&mut self, bytes: &[u8], scale: u8) -> Result<FontData> {
+        let data = FontData {
+            id: FontId(self.fonts.insert(())),
+            glyphs_height: self.glyphs_height,
+        };
+        self.record(Command::FontLoadFromMemory {
+            len: bytes.len(),
+            scale,
+            data,
+        });
+        Ok(data)
+    }
+
+    fn font_destroy(&mut self, id: FontId) -> Result {
+        self.fonts.remove(id.0);
+        self.record(Command::FontDestroy(id));
+        Ok(())
+    }
+
+    fn font_reload(&mut self, id: FontId, path: &str, scale: u8) -> Result<FontData> {
+        self.record(Command::FontReload {
+            id,
+            path: path.to_string(),
+            scale,
+        });
+        self.fonts.get(id.0)?;
+        Ok(FontData {
+            id,
+            glyphs_height: self.glyphs_height,
+        })
+    }
+
+    fn font_glyph_metrics(&mut self, font: FontId, glyph: char) -> Result<GlyphMetrics> {
+        self.record(Command::FontGlyphMetrics { font, glyph });
+        self.fonts.get(font.0)?;
+        Ok((self.glyph_metrics)(glyph))
+    }
+
+    fn font_create_decoded(&mut self, font: DecodedFont) -> Result<FontData> {
+        let len = *font
+            .font
+            .downcast::<usize>()
+            .map_err(|_| Error::Backend(String::from("Font was decoded by another backend.")))?;
+        let data = FontData {
+            id: FontId(self.fonts.insert(())),
+            glyphs_height: self.glyphs_height,
+        };
+        self.record(Command::FontCreateDecoded {
+            len,
+            scale: font.scale,
+            data,
+        });
+        Ok(data)
+    }
+
+    fn decoder(&self) -> Arc<dyn Decoder> {
+        Arc::new(RecordingDecoder {
+            texture_size: self.default_texture_size,
+        })
+    }
+
+    fn render_set_logical_screen(&mut self, screen: Option<LogicalScreen>) -> Result {
+        self.record(Command::RenderSetLogicalScreen(screen));
+        Ok(())
+    }
+
+    fn render_set_target(&mut self, target: Option<TextureId>) -> Result {
+        self.record(Command::RenderSetTarget(target));
+        if let Some(id) = target {
+            self.textures.get(id.0)?;
+        }
+        self.target = target;
+        Ok(())
+    }
+
+    fn render_set_clip_rect(&mut self, rect: Option<Rect>) -> Result {
+        self.record(Command::RenderSetClipRect(rect));
+        Ok(())
+    }
+
+    fn render_set_draw_color(&mut self, color: Color) -> Result {
+        self.record(Command::RenderSetDrawColor(color));
+        Ok(())
+    }
+
+    fn render_clear(&mut self) -> Result {
+        self.record(Command::RenderClear);
+        Ok(())
+    }
+
+    fn render_present(&mut self) -> Result {
+        self.record(Command::RenderPresent);
+        self.millis += DEFAULT_FRAME_MILLIS;
+        Ok(())
+    }
+
+    fn render_flush(&mut self) -> Result {
+        Ok(())
+    }
+
+    fn render_check_copy(&mut self, texture: TextureId) -> Result {
+        self.check_source(texture)
+    }
+
+    fn render_copy_texture(&mut self, texture: TextureId, options: CopyTextureOptions) -> Result {
+        self.record(Command::RenderCopyTexture { texture, options });
+        self.check_source(texture)
+    }
+
+    fn render_copy_texture_batch(
+        &mut self,
+        texture: TextureId,
+        copies: &[CopyTextureOptions],
+    ) -> Result {
+        self.record(Command::RenderCopyTextureBatch {
+            texture,
+            copies: copies.to_vec(),
+        });
+        self.check_source(texture)
+    }
+
+    fn render_fill_rect(&mut self, rect: Option<Rect>, color: Color) -> Result {
+        self.record(Command::RenderFillRect { rect, color });
+        Ok(())
+    }
+
+    fn render_draw_rect(&mut self, rect: Option<Rect>, color: Color) -> Result {
+        self.record(Command::RenderDrawRect { rect, color });
+        Ok(())
+    }
+
+    fn render_draw_points(&mut self, points: &[Point], color: Color) -> Result {
+        self.record(Command::RenderDrawPoints {
+            points: points.to_vec(),
+            color,
+        });
+        Ok(())
+    }
+
+    fn render_draw_lines(&mut self, points: &[Point], color: Color) -> Result {
+        self.record(Command::RenderDrawLines {
+            points: points.to_vec(),
+            color,
+        });
+        Ok(())
+    }
+
+    fn render_geometry(&mut self, vertices: &[Vertex]) -> Result {
+        self.record(Command::RenderGeometry(vertices.to_vec()));
+        Ok(())
+    }
+
+    fn render_font_glyph(&mut self, font: FontId, glyph: char, origin: Point) -> Result {
+        self.record(Command::RenderFontGlyph {
+            font,
+            glyph,
+            origin,
+        });
+        self.fonts.get(font.0).copied()
+    }
+
+    fn render_output_size(&mut self) -> Result<(u32, u32)> {
+        self.record(Command::RenderOutputSize);
+        match self.target {
+            Some(id) => self.textures.get(id.0).copied(),
+            None => Ok(self.window_size),
+        }
+    }
+
+    fn render_read_pixels(&mut self, rect: Option<Rect>) -> Result<Vec<u8>> {
+        self.record(Command::RenderReadPixels(rect));
+        let (w, h) = match self.target {
+            Some(id) => *self.textures.get(id.0)?,
+            None => self.window_size,
+        };
+        let rect = rect.unwrap_or(Rect::new(0, 0, w, h));
+        if !rect.is_within(w, h) {
+            return Err(Error::OutOfBounds);
+        }
+        Ok(vec![0; PixelFormat::Rgba8.buffer_size(rect.w, rect.h)])
+    }
+
+    fn events_pump(&mut self, events: &mut Vec<Event>) {
+        for event in self.events.drain(..) {
+            // queued resizes stand for the user resizing the window
+            if let Event::WindowResized(size) = event {
+                self.window_size = (size.width, size.height);
+            }
+            events.push(event);
+        }
+    }
+
+    fn input_mouse_position(&mut self) -> Result<(i32, i32)> {
+        Ok(self.mouse_position)
+    }
+
+    fn system_get_millis(&mut self) -> Result<u64> {
+        Ok(self.millis)
+    }
+
+    fn system_log(&self, s: &str) {
+        self.record(Command::Log(s.to_string()));
+    }
+}
@@ -0,0 +1,169 @@
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+
+/// A position on the pixel grid, where `(x + 0.5, y + 0.5)` is the center of pixel `(x, y)`.
+pub(crate) type Position = (f32, f32);
+
+/// How many segments an arc of `sweep` degrees needs to stay within half a pixel of a circle of
+/// `radius` pixels.
+pub(crate) fn segments(radius: f32, sweep: f32) -> usize {
+    let full = if radius > 0.5 {
+        PI / (1.0 - 0.5 / radius).acos()
+    } else {
+        0.0
+    };
+    let full = full.clamp(8.0, 512.0);
+    ((full * sweep.abs() / 360.0).ceil() as usize).max(1)
+}
+
+/// Points along an ellipse, from `start` to `end` degrees clockwise from the right, both ends
+/// included.
+pub(crate) fn arc(
+    center: Position,
+    radius_x: f32,
+    radius_y: f32,
+    start: f32,
+    end: f32,
+    segments: usize,
+) -> Vec<Position> {
+    (0..=segments)
+        .map(|i| {
+            let angle = (start + (end - start) * i as f32 / segments as f32).to_radians();
+            let (sin, cos) = angle.sin_cos();
+            (center.0 + radius_x * cos, center.1 + radius_y * sin)
+        })
+        .collect()
+}
+
+/// Triangles joining `center` to each pair of neighbouring points of `outline`.
+pub(crate) fn fan(center: Position, outline: &[Position]) -> Vec<Position> {
+    outline
+        .windows(2)
+        .flat_map(|pair| [center, pair[0], pair[1]])
+        .collect()
+}
+
+/// Triangles filling the band between two outlines with the same number of points.
+pub(crate) fn band(inner: &[Position], outer: &[Position]) -> Vec<Position> {
+    inner
+        .windows(2)
+        .zip(outer.windows(2))
+        .flat_map(|(inner, outer)| [inner[0], outer[0], outer[1], inner[0], outer[1], inner[1]])
+        .collect()
+}
+
+/// Triangles covering lines of `thickness` joining each point to the next one. Corners are
+/// beveled so the outside of a turn has no gap, and the ends reach half a pixel past the first
+/// and last points so the pixels there are covered, as with thin lines.
+pub(crate) fn thick_polyline(points: &[Position], thickness: f32) -> Vec<Position> {
+    let mut points = points.to_vec();
+    if let Some(start) = points.first().copied() {
+        if let Some(&next) = points.iter().find(|&&point| point != start) {
+            points[0] = extend(next, start, 0.5);
+        }
+    }
+    if let Some(end) = points.last().copied() {
+        if let Some(&previous) = points.iter().rev().find(|&&point| point != end) {
+            let last = points.len() - 1;
+            points[last] = extend(previous, end, 0.5);
+        }
+    }
+    let half = thickness / 2.0;
+    let mut triangles = Vec::new();
+    // the left and right corners where the last segment ended
+    let mut previous: Option<(Position, Position)> = None;
+    for pair in points.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length == 0.0 {
+            continue;
+        }
+        let (nx, ny) = (-dy / length * half, dx / length * half);
+        let (a_left, a_right) = ((a.0 + nx, a.1 + ny), (a.0 - nx, a.1 - ny));
+        let (b_left, b_right) = ((b.0 + nx, b.1 + ny), (b.0 - nx, b.1 - ny));
+        if let Some((left, right)) = previous {
+            triangles.extend([a, left, a_left, a, right, a_right]);
+        }
+        triangles.extend([a_left, b_left, b_right, a_left, b_right, a_right]);
+        previous = Some((b_left, b_right));
+    }
+    triangles
+}
+
+/// Moves `to` further away from `from` by `by`.
+fn extend(from: Position, to: Position, by: f32) -> Position {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    (to.0 + dx / length * by, to.1 + dy / length * by)
+}
+
+/// Triangles filling a simple polygon, convex or not, by clipping its ears. Whatever is left
+/// of a self-intersecting polygon once no more ears can be found is not filled.
+pub(crate) fn triangulate(points: &[Position]) -> Vec<Position> {
+    let mut points = points;
+    if points.len() > 1 && points.first() == points.last() {
+        points = &points[..points.len() - 1];
+    }
+    let winding = signed_area(points).signum();
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    let mut triangles = Vec::new();
+    let mut i = 0;
+    let mut misses = 0;
+    while remaining.len() > 3 && misses < remaining.len() {
+        let n = remaining.len();
+        let (a, b, c) = (
+            remaining[(i + n - 1) % n],
+            remaining[i],
+            remaining[(i + 1) % n],
+        );
+        let turn = cross(points[a], points[b], points[c]);
+        if turn == 0.0 {
+            // a point in the middle of a straight edge adds nothing
+            remaining.remove(i);
+            misses = 0;
+        } else if turn.signum() == winding
+            && !remaining.iter().any(|&p| {
+                p != a && p != b && p != c && inside(points[p], [points[a], points[b], points[c]])
+            })
+        {
+            triangles.extend([points[a], points[b], points[c]]);
+            remaining.remove(i);
+            misses = 0;
+        } else {
+            i += 1;
+            misses += 1;
+        }
+        i %= remaining.len();
+    }
+    if remaining.len() == 3 {
+        triangles.extend(remaining.iter().map(|&p| points[p]));
+    }
+    triangles
+}
+
+/// Twice the area of the polygon, positive when its points go clockwise on the screen.
+fn signed_area(points: &[Position]) -> f32 {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum()
+}
+
+/// Positive when `a`, `b`, `c` turn clockwise on the screen, zero when they are in line.
+fn cross(a: Position, b: Position, c: Position) -> f32 {
+    (b.0 - a.0) * (c.1 - b.1) - (b.1 - a.1) * (c.0 - b.0)
+}
+
+/// Whether `point` is inside the triangle or on its edges.
+fn inside(point: Position, [a, b, c]: [Position; 3]) -> bool {
+    let d1 = cross(a, b, point);
+    let d2 = cross(b, c, point);
+    let d3 = cross(c, a, point);
+    let negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(negative && positive)
+}
@@ -0,0 +1,127 @@
+use crate::types::ResourceId;
+use crate::{Error, ResourceKind, Result};
+use alloc::vec::Vec;
+
+/// Storage for backend resources addressed by generational `ResourceId`s.
+///
+/// Removing a value frees its slot for reuse and bumps the slot's generation, so ids of removed
+/// values are reported as stale instead of silently referring to whatever took their place.
+pub struct Slots<T> {
+    kind: ResourceKind,
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+    len: usize,
+}
+
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+impl<T> Slots<T> {
+    pub fn new(kind: ResourceKind) -> Self {
+        Self::with_capacity(kind, 0)
+    }
+
+    pub fn with_capacity(kind: ResourceKind, capacity: usize) -> Self {
+        Self {
+            kind,
+            slots: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Number of live values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of slots ever allocated, live or free.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn insert(&mut self, value: T) -> ResourceId {
+        self.len += 1;
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.value = Some(value);
+            return ResourceId::new(index, slot.generation);
+        }
+        let index = self.slots.len() as u32;
+        self.slots.push(Slot {
+            generation: 0,
+            value: Some(value),
+        });
+        ResourceId::new(index, 0)
+    }
+
+    pub fn get(&self, id: ResourceId) -> Result<&T> {
+        let slot = self.slot(id)?;
+        slot.value.as_ref().ok_or(Error::StaleResource(self.kind))
+    }
+
+    pub fn get_mut(&mut self, id: ResourceId) -> Result<&mut T> {
+        let kind = self.kind;
+        let slot = self.slot_mut(id)?;
+        slot.value.as_mut().ok_or(Error::StaleResource(kind))
+    }
+
+    /// Removes the value, returning `None` if the id is stale or invalid.
+    pub fn remove(&mut self, id: ResourceId) -> Option<T> {
+        let slot = self.slot_mut(id).ok()?;
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(id.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ResourceId, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let id = ResourceId::new(index as u32, slot.generation);
+            slot.value.as_ref().map(|value| (id, value))
+        })
+    }
+
+    /// Removes every value, freeing all slots.
+    pub fn drain(&mut self) -> impl Iterator<Item = T> {
+        self.len = 0;
+        self.free = (0..self.slots.len() as u32).rev().collect();
+        let values = self
+            .slots
+            .iter_mut()
+            .filter_map(|slot| {
+                let value = slot.value.take()?;
+                slot.generation = slot.generation.wrapping_add(1);
+                Some(value)
+            })
+            .collect::<Vec<_>>();
+        values.into_iter()
+    }
+
+    fn slot(&self, id: ResourceId) -> Result<&Slot<T>> {
+        match self.slots.get(id.index as usize) {
+            Some(slot) if id.generation == slot.generation => Ok(slot),
+            Some(slot) if id.generation < slot.generation => Err(Error::StaleResource(self.kind)),
+            _ => Err(Error::InvalidResource(self.kind)),
+        }
+    }
+
+    fn slot_mut(&mut self, id: ResourceId) -> Result<&mut Slot<T>> {
+        let kind = self.kind;
+        match self.slots.get_mut(id.index as usize) {
+            Some(slot) if id.generation == slot.generation => Ok(slot),
+            Some(slot) if id.generation < slot.generation => Err(Error::StaleResource(kind)),
+            _ => Err(Error::InvalidResource(kind)),
+        }
+    }
+}
@@ -1,59 +1,138 @@
-use crate::{BackendRef, BackendWeakRef, Result};
-use crate::types::{TextureData, TextureId};
+use crate::{BackendRef, BackendWeakRef, Error, Result};
+use crate::types::{PixelFormat, Rect, TextureData, TextureId};
 use alloc::rc::Rc;
 use alloc::rc::Weak;
+use alloc::string::String;
+use core::cell::Cell;
 
 #[derive(Copy, Clone, Debug)]
 pub enum TextureKind {
     Static,
     Target,
+    /// Created from pixels, and rewritable with `Texture::update`.
+    Streaming,
+}
+
+/// Something `Canvas::copy_texture` can draw: a whole texture, or a region of one.
+pub trait TextureSource {
+    fn texture_id(&self) -> TextureId;
+
+    /// Size of the region, or of the whole texture.
+    fn size(&self) -> (u32, u32);
+
+    /// The part of the texture covered, or `None` for all of it. The `src` rect of
+    /// `CopyTextureOptions` is relative to this region.
+    fn region(&self) -> Option<Rect>;
 }
 
 pub struct Texture {
     pub(crate) id: TextureId,
     backend: BackendWeakRef,
     kind: TextureKind,
-    width: u32,
-    height: u32,
+    /// Shared so the size can follow the backend texture when it is reloaded.
+    size: Rc<Cell<(u32, u32)>>,
 }
 
 impl Texture {
     pub(crate) fn new_static(backend: &BackendRef, path: &str) -> Result<Self> {
-        let TextureData { id, width, height } = backend.borrow_mut().texture_load(path)?;
-        Ok(Self {
-            id,
-            kind: TextureKind::Static,
-            width,
-            height,
-            backend: Rc::downgrade(backend),
-        })
+        let data = backend.borrow_mut().texture_load(path)?;
+        Ok(Self::new(backend, TextureKind::Static, data))
+    }
+
+    pub(crate) fn new_static_from_memory(backend: &BackendRef, bytes: &[u8]) -> Result<Self> {
+        let data = backend.borrow_mut().texture_load_from_memory(bytes)?;
+        Ok(Self::new(backend, TextureKind::Static, data))
     }
 
     pub(crate) fn new_target(backend: &BackendRef, w: u32, h: u32) -> Result<Self> {
-        let TextureData { id, width, height } = backend.borrow_mut().texture_create(w, h)?;
-        Ok(Self {
+        let data = backend.borrow_mut().texture_create(w, h)?;
+        Ok(Self::new(backend, TextureKind::Target, data))
+    }
+
+    pub(crate) fn new_streaming(
+        backend: &BackendRef,
+        w: u32,
+        h: u32,
+        format: PixelFormat,
+        pixels: &[u8],
+    ) -> Result<Self> {
+        let data = backend
+            .borrow_mut()
+            .texture_create_from_pixels(w, h, format, pixels)?;
+        Ok(Self::new(backend, TextureKind::Streaming, data))
+    }
+
+    /// A static texture from pixels decoded elsewhere, such as on a loader thread.
+    pub(crate) fn new_static_from_pixels(
+        backend: &BackendRef,
+        w: u32,
+        h: u32,
+        format: PixelFormat,
+        pixels: &[u8],
+    ) -> Result<Self> {
+        let data = backend
+            .borrow_mut()
+            .texture_create_from_pixels(w, h, format, pixels)?;
+        Ok(Self::new(backend, TextureKind::Static, data))
+    }
+
+    fn new(backend: &BackendRef, kind: TextureKind, data: TextureData) -> Self {
+        let TextureData { id, width, height } = data;
+        Self {
             id,
-            kind: TextureKind::Target,
-            width,
-            height,
+            kind,
+            size: Rc::new(Cell::new((width, height))),
             backend: Rc::downgrade(backend),
-        })
+        }
     }
 
     #[inline]
     pub fn width(&self) -> u32 {
-        self.width
+        self.size.get().0
     }
 
     #[inline]
     pub fn height(&self) -> u32 {
-        self.height
+        self.size.get().1
     }
 
     #[inline]
     pub fn kind(&self) -> TextureKind {
         self.kind
     }
+
+    /// Overwrites `rect`, or the whole texture, with pixels in the format the texture was created
+    /// with. Only streaming textures can be updated.
+    pub fn update(&self, rect: Option<Rect>, pixels: &[u8]) -> Result {
+        let TextureKind::Streaming = self.kind else {
+            return Err(Error::NotStreaming);
+        };
+        let backend = self
+            .backend
+            .upgrade()
+            .ok_or(Error::Backend(String::from("Backend was already dropped.")))?;
+        let result = backend.borrow_mut().texture_update(self.id, rect, pixels);
+        result
+    }
+
+    #[cfg_attr(not(feature = "hot-reload"), allow(dead_code))]
+    pub(crate) fn shared_size(&self) -> &Rc<Cell<(u32, u32)>> {
+        &self.size
+    }
+}
+
+impl TextureSource for Texture {
+    fn texture_id(&self) -> TextureId {
+        self.id
+    }
+
+    fn size(&self) -> (u32, u32) {
+        self.size.get()
+    }
+
+    fn region(&self) -> Option<Rect> {
+        None
+    }
 }
 
 impl Drop for Texture {
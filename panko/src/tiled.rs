@@ -0,0 +1,837 @@
+//! Reads maps and tilesets saved by the Tiled editor, as TMX/TSX or JSON.
+
+use crate::assets::sibling_path;
+use crate::tilemap::{
+    Layer, MapObject, ObjectLayer, ObjectShape, Properties, PropertyValue, Tile, TileLayer,
+    Tilemap, Tileset,
+};
+use crate::types::{Color, Point};
+use crate::{Error, Result};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use base64::Engine;
+use core::fmt::Display;
+use core::str::FromStr;
+use hashbrown::HashMap;
+use roxmltree::Node;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Reads the files a map refers to, by their path.
+pub(crate) type ReadFile<'a> = dyn FnMut(&str) -> Result<Vec<u8>> + 'a;
+
+/// Parses the map at `path`, reading external tilesets through `read`. Tileset images are not
+/// loaded: the map is returned without textures.
+pub(crate) fn parse(path: &str, bytes: &[u8], read: &mut ReadFile) -> Result<Tilemap> {
+    if is_xml(bytes) {
+        tmx::parse_map(path, text(bytes)?, read)
+    } else {
+        json::parse_map(path, bytes, read)
+    }
+}
+
+/// A tileset in a file of its own, in either format.
+fn external_tileset(path: &str, first_gid: u32, read: &mut ReadFile) -> Result<Tileset> {
+    let bytes = read(path)?;
+    if is_xml(&bytes) {
+        tmx::parse_tileset_file(path, first_gid, text(&bytes)?)
+    } else {
+        json::parse_tileset_file(path, first_gid, &bytes)
+    }
+}
+
+fn is_xml(bytes: &[u8]) -> bool {
+    bytes
+        .iter()
+        .find(|b| !b.is_ascii_whitespace())
+        .is_some_and(|&b| b == b'<')
+}
+
+fn text(bytes: &[u8]) -> Result<&str> {
+    core::str::from_utf8(bytes).map_err(invalid)
+}
+
+fn invalid(reason: impl Display) -> Error {
+    Error::Decode(format!("Invalid Tiled map: {}", reason))
+}
+
+fn unsupported(what: &str) -> Error {
+    Error::Decode(format!("{} are not supported.", what))
+}
+
+fn check_orientation(orientation: &str, infinite: bool) -> Result {
+    if orientation != "orthogonal" {
+        return Err(unsupported("Maps that are not orthogonal"));
+    }
+    if infinite {
+        return Err(unsupported("Infinite maps"));
+    }
+    Ok(())
+}
+
+fn tiles(width: u32, height: u32, gids: Vec<u32>) -> Result<Vec<Option<Tile>>> {
+    if gids.len() != (width * height) as usize {
+        return Err(invalid(format!(
+            "layer has {} tiles instead of {}",
+            gids.len(),
+            width * height
+        )));
+    }
+    Ok(gids.into_iter().map(Tile::from_raw).collect())
+}
+
+/// Tile data stored as base64, in little-endian `u32`s once decompressed.
+fn decode_base64(data: &str, compression: Option<&str>) -> Result<Vec<u32>> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(data.trim())
+        .map_err(invalid)?;
+    let bytes = match compression {
+        None | Some("") => bytes,
+        Some("zlib") => miniz_oxide::inflate::decompress_to_vec_zlib(&bytes)
+            .map_err(|e| invalid(format!("{:?}", e)))?,
+        Some("gzip") => miniz_oxide::inflate::decompress_to_vec(gzip_body(&bytes)?)
+            .map_err(|e| invalid(format!("{:?}", e)))?,
+        Some(other) => {
+            return Err(Error::Decode(format!(
+                "Compression '{}' is not supported.",
+                other
+            )))
+        }
+    };
+    if bytes.len() % 4 != 0 {
+        return Err(invalid("tile data is not made of 32-bit ids"));
+    }
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
+
+/// The deflate stream of a gzip member, without its header and trailer.
+fn gzip_body(bytes: &[u8]) -> Result<&[u8]> {
+    const FHCRC: u8 = 2;
+    const FEXTRA: u8 = 4;
+    const FNAME: u8 = 8;
+    const FCOMMENT: u8 = 16;
+
+    let truncated = || invalid("truncated gzip data");
+    if bytes.len() < 18 || bytes[0..3] != [0x1f, 0x8b, 8] {
+        return Err(invalid("bad gzip header"));
+    }
+    let flags = bytes[3];
+    let mut at = 10;
+    if flags & FEXTRA != 0 {
+        let len = u16::from_le_bytes([bytes[at], bytes[at + 1]]) as usize;
+        at += 2 + len;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let end = bytes.get(at..).ok_or_else(truncated)?;
+            at += end.iter().position(|&b| b == 0).ok_or_else(truncated)? + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        at += 2;
+    }
+    bytes.get(at..bytes.len() - 8).ok_or_else(truncated)
+}
+
+/// `#AARRGGBB` or `#RRGGBB`. Tiled writes an empty string for unset colors.
+fn parse_color(value: &str) -> Result<Color> {
+    let hex = value.trim_start_matches('#');
+    if hex.is_empty() {
+        return Ok(Color::default());
+    }
+    let wrong = || invalid(format!("color '{}'", value));
+    let [a, r, g, b] = u32::from_str_radix(hex, 16)
+        .map_err(|_| wrong())?
+        .to_be_bytes();
+    match hex.len() {
+        6 => Ok(Color::new(r, g, b, 255)),
+        8 => Ok(Color::new(r, g, b, a)),
+        _ => Err(wrong()),
+    }
+}
+
+/// Fills in the columns of tilesets saved without them, from the width of their image.
+pub(crate) fn tileset_columns(
+    columns: u32,
+    image_width: u32,
+    tile_width: u32,
+    margin: u32,
+    spacing: u32,
+) -> u32 {
+    if columns > 0 || tile_width == 0 {
+        return columns;
+    }
+    (image_width.saturating_sub(2 * margin) + spacing) / (tile_width + spacing)
+}
+
+mod tmx {
+    use super::*;
+
+    pub(super) fn parse_map(path: &str, text: &str, read: &mut ReadFile) -> Result<Tilemap> {
+        let document = roxmltree::Document::parse(text).map_err(invalid)?;
+        let map = document.root_element();
+        if !map.has_tag_name("map") {
+            return Err(invalid("root element is not <map>"));
+        }
+        check_orientation(
+            map.attribute("orientation").unwrap_or("orthogonal"),
+            attr(map, "infinite")?.unwrap_or(0) != 0,
+        )?;
+
+        let mut tilesets = Vec::new();
+        for node in map.children().filter(|n| n.has_tag_name("tileset")) {
+            let first_gid = required(node, "firstgid")?;
+            tilesets.push(match node.attribute("source") {
+                Some(source) => external_tileset(&sibling_path(path, source), first_gid, read)?,
+                None => parse_tileset(path, first_gid, node)?,
+            });
+        }
+        tilesets.sort_by_key(|tileset| tileset.first_gid);
+
+        let mut layers = Vec::new();
+        parse_layers(map, Point::new(0, 0), true, &mut layers)?;
+
+        Ok(Tilemap {
+            width: required(map, "width")?,
+            height: required(map, "height")?,
+            tile_width: required(map, "tilewidth")?,
+            tile_height: required(map, "tileheight")?,
+            tilesets,
+            textures: Vec::new(),
+            layers,
+            properties: properties(map)?,
+        })
+    }
+
+    pub(super) fn parse_tileset_file(path: &str, first_gid: u32, text: &str) -> Result<Tileset> {
+        let document = roxmltree::Document::parse(text).map_err(invalid)?;
+        let node = document.root_element();
+        if !node.has_tag_name("tileset") {
+            return Err(invalid("root element is not <tileset>"));
+        }
+        parse_tileset(path, first_gid, node)
+    }
+
+    /// `path` is the file the tileset is in, which its image is relative to.
+    fn parse_tileset(path: &str, first_gid: u32, node: Node) -> Result<Tileset> {
+        let image = node
+            .children()
+            .find(|n| n.has_tag_name("image"))
+            .ok_or_else(|| unsupported("Tilesets made of separate images"))?;
+        let tile_width = required(node, "tilewidth")?;
+        let spacing = attr(node, "spacing")?.unwrap_or(0);
+        let margin = attr(node, "margin")?.unwrap_or(0);
+
+        let mut tile_properties = HashMap::new();
+        for tile in node.children().filter(|n| n.has_tag_name("tile")) {
+            let properties = properties(tile)?;
+            if !properties.is_empty() {
+                tile_properties.insert(required(tile, "id")?, properties);
+            }
+        }
+
+        Ok(Tileset {
+            name: node.attribute("name").unwrap_or_default().to_string(),
+            first_gid,
+            tile_width,
+            tile_height: required(node, "tileheight")?,
+            spacing,
+            margin,
+            columns: tileset_columns(
+                attr(node, "columns")?.unwrap_or(0),
+                attr(image, "width")?.unwrap_or(0),
+                tile_width,
+                margin,
+                spacing,
+            ),
+            tile_count: attr(node, "tilecount")?.unwrap_or(u32::MAX),
+            image: sibling_path(path, required::<String>(image, "source")?.as_str()),
+            properties: properties(node)?,
+            tile_properties,
+        })
+    }
+
+    /// Adds the layers under `parent`, flattening groups into their layers.
+    fn parse_layers(parent: Node, offset: Point, visible: bool, layers: &mut Vec<Layer>) -> Result {
+        for node in parent.children().filter(Node::is_element) {
+            let offset = Point::new(
+                offset.x + attr::<f32>(node, "offsetx")?.unwrap_or(0.0) as i32,
+                offset.y + attr::<f32>(node, "offsety")?.unwrap_or(0.0) as i32,
+            );
+            let visible = visible && attr(node, "visible")?.unwrap_or(1) != 0;
+            let name = node.attribute("name").unwrap_or_default().to_string();
+            match node.tag_name().name() {
+                "layer" => {
+                    let width = required(node, "width")?;
+                    let height = required(node, "height")?;
+                    let data = node
+                        .children()
+                        .find(|n| n.has_tag_name("data"))
+                        .ok_or_else(|| invalid(format!("layer '{}' has no data", name)))?;
+                    layers.push(Layer::Tiles(TileLayer {
+                        width,
+                        height,
+                        visible,
+                        opacity: attr(node, "opacity")?.unwrap_or(1.0),
+                        offset,
+                        properties: properties(node)?,
+                        tiles: tiles(width, height, parse_data(data)?)?,
+                        name,
+                    }));
+                }
+                "objectgroup" => layers.push(Layer::Objects(ObjectLayer {
+                    visible,
+                    offset,
+                    properties: properties(node)?,
+                    objects: node
+                        .children()
+                        .filter(|n| n.has_tag_name("object"))
+                        .map(parse_object)
+                        .collect::<Result<_>>()?,
+                    name,
+                })),
+                "group" => parse_layers(node, offset, visible, layers)?,
+                // image layers are not drawn, and the rest are not layers
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_data(data: Node) -> Result<Vec<u32>> {
+        if data.children().any(|n| n.has_tag_name("chunk")) {
+            return Err(unsupported("Infinite maps"));
+        }
+        let text = data.text().unwrap_or_default();
+        match data.attribute("encoding") {
+            Some("csv") => text
+                .split(',')
+                .map(|gid| gid.trim().parse().map_err(invalid))
+                .collect(),
+            Some("base64") => decode_base64(text, data.attribute("compression")),
+            Some(other) => Err(Error::Decode(format!(
+                "Encoding '{}' is not supported.",
+                other
+            ))),
+            None => data
+                .children()
+                .filter(|n| n.has_tag_name("tile"))
+                .map(|tile| Ok(attr(tile, "gid")?.unwrap_or(0)))
+                .collect(),
+        }
+    }
+
+    fn parse_object(node: Node) -> Result<MapObject> {
+        let mut shape = ObjectShape::Rectangle;
+        for child in node.children().filter(Node::is_element) {
+            shape = match child.tag_name().name() {
+                "ellipse" => ObjectShape::Ellipse,
+                "point" => ObjectShape::Point,
+                "polygon" => ObjectShape::Polygon(points(child)?),
+                "polyline" => ObjectShape::Polyline(points(child)?),
+                "text" => ObjectShape::Text(child.text().unwrap_or_default().to_string()),
+                _ => continue,
+            };
+        }
+        Ok(MapObject {
+            id: attr(node, "id")?.unwrap_or(0),
+            name: node.attribute("name").unwrap_or_default().to_string(),
+            class: node
+                .attribute("class")
+                .or(node.attribute("type"))
+                .unwrap_or_default()
+                .to_string(),
+            x: attr(node, "x")?.unwrap_or(0.0),
+            y: attr(node, "y")?.unwrap_or(0.0),
+            width: attr(node, "width")?.unwrap_or(0.0),
+            height: attr(node, "height")?.unwrap_or(0.0),
+            rotation: attr(node, "rotation")?.unwrap_or(0.0),
+            visible: attr(node, "visible")?.unwrap_or(1) != 0,
+            tile: attr(node, "gid")?.and_then(Tile::from_raw),
+            shape,
+            properties: properties(node)?,
+        })
+    }
+
+    /// The `points` of a polygon or polyline: `x,y` pairs separated by spaces.
+    fn points(node: Node) -> Result<Vec<(f32, f32)>> {
+        required::<String>(node, "points")?
+            .split_whitespace()
+            .map(|point| {
+                let (x, y) = point
+                    .split_once(',')
+                    .ok_or_else(|| invalid(format!("point '{}'", point)))?;
+                Ok((x.parse().map_err(invalid)?, y.parse().map_err(invalid)?))
+            })
+            .collect()
+    }
+
+    /// The `<properties>` directly under `node`.
+    fn properties(node: Node) -> Result<Properties> {
+        let mut values = HashMap::new();
+        let Some(list) = node.children().find(|n| n.has_tag_name("properties")) else {
+            return Ok(Properties(values));
+        };
+        for property in list.children().filter(|n| n.has_tag_name("property")) {
+            let name: String = required(property, "name")?;
+            // long strings are written as text instead of an attribute
+            let value = property
+                .attribute("value")
+                .or(property.text())
+                .unwrap_or_default();
+            let wrong = || invalid(format!("property '{}'", name));
+            let value = match property.attribute("type").unwrap_or("string") {
+                "int" => PropertyValue::Int(value.parse().map_err(|_| wrong())?),
+                "float" => PropertyValue::Float(value.parse().map_err(|_| wrong())?),
+                "bool" => PropertyValue::Bool(value == "true"),
+                "color" => PropertyValue::Color(parse_color(value)?),
+                "file" => PropertyValue::File(value.to_string()),
+                "object" => PropertyValue::Object(value.parse().map_err(|_| wrong())?),
+                "class" => PropertyValue::Class(properties(property)?),
+                _ => PropertyValue::String(value.to_string()),
+            };
+            values.insert(name, value);
+        }
+        Ok(Properties(values))
+    }
+
+    fn attr<T: FromStr>(node: Node, name: &str) -> Result<Option<T>> {
+        node.attribute(name)
+            .map(|value| {
+                value.parse().map_err(|_| {
+                    invalid(format!(
+                        "<{}> has an invalid {} '{}'",
+                        node.tag_name().name(),
+                        name,
+                        value
+                    ))
+                })
+            })
+            .transpose()
+    }
+
+    fn required<T: FromStr>(node: Node, name: &str) -> Result<T> {
+        attr(node, name)?
+            .ok_or_else(|| invalid(format!("<{}> has no {}", node.tag_name().name(), name)))
+    }
+}
+
+mod json {
+    use super::*;
+
+    pub(super) fn parse_map(path: &str, bytes: &[u8], read: &mut ReadFile) -> Result<Tilemap> {
+        let map: JsonMap = serde_json::from_slice(bytes).map_err(invalid)?;
+        check_orientation(&map.orientation, map.infinite)?;
+
+        let mut tilesets = map
+            .tilesets
+            .into_iter()
+            .map(|tileset| match tileset.source {
+                Some(ref source) => {
+                    external_tileset(&sibling_path(path, source), tileset.firstgid, read)
+                }
+                None => {
+                    let first_gid = tileset.firstgid;
+                    tileset.into_tileset(path, first_gid)
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        tilesets.sort_by_key(|tileset| tileset.first_gid);
+
+        let mut layers = Vec::new();
+        add_layers(map.layers, Point::new(0, 0), true, &mut layers)?;
+
+        Ok(Tilemap {
+            width: map.width,
+            height: map.height,
+            tile_width: map.tilewidth,
+            tile_height: map.tileheight,
+            tilesets,
+            textures: Vec::new(),
+            layers,
+            properties: properties(map.properties)?,
+        })
+    }
+
+    pub(super) fn parse_tileset_file(path: &str, first_gid: u32, bytes: &[u8]) -> Result<Tileset> {
+        let tileset: JsonTileset = serde_json::from_slice(bytes).map_err(invalid)?;
+        tileset.into_tileset(path, first_gid)
+    }
+
+    fn add_layers(
+        json: Vec<JsonLayer>,
+        offset: Point,
+        visible: bool,
+        layers: &mut Vec<Layer>,
+    ) -> Result {
+        for layer in json {
+            let common = match &layer {
+                JsonLayer::Tiles { common, .. }
+                | JsonLayer::Objects { common, .. }
+                | JsonLayer::Group { common, .. } => common,
+                JsonLayer::Other => continue,
+            };
+            let offset = Point::new(
+                offset.x + common.offsetx as i32,
+                offset.y + common.offsety as i32,
+            );
+            let visible = visible && common.visible;
+            match layer {
+                JsonLayer::Tiles {
+                    common,
+                    width,
+                    height,
+                    data,
+                    encoding,
+                    compression,
+                    chunks,
+                } => {
+                    if chunks.is_some() {
+                        return Err(unsupported("Infinite maps"));
+                    }
+                    let gids = match (data, encoding.as_deref()) {
+                        (JsonData::Gids(gids), None | Some("csv")) => gids,
+                        (JsonData::Base64(data), Some("base64")) => {
+                            decode_base64(&data, compression.as_deref())?
+                        }
+                        _ => return Err(invalid(format!("layer '{}' data", common.name))),
+                    };
+                    layers.push(Layer::Tiles(TileLayer {
+                        width,
+                        height,
+                        visible,
+                        opacity: common.opacity,
+                        offset,
+                        properties: properties(common.properties)?,
+                        tiles: tiles(width, height, gids)?,
+                        name: common.name,
+                    }));
+                }
+                JsonLayer::Objects { common, objects } => {
+                    layers.push(Layer::Objects(ObjectLayer {
+                        visible,
+                        offset,
+                        properties: properties(common.properties)?,
+                        objects: objects
+                            .into_iter()
+                            .map(JsonObject::into_object)
+                            .collect::<Result<_>>()?,
+                        name: common.name,
+                    }))
+                }
+                JsonLayer::Group { layers: group, .. } => {
+                    add_layers(group, offset, visible, layers)?
+                }
+                JsonLayer::Other => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn properties(json: Vec<JsonProperty>) -> Result<Properties> {
+        json.into_iter()
+            .map(|property| {
+                let value = property_value(&property.name, &property.kind, property.value)?;
+                Ok((property.name, value))
+            })
+            .collect::<Result<_>>()
+            .map(Properties)
+    }
+
+    fn property_value(name: &str, kind: &str, value: Value) -> Result<PropertyValue> {
+        let wrong = || invalid(format!("property '{}'", name));
+        Ok(match kind {
+            "int" => PropertyValue::Int(value.as_f64().ok_or_else(wrong)? as i64),
+            "float" => PropertyValue::Float(value.as_f64().ok_or_else(wrong)?),
+            "bool" => PropertyValue::Bool(value.as_bool().ok_or_else(wrong)?),
+            "color" => PropertyValue::Color(parse_color(value.as_str().ok_or_else(wrong)?)?),
+            "file" => PropertyValue::File(value.as_str().ok_or_else(wrong)?.to_string()),
+            "object" => PropertyValue::Object(value.as_u64().ok_or_else(wrong)? as u32),
+            // members of a class only carry their JSON type
+            "class" => PropertyValue::Class(Properties(
+                value
+                    .as_object()
+                    .ok_or_else(wrong)?
+                    .iter()
+                    .map(|(name, value)| {
+                        let kind = match value {
+                            Value::Bool(_) => "bool",
+                            Value::Number(n) if n.is_f64() => "float",
+                            Value::Number(_) => "int",
+                            Value::Object(_) => "class",
+                            _ => "string",
+                        };
+                        Ok((name.clone(), property_value(name, kind, value.clone())?))
+                    })
+                    .collect::<Result<_>>()?,
+            )),
+            _ => PropertyValue::String(match value {
+                Value::String(value) => value,
+                value => value.to_string(),
+            }),
+        })
+    }
+
+    #[derive(Deserialize)]
+    struct JsonMap {
+        width: u32,
+        height: u32,
+        tilewidth: u32,
+        tileheight: u32,
+        #[serde(default = "orthogonal")]
+        orientation: String,
+        #[serde(default)]
+        infinite: bool,
+        #[serde(default)]
+        tilesets: Vec<JsonTileset>,
+        #[serde(default)]
+        layers: Vec<JsonLayer>,
+        #[serde(default)]
+        properties: Vec<JsonProperty>,
+    }
+
+    fn orthogonal() -> String {
+        String::from("orthogonal")
+    }
+
+    /// Either a whole tileset, or the `firstgid` and `source` of an external one.
+    #[derive(Deserialize)]
+    struct JsonTileset {
+        #[serde(default)]
+        firstgid: u32,
+        source: Option<String>,
+        #[serde(default)]
+        name: String,
+        #[serde(default)]
+        tilewidth: u32,
+        #[serde(default)]
+        tileheight: u32,
+        #[serde(default)]
+        spacing: u32,
+        #[serde(default)]
+        margin: u32,
+        #[serde(default)]
+        columns: u32,
+        tilecount: Option<u32>,
+        image: Option<String>,
+        #[serde(default)]
+        imagewidth: u32,
+        #[serde(default)]
+        tiles: Vec<JsonTile>,
+        #[serde(default)]
+        properties: Vec<JsonProperty>,
+    }
+
+    impl JsonTileset {
+        fn into_tileset(self, path: &str, first_gid: u32) -> Result<Tileset> {
+            let image = self
+                .image
+                .ok_or_else(|| unsupported("Tilesets made of separate images"))?;
+            let mut tile_properties = HashMap::new();
+            for tile in self.tiles {
+                let properties = properties(tile.properties)?;
+                if !properties.is_empty() {
+                    tile_properties.insert(tile.id, properties);
+                }
+            }
+            Ok(Tileset {
+                name: self.name,
+                first_gid,
+                tile_width: self.tilewidth,
+                tile_height: self.tileheight,
+                spacing: self.spacing,
+                margin: self.margin,
+                columns: tileset_columns(
+                    self.columns,
+                    self.imagewidth,
+                    self.tilewidth,
+                    self.margin,
+                    self.spacing,
+                ),
+                tile_count: self.tilecount.unwrap_or(u32::MAX),
+                image: sibling_path(path, &image),
+                properties: properties(self.properties)?,
+                tile_properties,
+            })
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct JsonTile {
+        id: u32,
+        #[serde(default)]
+        properties: Vec<JsonProperty>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(tag = "type")]
+    enum JsonLayer {
+        #[serde(rename = "tilelayer")]
+        Tiles {
+            #[serde(flatten)]
+            common: JsonLayerCommon,
+            #[serde(default)]
+            width: u32,
+            #[serde(default)]
+            height: u32,
+            #[serde(default)]
+            data: JsonData,
+            encoding: Option<String>,
+            compression: Option<String>,
+            chunks: Option<Value>,
+        },
+        #[serde(rename = "objectgroup")]
+        Objects {
+            #[serde(flatten)]
+            common: JsonLayerCommon,
+            #[serde(default)]
+            objects: Vec<JsonObject>,
+        },
+        #[serde(rename = "group")]
+        Group {
+            #[serde(flatten)]
+            common: JsonLayerCommon,
+            #[serde(default)]
+            layers: Vec<JsonLayer>,
+        },
+        /// Image layers, which are not drawn.
+        #[serde(other)]
+        Other,
+    }
+
+    #[derive(Deserialize)]
+    struct JsonLayerCommon {
+        #[serde(default)]
+        name: String,
+        #[serde(default = "yes")]
+        visible: bool,
+        #[serde(default = "opaque")]
+        opacity: f32,
+        #[serde(default)]
+        offsetx: f32,
+        #[serde(default)]
+        offsety: f32,
+        #[serde(default)]
+        properties: Vec<JsonProperty>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum JsonData {
+        Gids(Vec<u32>),
+        Base64(String),
+    }
+
+    impl Default for JsonData {
+        fn default() -> Self {
+            JsonData::Gids(Vec::new())
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct JsonObject {
+        #[serde(default)]
+        id: u32,
+        #[serde(default)]
+        name: String,
+        #[serde(default, rename = "type")]
+        kind: String,
+        #[serde(default)]
+        class: String,
+        #[serde(default)]
+        x: f32,
+        #[serde(default)]
+        y: f32,
+        #[serde(default)]
+        width: f32,
+        #[serde(default)]
+        height: f32,
+        #[serde(default)]
+        rotation: f32,
+        #[serde(default = "yes")]
+        visible: bool,
+        gid: Option<u32>,
+        #[serde(default)]
+        ellipse: bool,
+        #[serde(default)]
+        point: bool,
+        polygon: Option<Vec<JsonPoint>>,
+        polyline: Option<Vec<JsonPoint>>,
+        text: Option<JsonText>,
+        #[serde(default)]
+        properties: Vec<JsonProperty>,
+    }
+
+    impl JsonObject {
+        fn into_object(self) -> Result<MapObject> {
+            let points = |points: Vec<JsonPoint>| points.into_iter().map(|p| (p.x, p.y)).collect();
+            let shape = if self.ellipse {
+                ObjectShape::Ellipse
+            } else if self.point {
+                ObjectShape::Point
+            } else if let Some(polygon) = self.polygon {
+                ObjectShape::Polygon(points(polygon))
+            } else if let Some(polyline) = self.polyline {
+                ObjectShape::Polyline(points(polyline))
+            } else if let Some(text) = self.text {
+                ObjectShape::Text(text.text)
+            } else {
+                ObjectShape::Rectangle
+            };
+            Ok(MapObject {
+                id: self.id,
+                name: self.name,
+                class: if self.class.is_empty() {
+                    self.kind
+                } else {
+                    self.class
+                },
+                x: self.x,
+                y: self.y,
+                width: self.width,
+                height: self.height,
+                rotation: self.rotation,
+                visible: self.visible,
+                tile: self.gid.and_then(Tile::from_raw),
+                shape,
+                properties: properties(self.properties)?,
+            })
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct JsonPoint {
+        x: f32,
+        y: f32,
+    }
+
+    #[derive(Deserialize)]
+    struct JsonText {
+        #[serde(default)]
+        text: String,
+    }
+
+    #[derive(Deserialize)]
+    struct JsonProperty {
+        name: String,
+        #[serde(default = "string", rename = "type")]
+        kind: String,
+        #[serde(default)]
+        value: Value,
+    }
+
+    fn string() -> String {
+        String::from("string")
+    }
+
+    fn yes() -> bool {
+        true
+    }
+
+    fn opaque() -> f32 {
+        1.0
+    }
+}
@@ -0,0 +1,467 @@
+use crate::assets::Handle;
+use crate::camera::Camera2D;
+use crate::canvas::Canvas;
+use crate::texture::Texture;
+use crate::types::{Color, CopyTextureOptions, Point, Rect};
+use crate::Result;
+use alloc::string::String;
+use alloc::vec::Vec;
+use hashbrown::HashMap;
+
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+/// Only used by hexagonal maps, but cleared from every gid.
+const ROTATED_HEXAGONAL: u32 = 0x1000_0000;
+
+/// An orthogonal map made in the Tiled editor, loaded with `Context::load_tilemap`.
+///
+/// Tile layers are drawn with `draw`; object layers are left to the game to read.
+pub struct Tilemap {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) tile_width: u32,
+    pub(crate) tile_height: u32,
+    pub(crate) tilesets: Vec<Tileset>,
+    /// One for each tileset, in the same order.
+    pub(crate) textures: Vec<Handle<Texture>>,
+    pub(crate) layers: Vec<Layer>,
+    pub(crate) properties: Properties,
+}
+
+/// An image cut into a grid of tiles, numbered from `first_gid` in the map.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tileset {
+    pub name: String,
+    pub first_gid: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// Pixels between two tiles of the image.
+    pub spacing: u32,
+    /// Pixels around the tiles of the image.
+    pub margin: u32,
+    pub columns: u32,
+    pub tile_count: u32,
+    /// The path the image was loaded from.
+    pub image: String,
+    pub properties: Properties,
+    /// Properties of single tiles, by their id within the tileset.
+    pub tile_properties: HashMap<u32, Properties>,
+}
+
+/// A tile placed on the map: its global id and how it is flipped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Tile {
+    pub gid: u32,
+    pub flip_h: bool,
+    pub flip_v: bool,
+    /// Swaps the x and y axes, done before the other two flips.
+    pub flip_d: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Layer {
+    Tiles(TileLayer),
+    Objects(ObjectLayer),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileLayer {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub visible: bool,
+    /// From 0 to 1, applied to the tiles when drawing.
+    pub opacity: f32,
+    /// Moves the whole layer, in pixels.
+    pub offset: Point,
+    pub properties: Properties,
+    /// Row by row, `None` where there is no tile.
+    pub tiles: Vec<Option<Tile>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectLayer {
+    pub name: String,
+    pub visible: bool,
+    pub offset: Point,
+    pub properties: Properties,
+    pub objects: Vec<MapObject>,
+}
+
+/// A shape or point placed on an object layer, in map pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapObject {
+    pub id: u32,
+    pub name: String,
+    /// The "class" of the object, which older versions of Tiled called its "type".
+    pub class: String,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// Degrees clockwise around `(x, y)`.
+    pub rotation: f32,
+    pub visible: bool,
+    /// For tile objects, which Tiled places by their bottom-left corner.
+    pub tile: Option<Tile>,
+    pub shape: ObjectShape,
+    pub properties: Properties,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ObjectShape {
+    #[default]
+    Rectangle,
+    Ellipse,
+    Point,
+    /// Points relative to the object position.
+    Polygon(Vec<(f32, f32)>),
+    Polyline(Vec<(f32, f32)>),
+    Text(String),
+}
+
+/// The custom properties set on a map, tileset, tile, layer or object.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Properties(pub(crate) HashMap<String, PropertyValue>);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyValue {
+    String(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Color(Color),
+    /// A path, as written in the map.
+    File(String),
+    /// The id of another object, or 0 for none.
+    Object(u32),
+    Class(Properties),
+}
+
+impl Tilemap {
+    /// Width in tiles.
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in tiles.
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[inline]
+    pub fn tile_width(&self) -> u32 {
+        self.tile_width
+    }
+
+    #[inline]
+    pub fn tile_height(&self) -> u32 {
+        self.tile_height
+    }
+
+    #[inline]
+    pub fn pixel_width(&self) -> u32 {
+        self.width * self.tile_width
+    }
+
+    #[inline]
+    pub fn pixel_height(&self) -> u32 {
+        self.height * self.tile_height
+    }
+
+    #[inline]
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    #[inline]
+    pub fn tilesets(&self) -> &[Tileset] {
+        &self.tilesets
+    }
+
+    /// Every layer in drawing order, with the layers of groups in place of the groups.
+    #[inline]
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&Layer> {
+        self.layers.iter().find(|layer| layer.name() == name)
+    }
+
+    pub fn tile_layer(&self, name: &str) -> Option<&TileLayer> {
+        self.layers.iter().find_map(|layer| match layer {
+            Layer::Tiles(tiles) if tiles.name == name => Some(tiles),
+            _ => None,
+        })
+    }
+
+    pub fn object_layer(&self, name: &str) -> Option<&ObjectLayer> {
+        self.layers.iter().find_map(|layer| match layer {
+            Layer::Objects(objects) if objects.name == name => Some(objects),
+            _ => None,
+        })
+    }
+
+    /// The objects of every object layer.
+    pub fn objects(&self) -> impl Iterator<Item = &MapObject> {
+        self.layers.iter().flat_map(|layer| match layer {
+            Layer::Objects(objects) => objects.objects.as_slice(),
+            Layer::Tiles(_) => &[],
+        })
+    }
+
+    /// The tileset a tile comes from, along with its index.
+    pub fn tileset(&self, gid: u32) -> Option<(usize, &Tileset)> {
+        let index = self
+            .tilesets
+            .partition_point(|tileset| tileset.first_gid <= gid)
+            .checked_sub(1)?;
+        let tileset = &self.tilesets[index];
+        (gid - tileset.first_gid < tileset.tile_count).then_some((index, tileset))
+    }
+
+    /// Properties set on a single tile in its tileset.
+    pub fn tile_properties(&self, gid: u32) -> Option<&Properties> {
+        let (_, tileset) = self.tileset(gid)?;
+        tileset.tile_properties.get(&(gid - tileset.first_gid))
+    }
+
+    /// Draws every visible tile layer. `viewport` is the part of the map, in pixels, that is
+    /// drawn at the top-left corner of the canvas; tiles outside of it are skipped.
+    pub fn draw(&self, canvas: &Canvas, viewport: Rect) -> Result {
+        for layer in self.visible_tile_layers() {
+            self.draw_layer(canvas, layer, viewport)?;
+        }
+        Ok(())
+    }
+
+    /// Draws every visible tile layer through `camera`, at their place in the world, skipping
+    /// the tiles the camera does not see.
+    pub fn draw_with_camera(&self, canvas: &Canvas, camera: &Camera2D) -> Result {
+        let visible = camera.visible_rect();
+        canvas.with_camera(camera, |canvas| {
+            for layer in self.visible_tile_layers() {
+                self.draw_tiles(canvas, layer, visible, Point::new(0, 0))?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Draws the tiles of one layer inside `viewport`, even if the layer is hidden.
+    ///
+    /// Tiles flipped diagonally are rotated around their center, so tiles that are not square
+    /// keep the size of their cell.
+    pub fn draw_layer(&self, canvas: &Canvas, layer: &TileLayer, viewport: Rect) -> Result {
+        self.draw_tiles(canvas, layer, viewport, Point::new(viewport.x, viewport.y))
+    }
+
+    fn visible_tile_layers(&self) -> impl Iterator<Item = &TileLayer> {
+        self.layers.iter().filter_map(|layer| match layer {
+            Layer::Tiles(layer) if layer.visible => Some(layer),
+            _ => None,
+        })
+    }
+
+    /// Draws the tiles overlapping `visible`, with the map point `origin` at the canvas origin.
+    fn draw_tiles(
+        &self,
+        canvas: &Canvas,
+        layer: &TileLayer,
+        visible: Rect,
+        origin: Point,
+    ) -> Result {
+        let (tile_w, tile_h) = (self.tile_width as i32, self.tile_height as i32);
+        if tile_w == 0 || tile_h == 0 {
+            return Ok(());
+        }
+        // tiles larger than the grid stick out to the right and up from their cell
+        let (overhang_w, overhang_h) = self.tilesets.iter().fold((0, 0), |(w, h), tileset| {
+            (
+                w.max(tileset.tile_width as i32 - tile_w),
+                h.max(tileset.tile_height as i32 - tile_h),
+            )
+        });
+
+        let left = visible.x - layer.offset.x;
+        let top = visible.y - layer.offset.y;
+        let columns = span(
+            left - overhang_w,
+            left + visible.w as i32,
+            tile_w,
+            layer.width,
+        );
+        let rows = span(
+            top,
+            top + visible.h as i32 + overhang_h,
+            tile_h,
+            layer.height,
+        );
+        let (shift_x, shift_y) = (layer.offset.x - origin.x, layer.offset.y - origin.y);
+        let color_mod = (layer.opacity < 1.0).then(|| {
+            let alpha = (layer.opacity.max(0.0) * 255.0).round() as u8;
+            Color::new(255, 255, 255, alpha)
+        });
+
+        for row in rows {
+            for column in columns.clone() {
+                let Some(tile) = layer.tiles[(row * layer.width + column) as usize] else {
+                    continue;
+                };
+                let Some((index, tileset)) = self.tileset(tile.gid) else {
+                    continue;
+                };
+                let (w, h) = (tileset.tile_width, tileset.tile_height);
+                let dest = Rect::new(
+                    column as i32 * tile_w + shift_x,
+                    (row as i32 + 1) * tile_h - h as i32 + shift_y,
+                    w,
+                    h,
+                );
+                let mut options = CopyTextureOptions {
+                    src: Some(tileset.tile_rect(tile.gid - tileset.first_gid)),
+                    dest: Some(dest),
+                    flip_h: tile.flip_h,
+                    flip_v: tile.flip_v,
+                    color_mod,
+                    ..Default::default()
+                };
+                if tile.flip_d {
+                    // swapping the axes is a quarter turn of the vertically flipped tile, and the
+                    // other two flips trade places once turned
+                    options.angle = 90.0;
+                    options.flip_h = tile.flip_v;
+                    options.flip_v = !tile.flip_h;
+                }
+                canvas.copy_texture(&*self.textures[index], options)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The cells of a row or column of `count` cells of `size` overlapping `start..end`.
+fn span(start: i32, end: i32, size: i32, count: u32) -> core::ops::Range<u32> {
+    let first = start.div_euclid(size).clamp(0, count as i32);
+    let last = (end + size - 1).div_euclid(size).clamp(first, count as i32);
+    first as u32..last as u32
+}
+
+impl Tileset {
+    /// Where a tile, by its id within the tileset, is in the image.
+    pub fn tile_rect(&self, id: u32) -> Rect {
+        let columns = self.columns.max(1);
+        let (column, row) = (id % columns, id / columns);
+        Rect::new(
+            (self.margin + column * (self.tile_width + self.spacing)) as i32,
+            (self.margin + row * (self.tile_height + self.spacing)) as i32,
+            self.tile_width,
+            self.tile_height,
+        )
+    }
+}
+
+impl Tile {
+    /// Splits a gid as stored by Tiled into the tile and its flip flags. 0 is no tile.
+    pub fn from_raw(raw: u32) -> Option<Self> {
+        let gid = raw
+            & !(FLIPPED_HORIZONTALLY | FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY | ROTATED_HEXAGONAL);
+        (gid != 0).then_some(Self {
+            gid,
+            flip_h: raw & FLIPPED_HORIZONTALLY != 0,
+            flip_v: raw & FLIPPED_VERTICALLY != 0,
+            flip_d: raw & FLIPPED_DIAGONALLY != 0,
+        })
+    }
+}
+
+impl Layer {
+    pub fn name(&self) -> &str {
+        match self {
+            Layer::Tiles(layer) => &layer.name,
+            Layer::Objects(layer) => &layer.name,
+        }
+    }
+}
+
+impl TileLayer {
+    pub fn tile(&self, x: u32, y: u32) -> Option<Tile> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.tiles[(y * self.width + x) as usize]
+    }
+}
+
+impl ObjectLayer {
+    pub fn object(&self, name: &str) -> Option<&MapObject> {
+        self.objects.iter().find(|object| object.name == name)
+    }
+
+    pub fn objects_of_class<'a>(&'a self, class: &'a str) -> impl Iterator<Item = &'a MapObject> {
+        self.objects
+            .iter()
+            .filter(move |object| object.class == class)
+    }
+}
+
+impl Properties {
+    pub fn get(&self, name: &str) -> Option<&PropertyValue> {
+        self.0.get(name)
+    }
+
+    /// The value of a string or file property.
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            PropertyValue::String(value) | PropertyValue::File(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_int(&self, name: &str) -> Option<i64> {
+        match self.get(name)? {
+            PropertyValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value of a float property, or of an int one.
+    pub fn get_float(&self, name: &str) -> Option<f64> {
+        match self.get(name)? {
+            PropertyValue::Float(value) => Some(*value),
+            PropertyValue::Int(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.get(name)? {
+            PropertyValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_color(&self, name: &str) -> Option<Color> {
+        match self.get(name)? {
+            PropertyValue::Color(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &PropertyValue)> {
+        self.0.iter().map(|(name, value)| (name.as_str(), value))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
@@ -0,0 +1,267 @@
+use crate::backend::{Backend, DecodedFont, Decoder};
+use crate::types::*;
+use crate::Result;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use hashbrown::HashMap;
+
+/// Wraps the backend of a `Context` and keeps track of every texture and font it has alive, so
+/// leaks can be reported when the `Context` shuts down.
+pub(crate) struct Tracked<B> {
+    backend: B,
+    live: Rc<RefCell<LiveResources>>,
+}
+
+#[derive(Default)]
+pub(crate) struct LiveResources {
+    textures: HashMap<TextureId, TextureOrigin>,
+    fonts: HashMap<FontId, (Option<String>, u8)>,
+}
+
+enum TextureOrigin {
+    File(String),
+    Memory,
+    Target(u32, u32),
+    Pixels(u32, u32),
+}
+
+impl<B: Backend> Tracked<B> {
+    pub(crate) fn new(backend: B, live: Rc<RefCell<LiveResources>>) -> Self {
+        Self { backend, live }
+    }
+}
+
+impl LiveResources {
+    /// One line per live resource, ordered by id.
+    pub(crate) fn report(&self) -> Vec<String> {
+        let mut textures = self.textures.iter().collect::<Vec<_>>();
+        textures.sort_by_key(|(id, _)| id.0.index);
+        let mut fonts = self.fonts.iter().collect::<Vec<_>>();
+        fonts.sort_by_key(|(id, _)| id.0.index);
+
+        let textures = textures.into_iter().map(|(id, origin)| match origin {
+            TextureOrigin::File(path) => {
+                format!("Leaked texture {:?} loaded from '{}'.", id.0, path)
+            }
+            TextureOrigin::Memory => format!("Leaked texture {:?} loaded from memory.", id.0),
+            TextureOrigin::Target(w, h) => {
+                format!("Leaked {}x{} render target {:?}.", w, h, id.0)
+            }
+            TextureOrigin::Pixels(w, h) => {
+                format!("Leaked {}x{} texture {:?} created from pixels.", w, h, id.0)
+            }
+        });
+        let fonts = fonts.into_iter().map(|(id, (path, scale))| match path {
+            Some(path) => format!(
+                "Leaked font {:?} loaded from '{}' at scale {}.",
+                id.0, path, scale
+            ),
+            None => format!(
+                "Leaked font {:?} loaded from memory at scale {}.",
+                id.0, scale
+            ),
+        });
+        textures.chain(fonts).collect()
+    }
+}
+
+impl<B: Backend> Backend for Tracked<B> {
+    fn window_set_config(&mut self, config: WindowConfig) -> Result {
+        self.backend.window_set_config(config)
+    }
+
+    fn window_size(&mut self) -> Result<(u32, u32)> {
+        self.backend.window_size()
+    }
+
+    fn texture_create(&mut self, w: u32, h: u32) -> Result<TextureData> {
+        let data = self.backend.texture_create(w, h)?;
+        self.live
+            .borrow_mut()
+            .textures
+            .insert(data.id, TextureOrigin::Target(w, h));
+        Ok(data)
+    }
+
+    fn texture_load(&mut self, path: &str) -> Result<TextureData> {
+        let data = self.backend.texture_load(path)?;
+        self.live
+            .borrow_mut()
+            .textures
+            .insert(data.id, TextureOrigin::File(path.to_string()));
+        Ok(data)
+    }
+
+    fn texture_load_from_memory(&mut self, bytes: &[u8]) -> Result<TextureData> {
+        let data = self.backend.texture_load_from_memory(bytes)?;
+        self.live
+            .borrow_mut()
+            .textures
+            .insert(data.id, TextureOrigin::Memory);
+        Ok(data)
+    }
+
+    fn texture_create_from_pixels(
+        &mut self,
+        w: u32,
+        h: u32,
+        format: PixelFormat,
+        pixels: &[u8],
+    ) -> Result<TextureData> {
+        let data = self
+            .backend
+            .texture_create_from_pixels(w, h, format, pixels)?;
+        self.live
+            .borrow_mut()
+            .textures
+            .insert(data.id, TextureOrigin::Pixels(w, h));
+        Ok(data)
+    }
+
+    fn texture_update(&mut self, id: TextureId, rect: Option<Rect>, pixels: &[u8]) -> Result {
+        self.backend.texture_update(id, rect, pixels)
+    }
+
+    fn texture_destroy(&mut self, id: TextureId) -> Result {
+        self.live.borrow_mut().textures.remove(&id);
+        self.backend.texture_destroy(id)
+    }
+
+    fn texture_reload(&mut self, id: TextureId, path: &str) -> Result<TextureData> {
+        self.backend.texture_reload(id, path)
+    }
+
+    fn font_load(&mut self, path: &str, scale: u8) -> Result<FontData> {
+        let data = self.backend.font_load(path, scale)?;
+        self.live
+            .borrow_mut()
+            .fonts
+            .insert(data.id, (Some(path.to_string()), scale));
+        Ok(data)
+    }
+
+    fn font_load_from_memory(&mut self, bytes: &[u8], scale: u8) -> Result<FontData> {
+        let data = self.backend.font_load_from_memory(bytes, scale)?;
+        self.live.borrow_mut().fonts.insert(data.id, (None, scale));
+        Ok(data)
+    }
+
+    fn font_destroy(&mut self, id: FontId) -> Result {
+        self.live.borrow_mut().fonts.remove(&id);
+        self.backend.font_destroy(id)
+    }
+
+    fn font_reload(&mut self, id: FontId, path: &str, scale: u8) -> Result<FontData> {
+        self.backend.font_reload(id, path, scale)
+    }
+
+    fn font_glyph_metrics(&mut self, font: FontId, glyph: char) -> Result<GlyphMetrics> {
+        self.backend.font_glyph_metrics(font, glyph)
+    }
+
+    fn font_create_decoded(&mut self, font: DecodedFont) -> Result<FontData> {
+        let scale = font.scale;
+        let data = self.backend.font_create_decoded(font)?;
+        self.live.borrow_mut().fonts.insert(data.id, (None, scale));
+        Ok(data)
+    }
+
+    fn decoder(&self) -> Arc<dyn Decoder> {
+        self.backend.decoder()
+    }
+
+    fn render_set_logical_screen(&mut self, screen: Option<LogicalScreen>) -> Result {
+        self.backend.render_set_logical_screen(screen)
+    }
+
+    fn render_set_target(&mut self, target: Option<TextureId>) -> Result {
+        self.backend.render_set_target(target)
+    }
+
+    fn render_set_clip_rect(&mut self, rect: Option<Rect>) -> Result {
+        self.backend.render_set_clip_rect(rect)
+    }
+
+    fn render_set_draw_color(&mut self, color: Color) -> Result {
+        self.backend.render_set_draw_color(color)
+    }
+
+    fn render_clear(&mut self) -> Result {
+        self.backend.render_clear()
+    }
+
+    fn render_present(&mut self) -> Result {
+        self.backend.render_present()
+    }
+
+    fn render_flush(&mut self) -> Result {
+        self.backend.render_flush()
+    }
+
+    fn render_check_copy(&mut self, texture: TextureId) -> Result {
+        self.backend.render_check_copy(texture)
+    }
+
+    fn render_copy_texture(&mut self, texture: TextureId, options: CopyTextureOptions) -> Result {
+        self.backend.render_copy_texture(texture, options)
+    }
+
+    fn render_copy_texture_batch(
+        &mut self,
+        texture: TextureId,
+        copies: &[CopyTextureOptions],
+    ) -> Result {
+        self.backend.render_copy_texture_batch(texture, copies)
+    }
+
+    fn render_fill_rect(&mut self, rect: Option<Rect>, color: Color) -> Result {
+        self.backend.render_fill_rect(rect, color)
+    }
+
+    fn render_draw_rect(&mut self, rect: Option<Rect>, color: Color) -> Result {
+        self.backend.render_draw_rect(rect, color)
+    }
+
+    fn render_draw_points(&mut self, points: &[Point], color: Color) -> Result {
+        self.backend.render_draw_points(points, color)
+    }
+
+    fn render_draw_lines(&mut self, points: &[Point], color: Color) -> Result {
+        self.backend.render_draw_lines(points, color)
+    }
+
+    fn render_geometry(&mut self, vertices: &[Vertex]) -> Result {
+        self.backend.render_geometry(vertices)
+    }
+
+    fn render_font_glyph(&mut self, font: FontId, glyph: char, origin: Point) -> Result {
+        self.backend.render_font_glyph(font, glyph, origin)
+    }
+
+    fn render_output_size(&mut self) -> Result<(u32, u32)> {
+        self.backend.render_output_size()
+    }
+
+    fn render_read_pixels(&mut self, rect: Option<Rect>) -> Result<Vec<u8>> {
+        self.backend.render_read_pixels(rect)
+    }
+
+    fn events_pump(&mut self, events: &mut Vec<Event>) {
+        self.backend.events_pump(events)
+    }
+
+    fn input_mouse_position(&mut self) -> Result<(i32, i32)> {
+        self.backend.input_mouse_position()
+    }
+
+    fn system_get_millis(&mut self) -> Result<u64> {
+        self.backend.system_get_millis()
+    }
+
+    fn system_log(&self, s: &str) {
+        self.backend.system_log(s)
+    }
+}
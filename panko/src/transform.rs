@@ -0,0 +1,128 @@
+use crate::types::{CopyTextureOptions, Point, Rect};
+
+/// Places local coordinates in the coordinates around them: a local point is scaled by `scale`
+/// and turned clockwise by `rotation`, in degrees, around the local origin, which then lands on
+/// `(x, y)`.
+///
+/// The scale is the same on both axes, so rects and copies stay rects through any transform.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform2D {
+    pub x: f32,
+    pub y: f32,
+    pub scale: f32,
+    pub rotation: f32,
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform2D {
+    /// Leaves coordinates unchanged.
+    pub const IDENTITY: Self = Self {
+        x: 0.0,
+        y: 0.0,
+        scale: 1.0,
+        rotation: 0.0,
+    };
+
+    pub const fn translation(x: f32, y: f32) -> Self {
+        Self {
+            x,
+            y,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub const fn scaling(scale: f32) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub const fn rotation(degrees: f32) -> Self {
+        Self {
+            rotation: degrees,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// The transform that applies `inner` first and this one after it, as pushing `inner`
+    /// inside this one on a `Canvas` does.
+    pub fn then(&self, inner: Transform2D) -> Transform2D {
+        let (x, y) = self.point(inner.x, inner.y);
+        Transform2D {
+            x,
+            y,
+            scale: self.scale * inner.scale,
+            rotation: self.rotation + inner.rotation,
+        }
+    }
+
+    /// Where a local pixel corner ends up.
+    pub fn apply(&self, point: Point) -> Point {
+        let (x, y) = self.point(point.x as f32, point.y as f32);
+        Point::new(x.round() as i32, y.round() as i32)
+    }
+
+    pub(crate) fn point(&self, x: f32, y: f32) -> (f32, f32) {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let (x, y) = (x * self.scale, y * self.scale);
+        (self.x + x * cos - y * sin, self.y + x * sin + y * cos)
+    }
+
+    /// Moves `options.dest`, turning the copy along with the transform. Copies without a
+    /// `dest` fill the target as they would without a transform.
+    pub(crate) fn apply_to_copy(&self, options: &mut CopyTextureOptions) {
+        let Some(dest) = options.dest else {
+            return;
+        };
+        let center = options.center;
+        options.dest = Some(self.rect(dest, center));
+        if self.rotation != 0.0 || center.is_some() {
+            let (cx, cy) = pivot(dest, center);
+            options.center = Some(Point::new(
+                (cx * self.scale).round() as i32,
+                (cy * self.scale).round() as i32,
+            ));
+        }
+        options.angle += self.rotation as f64;
+    }
+
+    /// Moves a rect. Without rotation its edges are placed one by one, so rects that touch
+    /// still touch once moved; otherwise it keeps its axes and is placed around its turned
+    /// pivot, `center` or its middle.
+    pub(crate) fn rect(&self, rect: Rect, center: Option<Point>) -> Rect {
+        if self.rotation == 0.0 {
+            let (left, top) = self.point(rect.x as f32, rect.y as f32);
+            let (right, bottom) =
+                self.point(rect.x as f32 + rect.w as f32, rect.y as f32 + rect.h as f32);
+            let (left, top) = (left.round() as i32, top.round() as i32);
+            return Rect::new(
+                left,
+                top,
+                (right.round() as i32 - left).max(0) as u32,
+                (bottom.round() as i32 - top).max(0) as u32,
+            );
+        }
+        let (cx, cy) = pivot(rect, center);
+        let (px, py) = self.point(rect.x as f32 + cx, rect.y as f32 + cy);
+        Rect::new(
+            (px - cx * self.scale).round() as i32,
+            (py - cy * self.scale).round() as i32,
+            (rect.w as f32 * self.scale).round() as u32,
+            (rect.h as f32 * self.scale).round() as u32,
+        )
+    }
+}
+
+/// The point a copy turns around, relative to its rect.
+fn pivot(rect: Rect, center: Option<Point>) -> (f32, f32) {
+    match center {
+        Some(center) => (center.x as f32, center.y as f32),
+        None => (rect.w as f32 / 2.0, rect.h as f32 / 2.0),
+    }
+}
@@ -1,25 +1,78 @@
-pub type ResourceId = u32;
+use alloc::vec::Vec;
 
-#[derive(Copy, Clone, Debug)]
+/// Identifies a backend resource. The generation tells apart resources that have occupied the
+/// same slot over time.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ResourceId {
+    pub index: u32,
+    pub generation: u32,
+}
+
+impl ResourceId {
+    pub const fn new(index: u32, generation: u32) -> Self {
+        Self { index, generation }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct TextureId(pub ResourceId);
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct TextureData {
     pub id: TextureId,
     pub width: u32,
     pub height: u32,
 }
 
-#[derive(Copy, Clone, Debug)]
+/// Layout of raw pixel buffers, named by byte order in memory.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    Rgba8,
+    Bgra8,
+    Rgb8,
+}
+
+impl PixelFormat {
+    #[inline]
+    pub const fn bytes_per_pixel(self) -> usize {
+        match self {
+            Self::Rgba8 | Self::Bgra8 => 4,
+            Self::Rgb8 => 3,
+        }
+    }
+
+    /// Size in bytes of a `w` by `h` buffer.
+    #[inline]
+    pub const fn buffer_size(self, w: u32, h: u32) -> usize {
+        w as usize * h as usize * self.bytes_per_pixel()
+    }
+
+    /// Converts a buffer in this format to RGBA8.
+    pub fn to_rgba8(self, pixels: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Rgba8 => pixels.to_vec(),
+            Self::Bgra8 => pixels
+                .chunks_exact(4)
+                .flat_map(|p| [p[2], p[1], p[0], p[3]])
+                .collect(),
+            Self::Rgb8 => pixels
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct FontId(pub ResourceId);
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct FontData {
     pub id: FontId,
     pub glyphs_height: u32,
 }
 
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum TextAlign {
     #[default]
     Left,
@@ -28,7 +81,7 @@ pub enum TextAlign {
     Justified,
 }
 
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum TextCrossAlign {
     #[default]
     Start,
@@ -36,7 +89,7 @@ pub enum TextCrossAlign {
     End,
 }
 
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct TextPadding {
     pub left: u16,
     pub right: u16,
@@ -44,7 +97,7 @@ pub struct TextPadding {
     pub bottom: u16,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct GlyphMetrics {
     pub min_x: i32,
     pub max_x: i32,
@@ -63,7 +116,7 @@ impl GlyphMetrics {
     }
 }
 
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub struct CopyTextureOptions {
     pub src: Option<Rect>,
     pub dest: Option<Rect>,
@@ -71,10 +124,70 @@ pub struct CopyTextureOptions {
     pub angle: f64,
     pub flip_h: bool,
     pub flip_v: bool,
+    /// Multiplies the colors of the texture, alpha included, for this copy only.
     pub color_mod: Option<Color>,
+    pub blend_mode: BlendMode,
 }
 
-#[derive(Copy, Clone, Debug)]
+/// How the pixels of a copy are combined with the pixels already drawn to the target.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Replaces the target pixels, alpha included.
+    None,
+    /// Draws over the target, letting it show through by the transparency of the texture.
+    #[default]
+    Alpha,
+    /// Adds the colors, weighed by their alpha, to the target. For glows and lights.
+    Additive,
+    /// Multiplies the target by the colors, weighed by their alpha. For shadows and tints.
+    Multiply,
+    /// Multiplies the target by the colors, ignoring their alpha. For applying a light map.
+    Modulate,
+    /// Like `Alpha`, for textures whose colors were already multiplied by their alpha.
+    Premultiplied,
+}
+
+/// Widths of the borders of a nine-slice texture, which are drawn unscaled.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Insets {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+}
+
+impl Insets {
+    pub const fn new(left: u32, right: u32, top: u32, bottom: u32) -> Self {
+        Self {
+            left,
+            right,
+            top,
+            bottom,
+        }
+    }
+
+    pub const fn uniform(inset: u32) -> Self {
+        Self::new(inset, inset, inset, inset)
+    }
+}
+
+/// How the edges or the center of a nine-slice fill the space between the corners.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SliceFill {
+    #[default]
+    Stretch,
+    /// Repeats the slice at its own size, cutting the last repetition short.
+    Tile,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct NineSliceOptions {
+    pub edges: SliceFill,
+    pub center: SliceFill,
+    pub color_mod: Option<Color>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum WindowConfig {
     Borderless(Dimensions),
     Bordered {
@@ -84,19 +197,104 @@ pub enum WindowConfig {
     Fullscreen,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Dimensions {
     pub width: u32,
     pub height: u32,
 }
 
-#[derive(Copy, Clone, Debug)]
+impl Dimensions {
+    pub const fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+}
+
+/// How drawing to the window is sized and fitted into it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ResolutionPolicy {
+    /// Draws at the window's own resolution.
+    #[default]
+    Window,
+    /// Draws at a fixed size, scaled as much as fits in the window without changing its aspect
+    /// ratio. The rest of the window is left black.
+    Letterbox(Dimensions),
+    /// Like `Letterbox`, but only scaled by whole multiples so every pixel stays square and
+    /// sharp. Windows smaller than the size crop it instead of shrinking it.
+    IntegerScale(Dimensions),
+    /// Draws at a fixed size, stretched to fill the whole window.
+    Stretch(Dimensions),
+}
+
+impl ResolutionPolicy {
+    /// Where the drawing goes in a window of `width` by `height`, or `None` for `Window`.
+    pub fn logical_screen(&self, width: u32, height: u32) -> Option<LogicalScreen> {
+        let (size, scale) = match *self {
+            ResolutionPolicy::Window => return None,
+            ResolutionPolicy::Stretch(size) => {
+                return Some(LogicalScreen {
+                    size,
+                    viewport: Rect::new(0, 0, width, height),
+                })
+            }
+            ResolutionPolicy::Letterbox(size) => (size, fit_scale(size, width, height)),
+            ResolutionPolicy::IntegerScale(size) => {
+                (size, fit_scale(size, width, height).floor().max(1.0))
+            }
+        };
+        let w = (size.width as f32 * scale) as u32;
+        let h = (size.height as f32 * scale) as u32;
+        Some(LogicalScreen {
+            size,
+            viewport: Rect::new(
+                (width as i32 - w as i32) / 2,
+                (height as i32 - h as i32) / 2,
+                w,
+                h,
+            ),
+        })
+    }
+}
+
+fn fit_scale(size: Dimensions, width: u32, height: u32) -> f32 {
+    if size.width == 0 || size.height == 0 {
+        return 1.0;
+    }
+    (width as f32 / size.width as f32).min(height as f32 / size.height as f32)
+}
+
+/// A screen drawn at `size` and scaled into `viewport`, a rect of the window in window pixels.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LogicalScreen {
+    pub size: Dimensions,
+    pub viewport: Rect,
+}
+
+impl LogicalScreen {
+    /// Maps a point of the window into the logical screen. Points on the bars around the
+    /// viewport end up outside of it.
+    pub fn window_to_logical(&self, x: i32, y: i32) -> (i32, i32) {
+        let map = |at: i32, start: i32, len: u32, size: u32| {
+            if len == 0 {
+                return 0;
+            }
+            ((at - start) as i64 * size as i64).div_euclid(len as i64) as i32
+        };
+        (
+            map(x, self.viewport.x, self.viewport.w, self.size.width),
+            map(y, self.viewport.y, self.viewport.h, self.size.height),
+        )
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Event {
     KeyDown(Key),
     KeyUp(Key),
     MouseDown,
     MouseUp,
     MouseDoubleClick,
+    /// The window was resized, by the user or the system, to this size.
+    WindowResized(Dimensions),
     Close,
 }
 
@@ -109,7 +307,7 @@ pub enum Key {
     Count
 }
 
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct Point {
     pub x: i32,
     pub y: i32,
@@ -121,7 +319,43 @@ impl Point {
     }
 }
 
-#[derive(Copy, Clone, Debug, Default)]
+/// A corner of a triangle drawn with `Canvas::draw_triangles`. Colors are blended across the
+/// triangle between its corners.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Vertex {
+    pub x: f32,
+    pub y: f32,
+    pub color: Color,
+}
+
+impl Vertex {
+    pub const fn new(x: f32, y: f32, color: Color) -> Self {
+        Self { x, y, color }
+    }
+}
+
+/// Where drawing made inside `Canvas::with_layer` ends up once the frame is drawn: layers go
+/// from the lowest up, and within a layer, draws with a lower `y_sort` go first. Draws with the
+/// same order keep the order they were made in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DrawOrder {
+    pub layer: i32,
+    pub y_sort: i32,
+}
+
+impl DrawOrder {
+    pub const fn new(layer: i32) -> Self {
+        Self { layer, y_sort: 0 }
+    }
+
+    /// Sorts by `y` within the layer, usually the bottom of a sprite, so things lower on the
+    /// screen are drawn over the ones above them.
+    pub const fn y_sorted(layer: i32, y: i32) -> Self {
+        Self { layer, y_sort: y }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct Rect {
     pub x: i32,
     pub y: i32,
@@ -137,9 +371,25 @@ impl Rect {
     pub const fn point(&self) -> Point {
         Point::new(self.x, self.y)
     }
+
+    /// Whether the rect lies entirely inside a `width` by `height` area at the origin.
+    pub const fn is_within(&self, width: u32, height: u32) -> bool {
+        self.x >= 0
+            && self.y >= 0
+            && self.x as u64 + self.w as u64 <= width as u64
+            && self.y as u64 + self.h as u64 <= height as u64
+    }
+
+    /// The part of the rect that is also inside `other`, empty when they don't overlap.
+    pub fn intersection(&self, other: Rect) -> Rect {
+        let (x0, y0) = (self.x.max(other.x), self.y.max(other.y));
+        let x1 = (self.x + self.w as i32).min(other.x + other.w as i32);
+        let y1 = (self.y + self.h as i32).min(other.y + other.h as i32);
+        Rect::new(x0, y0, (x1 - x0).max(0) as u32, (y1 - y0).max(0) as u32)
+    }
 }
 
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
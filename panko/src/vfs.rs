@@ -0,0 +1,153 @@
+use crate::archive::Archive;
+use crate::{Error, Result};
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use std::path::{Path, PathBuf};
+
+/// A source of asset files, addressed by `/`-separated paths relative to its root. Mounts are
+/// shared with the background loading threads.
+pub trait Mount: Send + Sync {
+    /// Reads the whole file, or returns `Ok(None)` if this mount does not have it.
+    fn read(&self, path: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Where the file lives on disk, for mounts that are plain directories. Assets with a disk
+    /// path are loaded by the backend straight from the file, and can be hot-reloaded.
+    fn disk_path(&self, _path: &str) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// Resolves asset paths against a stack of mounts, the most recently mounted first.
+///
+/// While nothing is mounted paths are handed to the backend untouched.
+#[derive(Clone, Default)]
+pub struct Vfs {
+    mounts: Vec<Arc<dyn Mount>>,
+}
+
+impl Vfs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mounts on top of the stack, shadowing files with the same path in earlier mounts.
+    pub fn mount(&mut self, mount: impl Mount + 'static) {
+        self.mounts.push(Arc::new(mount));
+    }
+
+    pub fn mount_directory(&mut self, root: impl Into<PathBuf>) {
+        self.mount(DirectoryMount::new(root));
+    }
+
+    pub fn mount_archive(&mut self, path: impl AsRef<Path>) -> Result {
+        self.mount(Archive::open(path)?);
+        Ok(())
+    }
+
+    /// Removes every mount.
+    pub fn clear(&mut self) {
+        self.mounts.clear();
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.mounts.is_empty()
+    }
+
+    pub fn read(&self, path: &str) -> Result<Vec<u8>> {
+        let path = normalize(path)?;
+        for mount in self.mounts.iter().rev() {
+            if let Some(bytes) = mount.read(&path)? {
+                return Ok(bytes);
+            }
+        }
+        Err(Error::AssetNotFound(path))
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        self.resolve(path).is_ok()
+    }
+
+    /// Reads the file and, for unmounted paths and directory mounts, tells where it lives on disk.
+    pub(crate) fn read_located(&self, path: &str) -> Result<(Vec<u8>, Option<String>)> {
+        if self.is_empty() {
+            let bytes = std::fs::read(path).map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => Error::AssetNotFound(String::from(path)),
+                _ => Error::Io(format!("{}: {}", path, e)),
+            })?;
+            return Ok((bytes, Some(String::from(path))));
+        }
+        match self.resolve(path)? {
+            Resolved::Disk(path) => {
+                let bytes =
+                    std::fs::read(&path).map_err(|e| Error::Io(format!("{}: {}", path, e)))?;
+                Ok((bytes, Some(path)))
+            }
+            Resolved::Bytes(bytes) => Ok((bytes, None)),
+        }
+    }
+
+    pub(crate) fn resolve(&self, path: &str) -> Result<Resolved> {
+        let path = normalize(path)?;
+        for mount in self.mounts.iter().rev() {
+            if let Some(disk_path) = mount.disk_path(&path) {
+                return Ok(Resolved::Disk(disk_path.to_string_lossy().into_owned()));
+            }
+            if let Some(bytes) = mount.read(&path)? {
+                return Ok(Resolved::Bytes(bytes));
+            }
+        }
+        Err(Error::AssetNotFound(path))
+    }
+}
+
+/// Where a mounted asset was found.
+pub(crate) enum Resolved {
+    Disk(String),
+    Bytes(Vec<u8>),
+}
+
+/// Files under a directory on disk.
+pub struct DirectoryMount {
+    root: PathBuf,
+}
+
+impl DirectoryMount {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl Mount for DirectoryMount {
+    fn read(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        let Some(path) = self.disk_path(path) else {
+            return Ok(None);
+        };
+        std::fs::read(&path)
+            .map(Some)
+            .map_err(|e| Error::Io(format!("{}: {}", path.display(), e)))
+    }
+
+    fn disk_path(&self, path: &str) -> Option<PathBuf> {
+        let path = self.root.join(path);
+        path.is_file().then_some(path)
+    }
+}
+
+/// Turns `./a\b//../c.png` into `a/c.png`, refusing paths that climb out of the mount.
+pub(crate) fn normalize(path: &str) -> Result<String> {
+    let mut parts = Vec::new();
+    for part in path.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                if parts.pop().is_none() {
+                    return Err(Error::AssetNotFound(String::from(path)));
+                }
+            }
+            part => parts.push(part),
+        }
+    }
+    Ok(parts.join("/"))
+}
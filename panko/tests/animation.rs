@@ -0,0 +1,191 @@
+mod common;
+
+use common::{draw_frame, frame_commands};
+use panko::animation::{AnimatedSprite, Animation, AnimationDirection, AnimationFrame};
+use panko::recording::{Command, RecordingBackend};
+use panko::types::*;
+use panko::{Context, Error, Result};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// Four 16x16 frames, keyed so that sorting the names would put "walk 10" before "walk 2".
+const HASH_JSON: &str = r##"{
+  "frames": {
+    "walk 0.aseprite": { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "rotated": false, "trimmed": false, "spriteSourceSize": { "x": 0, "y": 0, "w": 16, "h": 16 }, "sourceSize": { "w": 16, "h": 16 }, "duration": 100 },
+    "walk 2.aseprite": { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "rotated": false, "trimmed": false, "spriteSourceSize": { "x": 0, "y": 0, "w": 16, "h": 16 }, "sourceSize": { "w": 16, "h": 16 }, "duration": 100 },
+    "walk 10.aseprite": { "frame": { "x": 32, "y": 0, "w": 12, "h": 14 }, "rotated": false, "trimmed": true, "spriteSourceSize": { "x": 2, "y": 1, "w": 12, "h": 14 }, "sourceSize": { "w": 16, "h": 16 }, "duration": 100 },
+    "walk 11.aseprite": { "frame": { "x": 48, "y": 0, "w": 16, "h": 16 }, "rotated": false, "trimmed": false, "spriteSourceSize": { "x": 0, "y": 0, "w": 16, "h": 16 }, "sourceSize": { "w": 16, "h": 16 }, "duration": 50 }
+  },
+  "meta": {
+    "app": "https://www.aseprite.org/",
+    "image": "walk.png",
+    "size": { "w": 64, "h": 16 },
+    "frameTags": [
+      { "name": "all", "from": 0, "to": 3, "direction": "forward", "color": "#000000ff" },
+      { "name": "back", "from": 0, "to": 2, "direction": "reverse" },
+      { "name": "bounce", "from": 0, "to": 2, "direction": "pingpong" },
+      { "name": "once", "from": 1, "to": 2, "direction": "forward", "repeat": "1" }
+    ]
+  }
+}"##;
+
+const ARRAY_JSON: &str = r#"{
+  "frames": [
+    { "filename": "a", "frame": { "x": 0, "y": 0, "w": 8, "h": 8 }, "duration": 10 },
+    { "filename": "b", "frame": { "x": 8, "y": 0, "w": 8, "h": 8 }, "duration": 10 }
+  ],
+  "meta": { "image": "sheet.png" }
+}"#;
+
+fn directory(test: &str, json: &str) -> PathBuf {
+    // the recording backend never decodes the sheets
+    common::directory(
+        test,
+        &[
+            ("walk.json", json.as_bytes()),
+            ("walk.png", b""),
+            ("sheet.png", b""),
+        ],
+    )
+}
+
+fn load(context: &mut Context, test: &str, json: &str) -> Result<Rc<Animation>> {
+    let dir = directory(test, json);
+    context.vfs().mount_directory(&dir);
+    context.load_animation("walk.json")
+}
+
+/// Frame indices after each of `steps` updates of `delta_ms`.
+fn play(sprite: &mut AnimatedSprite, delta_ms: u64, steps: usize) -> Vec<usize> {
+    (0..steps)
+        .map(|_| {
+            sprite.update(delta_ms);
+            sprite.frame_index()
+        })
+        .collect()
+}
+
+fn with_animation(test: &str, json: &'static str, check: impl FnOnce(Rc<Animation>) + 'static) {
+    let test = test.to_string();
+    draw_frame(
+        RecordingBackend::new(),
+        move |context| {
+            check(load(context, &test, json)?);
+            Ok(())
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+}
+
+#[test]
+fn aseprite_hash_export_is_read_in_file_order() {
+    with_animation("hash", HASH_JSON, |animation| {
+        let frames = animation.frames();
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[1].rect, Rect::new(16, 0, 16, 16));
+        assert_eq!(
+            frames[2],
+            AnimationFrame {
+                rect: Rect::new(32, 0, 12, 14),
+                offset: Point::new(2, 1),
+                duration_ms: 100,
+            }
+        );
+        let once = animation.tag("once").unwrap();
+        assert_eq!((once.from, once.to, once.repeat), (1, 2, Some(1)));
+        assert_eq!(
+            animation.tag("bounce").unwrap().direction,
+            AnimationDirection::PingPong
+        );
+        assert_eq!(animation.tag("all").unwrap().repeat, None);
+    });
+}
+
+#[test]
+fn aseprite_array_export_is_read() {
+    with_animation("array", ARRAY_JSON, |animation| {
+        assert_eq!(animation.frames().len(), 2);
+        assert_eq!(animation.frames()[1].rect, Rect::new(8, 0, 8, 8));
+        assert!(animation.tags().is_empty());
+    });
+}
+
+#[test]
+fn frames_advance_by_their_durations() {
+    with_animation("durations", HASH_JSON, |animation| {
+        let mut sprite = AnimatedSprite::new(animation);
+        // 100ms, 100ms, 100ms, 50ms, then around again
+        assert_eq!(play(&mut sprite, 50, 8), [0, 1, 1, 2, 2, 3, 0, 0]);
+        // a long delta skips frames
+        sprite.update(250);
+        assert_eq!(sprite.frame_index(), 3);
+    });
+}
+
+#[test]
+fn tags_play_in_their_direction() {
+    with_animation("directions", HASH_JSON, |animation| {
+        let mut sprite = AnimatedSprite::new(animation);
+
+        sprite.play("back").unwrap();
+        assert_eq!(sprite.frame_index(), 2);
+        assert_eq!(play(&mut sprite, 100, 4), [1, 0, 2, 1]);
+
+        sprite.play("bounce").unwrap();
+        assert_eq!(play(&mut sprite, 100, 6), [1, 2, 1, 0, 1, 2]);
+
+        sprite.play("once").unwrap();
+        assert_eq!(play(&mut sprite, 100, 3), [2, 2, 2]);
+        assert!(sprite.is_finished());
+
+        assert_eq!(
+            sprite.play("missing"),
+            Err(Error::Other(String::from(
+                "Animation has no tag 'missing'."
+            )))
+        );
+    });
+}
+
+#[test]
+fn current_frame_is_drawn_from_the_sheet() {
+    let dir = directory("draw", HASH_JSON);
+    let log = draw_frame(
+        RecordingBackend::new(),
+        move |context| {
+            context.vfs().mount_directory(&dir);
+            let mut sprite = AnimatedSprite::new(context.load_animation("walk.json")?);
+            sprite.update(250);
+            Ok(sprite)
+        },
+        |sprite, canvas| sprite.draw(canvas, Point::new(10, 20)),
+    )
+    .unwrap();
+
+    assert!(log.commands().iter().any(|c| matches!(
+        c,
+        Command::TextureLoad { path, .. } if path.ends_with("walk.png")
+    )));
+    let commands = frame_commands(&log);
+    assert!(matches!(
+        commands.as_slice(),
+        [Command::RenderCopyTexture { options, .. }]
+            if options.src == Some(Rect::new(32, 0, 12, 14))
+                && options.dest == Some(Rect::new(12, 21, 12, 14))
+    ));
+}
+
+#[test]
+fn invalid_json_is_a_decode_error() {
+    draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            let result = load(context, "invalid", r#"{ "frames": 3 }"#);
+            assert!(matches!(result, Err(Error::Decode(_))));
+            Ok(())
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+}
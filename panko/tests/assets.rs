@@ -0,0 +1,108 @@
+mod common;
+
+use common::{count, draw_frame};
+use panko::assets::Handle;
+use panko::recording::{Command, RecordingBackend};
+
+#[test]
+fn textures_are_loaded_once_per_path() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            let a = context.assets().texture("tiles.png")?;
+            let b = context.assets().texture("tiles.png")?;
+            let c = context.assets().texture("hero.png")?;
+            assert!(Handle::ptr_eq(&a, &b));
+            assert!(!Handle::ptr_eq(&a, &c));
+            assert_eq!(context.assets().texture_count(), 2);
+            Ok((a, b, c))
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+
+    let commands = log.commands();
+    assert_eq!(
+        count(&commands, |c| matches!(c, Command::TextureLoad { .. })),
+        2
+    );
+}
+
+#[test]
+fn context_shares_textures_between_spellings_of_a_path() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            let a = context.load_texture("maps/tiles.png")?;
+            let b = context.load_texture("./maps/tiles.png")?;
+            let c = context.load_texture("maps//tiles.png")?;
+            assert!(Handle::ptr_eq(&a, &b));
+            assert!(Handle::ptr_eq(&a, &c));
+            Ok((a, b, c))
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+
+    let commands = log.commands();
+    assert_eq!(
+        count(&commands, |c| matches!(c, Command::TextureLoad { .. })),
+        1
+    );
+}
+
+#[test]
+fn texture_is_released_with_its_last_handle() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            let a = context.assets().texture("tiles.png")?;
+            let b = a.clone();
+            drop(a);
+            assert_eq!(context.assets().texture_count(), 1);
+            drop(b);
+            assert_eq!(context.assets().texture_count(), 0);
+            // loading again after every handle is gone hits the backend again
+            context.assets().texture("tiles.png")
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+
+    let commands = log.commands();
+    assert_eq!(
+        count(&commands, |c| matches!(c, Command::TextureLoad { .. })),
+        2
+    );
+    assert_eq!(
+        count(&commands, |c| matches!(c, Command::TextureDestroy(_))),
+        2
+    );
+}
+
+#[test]
+fn fonts_are_keyed_by_path_and_scale() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            let a = context.assets().font("ui.ttf", 12)?;
+            let b = context.assets().font("ui.ttf", 12)?;
+            let c = context.assets().font("ui.ttf", 16)?;
+            assert!(Handle::ptr_eq(&a, &b));
+            assert!(!Handle::ptr_eq(&a, &c));
+            Ok((a, b, c))
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+
+    let commands = log.commands();
+    assert_eq!(
+        count(&commands, |c| matches!(c, Command::FontLoad { .. })),
+        2
+    );
+    assert_eq!(
+        count(&commands, |c| matches!(c, Command::FontDestroy(_))),
+        2
+    );
+}
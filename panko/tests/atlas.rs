@@ -0,0 +1,118 @@
+mod common;
+
+use common::{draw_frame, frame_commands};
+use panko::atlas::AtlasRegion;
+use panko::recording::{Command, RecordingBackend};
+use panko::types::*;
+use panko::Error;
+
+fn overlaps(a: Rect, b: Rect) -> bool {
+    a.x < b.x + b.w as i32
+        && b.x < a.x + a.w as i32
+        && a.y < b.y + b.h as i32
+        && b.y < a.y + a.h as i32
+}
+
+#[test]
+fn regions_are_packed_without_overlapping() {
+    draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            let mut atlas = context.create_atlas(64, 64);
+            let mut regions: Vec<AtlasRegion> = Vec::new();
+            for i in 0..60u32 {
+                let (w, h) = (4 + i * 7 % 13, 3 + i * 5 % 11);
+                let pixels = vec![0; (w * h * 4) as usize];
+                let region = atlas.add_pixels(w, h, PixelFormat::Rgba8, &pixels)?;
+                assert_eq!((region.width(), region.height()), (w, h));
+                assert!(region.rect().is_within(64, 64));
+                for other in regions.iter() {
+                    if std::ptr::eq(other.page(), region.page()) {
+                        // one pixel of padding on each side
+                        let padded = Rect::new(
+                            other.rect().x,
+                            other.rect().y,
+                            other.width() + 1,
+                            other.height() + 1,
+                        );
+                        assert!(!overlaps(padded, region.rect()));
+                    }
+                }
+                regions.push(region);
+            }
+            // about 4,000 pixels of images, 4,096 per page
+            assert!(atlas.page_count() <= 3, "{} pages", atlas.page_count());
+            Ok(())
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+}
+
+#[test]
+fn images_larger_than_a_page_are_rejected() {
+    draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            let mut atlas = context.create_atlas(16, 16);
+            let result = atlas.add_pixels(17, 1, PixelFormat::Rgba8, &[0; 68]);
+            assert_eq!(result.err(), Some(Error::AtlasFull));
+            // an exact fit needs no padding
+            atlas.add_pixels(16, 16, PixelFormat::Rgba8, &[0; 1024])?;
+            assert_eq!(atlas.page_count(), 1);
+            Ok(())
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+}
+
+#[test]
+fn regions_are_drawn_from_their_page() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            let mut atlas = context.create_atlas(32, 32);
+            atlas.add_pixels(8, 8, PixelFormat::Rgba8, &[0; 256])?;
+            let second = context.load_into_atlas(&mut atlas, "sprite.png")?;
+            assert_eq!((second.width(), second.height()), (32, 32));
+            assert_eq!(atlas.page_count(), 2);
+            Ok(second)
+        },
+        |region, canvas| {
+            canvas.copy_texture(region, CopyTextureOptions::default())?;
+            canvas.copy_texture(
+                region,
+                CopyTextureOptions {
+                    src: Some(Rect::new(2, 3, 4, 4)),
+                    ..Default::default()
+                },
+            )
+        },
+    )
+    .unwrap();
+
+    let page = match &frame_commands(&log)[0] {
+        Command::RenderCopyTexture { texture, .. } => *texture,
+        command => panic!("unexpected {:?}", command),
+    };
+    assert_eq!(
+        frame_commands(&log),
+        vec![
+            Command::RenderCopyTexture {
+                texture: page,
+                options: CopyTextureOptions {
+                    src: Some(Rect::new(0, 0, 32, 32)),
+                    ..Default::default()
+                },
+            },
+            Command::RenderCopyTexture {
+                texture: page,
+                options: CopyTextureOptions {
+                    src: Some(Rect::new(2, 3, 4, 4)),
+                    ..Default::default()
+                },
+            },
+        ]
+    );
+}
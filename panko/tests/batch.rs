@@ -0,0 +1,147 @@
+mod common;
+
+use common::{draw_frame, submitted_commands};
+use panko::assets::Handle;
+use panko::canvas::Canvas;
+use panko::recording::{Command, RecordingBackend};
+use panko::texture::Texture;
+use panko::types::*;
+use panko::Result;
+
+fn copy_to(x: i32) -> CopyTextureOptions {
+    CopyTextureOptions {
+        dest: Some(Rect::new(x, 0, 8, 8)),
+        ..Default::default()
+    }
+}
+
+fn drawn(
+    draw: impl FnMut(&mut (Handle<Texture>, Handle<Texture>), &mut Canvas) -> Result,
+) -> Vec<Command> {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            Ok((
+                context.load_texture("a.png")?,
+                context.load_texture("b.png")?,
+            ))
+        },
+        draw,
+    )
+    .unwrap();
+    submitted_commands(&log)
+}
+
+#[test]
+fn copies_of_one_texture_are_submitted_together() {
+    let commands = drawn(|(a, _), canvas| {
+        for x in 0..3 {
+            canvas.copy_texture(a, copy_to(x * 8))?;
+        }
+        Ok(())
+    });
+
+    assert_eq!(commands.len(), 1);
+    match &commands[0] {
+        Command::RenderCopyTextureBatch { copies, .. } => {
+            assert_eq!(copies, &vec![copy_to(0), copy_to(8), copy_to(16)]);
+        }
+        command => panic!("unexpected {:?}", command),
+    }
+}
+
+#[test]
+fn batches_end_when_the_texture_or_blend_mode_changes() {
+    let additive = CopyTextureOptions {
+        blend_mode: BlendMode::Additive,
+        ..copy_to(0)
+    };
+    let commands = drawn(move |(a, b), canvas| {
+        canvas.copy_texture(a, copy_to(0))?;
+        canvas.copy_texture(a, copy_to(8))?;
+        canvas.copy_texture(b, copy_to(0))?;
+        canvas.copy_texture(b, additive)
+    });
+
+    let sizes: Vec<usize> = commands
+        .iter()
+        .map(|command| match command {
+            Command::RenderCopyTextureBatch { copies, .. } => copies.len(),
+            Command::RenderCopyTexture { .. } => 1,
+            command => panic!("unexpected {:?}", command),
+        })
+        .collect();
+    assert_eq!(sizes, vec![2, 1, 1]);
+}
+
+#[test]
+fn other_drawing_keeps_its_place_between_copies() {
+    let commands = drawn(|(a, _), canvas| {
+        canvas.copy_texture(a, copy_to(0))?;
+        canvas.fill_rect(None, Color::RED)?;
+        canvas.copy_texture(a, copy_to(8))
+    });
+
+    assert!(matches!(
+        commands.as_slice(),
+        [
+            Command::RenderCopyTexture { .. },
+            Command::RenderFillRect { .. },
+            Command::RenderCopyTexture { .. },
+        ]
+    ));
+}
+
+#[test]
+fn texture_updates_wait_for_the_copies_before_them() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| context.create_texture_from_pixels(1, 1, PixelFormat::Rgba8, &[0; 4]),
+        |texture, canvas| {
+            canvas.copy_texture(texture, copy_to(0))?;
+            canvas.copy_texture(texture, copy_to(8))?;
+            texture.update(None, &[255; 4])?;
+            canvas.copy_texture(texture, copy_to(16))
+        },
+    )
+    .unwrap();
+
+    let commands = submitted_commands(&log);
+    assert!(matches!(
+        commands[0],
+        Command::RenderCopyTextureBatch { .. }
+    ));
+    assert!(matches!(commands[1], Command::TextureUpdate { .. }));
+    assert!(matches!(commands[2], Command::RenderCopyTexture { .. }));
+}
+
+#[test]
+fn copy_errors_come_back_from_the_copy() {
+    // a texture that outlives its context is unknown to the next one
+    let mut stale = None;
+    draw_frame(
+        RecordingBackend::new(),
+        |context| Ok(Some(context.load_texture("a.png")?)),
+        |texture, _| {
+            stale = texture.take();
+            Ok(())
+        },
+    )
+    .unwrap();
+    let stale = stale.unwrap();
+
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |_| Ok(()),
+        |_, canvas| {
+            assert!(canvas.copy_texture(&stale, copy_to(0)).is_err());
+            canvas.fill_rect(None, Color::RED)
+        },
+    )
+    .unwrap();
+
+    assert!(matches!(
+        submitted_commands(&log).as_slice(),
+        [Command::RenderFillRect { .. }]
+    ));
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::{draw_frame, frame_commands};
+use panko::assets::Handle;
+use panko::camera::Camera2D;
+use panko::canvas::Canvas;
+use panko::recording::{Command, RecordingBackend};
+use panko::types::*;
+use panko::Result;
+
+/// A camera on a 100x100 viewport, looking at the world origin.
+fn camera(zoom: f32, rotation: f32) -> Camera2D {
+    let mut camera = Camera2D::new(Rect::new(0, 0, 100, 100));
+    camera.look_at(Point::new(0, 0));
+    camera.zoom = zoom;
+    camera.rotation = rotation;
+    camera
+}
+
+/// The options of every copy made by `draw`, and the rects of every outline.
+fn drawn(
+    draw: impl FnMut(&mut Handle<panko::texture::Texture>, &mut Canvas) -> Result,
+) -> (Vec<CopyTextureOptions>, Vec<Rect>) {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| context.load_texture("sprite.png"),
+        draw,
+    )
+    .unwrap();
+
+    let mut copies = Vec::new();
+    let mut rects = Vec::new();
+    for command in frame_commands(&log) {
+        match command {
+            Command::RenderCopyTexture { options, .. } => copies.push(options),
+            Command::RenderDrawRect { rect, .. } => rects.push(rect.unwrap()),
+            _ => {}
+        }
+    }
+    (copies, rects)
+}
+
+fn copy_to(dest: Rect) -> CopyTextureOptions {
+    CopyTextureOptions {
+        dest: Some(dest),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn a_new_camera_shows_the_world_unchanged() {
+    let camera = Camera2D::new(Rect::new(20, 10, 100, 50));
+
+    assert_eq!(camera.world_to_screen(Point::new(3, 4)), Point::new(3, 4));
+    assert_eq!(camera.visible_rect(), Rect::new(20, 10, 100, 50));
+}
+
+#[test]
+fn screen_to_world_undoes_world_to_screen() {
+    let camera = camera(4.0, 0.0);
+
+    for point in [Point::new(0, 0), Point::new(-12, 10), Point::new(3, -7)] {
+        let screen = camera.world_to_screen(point);
+        assert_eq!(camera.screen_to_world(screen), point);
+    }
+}
+
+#[test]
+fn screen_pixels_pick_the_world_pixel_under_them() {
+    let camera = camera(2.0, 90.0);
+
+    assert_eq!(camera.world_to_screen(Point::new(0, 0)), Point::new(50, 50));
+    // the middle of the center pixel, turned back a quarter, is just above the world origin
+    assert_eq!(
+        camera.screen_to_world(Point::new(50, 50)),
+        Point::new(0, -1)
+    );
+    assert_eq!(camera.screen_to_world(Point::new(40, 60)), Point::new(5, 4));
+}
+
+#[test]
+fn zoom_scales_around_the_viewport_center() {
+    let camera = camera(2.0, 0.0);
+
+    assert_eq!(camera.visible_rect(), Rect::new(-25, -25, 50, 50));
+    let (copies, rects) = drawn(|texture, canvas| {
+        canvas.with_camera(&camera, |canvas| {
+            canvas.copy_texture(texture, copy_to(Rect::new(10, -5, 8, 8)))?;
+            canvas.draw_rect(Some(Rect::new(0, 0, 4, 2)), Color::RED)
+        })
+    });
+
+    assert_eq!(copies[0].dest, Some(Rect::new(70, 40, 16, 16)));
+    assert_eq!(copies[0].angle, 0.0);
+    assert_eq!(rects, vec![Rect::new(50, 50, 8, 4)]);
+}
+
+#[test]
+fn rotation_turns_copies_with_the_world() {
+    let camera = camera(1.0, 90.0);
+
+    let (copies, _) = drawn(|texture, canvas| {
+        canvas.with_camera(&camera, |canvas| {
+            canvas.copy_texture(texture, copy_to(Rect::new(10, 0, 10, 10)))
+        })
+    });
+
+    // the center (15, 5) turns a quarter clockwise around the origin to (-5, 15)
+    assert_eq!(copies[0].dest, Some(Rect::new(40, 60, 10, 10)));
+    assert_eq!(copies[0].center, Some(Point::new(5, 5)));
+    assert_eq!(copies[0].angle, 90.0);
+}
+
+#[test]
+fn text_is_drawn_through_the_camera() {
+    let camera = camera(2.0, 0.0);
+
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| context.load_font("font.ttf", 12),
+        move |font, canvas| {
+            canvas.with_camera(&camera, |canvas| {
+                canvas.draw_text(font, "ab", Point::new(0, 0), Color::WHITE)
+            })
+        },
+    )
+    .unwrap();
+
+    let dests: Vec<Rect> = frame_commands(&log)
+        .into_iter()
+        .filter_map(|command| match command {
+            Command::RenderCopyTexture { options, .. } => options.dest,
+            _ => None,
+        })
+        .collect();
+    assert_eq!(dests.len(), 2);
+    assert_eq!((dests[0].x, dests[0].y, dests[0].w), (50, 50, 16));
+    assert_eq!((dests[1].x, dests[1].y), (66, 50));
+}
+
+#[test]
+fn drawing_after_the_camera_is_not_moved() {
+    let camera = camera(3.0, 45.0);
+
+    let (copies, _) = drawn(|texture, canvas| {
+        canvas.with_camera(&camera, |canvas| {
+            assert_eq!(canvas.camera(), Some(camera));
+            Ok(())
+        })?;
+        assert_eq!(canvas.camera(), None);
+        canvas.copy_texture(texture, copy_to(Rect::new(1, 2, 3, 4)))
+    });
+
+    assert_eq!(copies, vec![copy_to(Rect::new(1, 2, 3, 4))]);
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use common::{draw_frame, frame_commands};
+use panko::recording::{Command, RecordingBackend};
+use panko::types::*;
+
+const FIRST_TEXTURE: TextureId = TextureId(ResourceId::new(0, 0));
+
+#[test]
+fn copy_texture_forwards_options() {
+    let options = CopyTextureOptions {
+        src: Some(Rect::new(0, 0, 8, 8)),
+        dest: Some(Rect::new(10, 20, 16, 16)),
+        angle: 90.0,
+        flip_h: true,
+        color_mod: Some(Color::RED),
+        ..Default::default()
+    };
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| context.load_texture("sprite.png"),
+        move |texture, canvas| canvas.copy_texture(texture, options),
+    )
+    .unwrap();
+
+    assert_eq!(
+        frame_commands(&log),
+        vec![Command::RenderCopyTexture {
+            texture: FIRST_TEXTURE,
+            options,
+        }]
+    );
+}
+
+#[test]
+fn texture_load_reports_configured_size() {
+    let mut backend = RecordingBackend::new();
+    backend.set_texture_size("tiles.png", 64, 48);
+    draw_frame(
+        backend,
+        |context| context.load_texture("tiles.png"),
+        |texture, _| {
+            assert_eq!((texture.width(), texture.height()), (64, 48));
+            Ok(())
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn clear_and_draw_rect() {
+    let rect = Rect::new(1, 2, 3, 4);
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |_| Ok(()),
+        move |_, canvas| {
+            canvas.clear(Color::WHITE)?;
+            canvas.draw_rect(Some(rect), Color::GREEN)
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        frame_commands(&log),
+        vec![
+            Command::RenderFillRect {
+                rect: None,
+                color: Color::WHITE,
+            },
+            Command::RenderDrawRect {
+                rect: Some(rect),
+                color: Color::GREEN,
+            },
+        ]
+    );
+}
+
+#[test]
+fn with_target_restores_previous_target() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| context.create_target(32, 32),
+        |target, canvas| {
+            canvas.with_target(Some(target), |canvas| canvas.clear(Color::RED))?;
+            canvas.clear(Color::WHITE)
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        frame_commands(&log),
+        vec![
+            Command::RenderSetTarget(Some(FIRST_TEXTURE)),
+            Command::RenderFillRect {
+                rect: None,
+                color: Color::RED,
+            },
+            Command::RenderSetTarget(None),
+            Command::RenderFillRect {
+                rect: None,
+                color: Color::WHITE,
+            },
+        ]
+    );
+}
+
+#[test]
+fn dropped_texture_is_destroyed() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| Ok(Some(context.create_target(8, 8)?)),
+        |target, _| {
+            target.take();
+            Ok(())
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        frame_commands(&log),
+        vec![Command::TextureDestroy(FIRST_TEXTURE)]
+    );
+}
+
+#[test]
+fn context_captures_targets_and_restores_the_window() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            let mut target = context.create_target(4, 2)?;
+            let image = context.capture_texture(&mut target)?;
+            assert_eq!((image.width, image.height), (4, 2));
+            assert_eq!(image.pixels.len(), 32);
+            Ok(target)
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+
+    assert_eq!(
+        log.commands()[1..5],
+        [
+            Command::RenderSetTarget(Some(FIRST_TEXTURE)),
+            Command::RenderOutputSize,
+            Command::RenderReadPixels(None),
+            Command::RenderSetTarget(None),
+        ]
+    );
+}
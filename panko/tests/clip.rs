@@ -0,0 +1,158 @@
+mod common;
+
+use common::{draw_frame, frame_commands};
+use panko::camera::Camera2D;
+use panko::canvas::Canvas;
+use panko::recording::{Command, RecordingBackend};
+use panko::types::*;
+use panko::Result;
+
+const FIRST_TEXTURE: TextureId = TextureId(ResourceId::new(0, 0));
+
+/// The clip rects set by `draw`, in order.
+fn clips(draw: impl FnMut(&mut (), &mut Canvas) -> Result) -> Vec<Option<Rect>> {
+    let log = draw_frame(RecordingBackend::new(), |_| Ok(()), draw).unwrap();
+    frame_commands(&log)
+        .into_iter()
+        .filter_map(|command| match command {
+            Command::RenderSetClipRect(rect) => Some(rect),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn nested_clips_intersect() {
+    let clips = clips(|_, canvas| {
+        canvas.push_clip(Rect::new(10, 10, 100, 50))?;
+        canvas.push_clip(Rect::new(50, 0, 100, 30))?;
+        assert_eq!(canvas.clip(), Some(Rect::new(50, 10, 60, 20)));
+        canvas.push_clip(Rect::new(200, 200, 10, 10))?;
+        assert_eq!(canvas.clip().map(|clip| (clip.w, clip.h)), Some((0, 0)));
+        Ok(())
+    });
+
+    assert_eq!(
+        &clips[..2],
+        [
+            Some(Rect::new(10, 10, 100, 50)),
+            Some(Rect::new(50, 10, 60, 20)),
+        ]
+    );
+}
+
+#[test]
+fn popping_restores_the_previous_clip() {
+    let clips = clips(|_, canvas| {
+        canvas.push_clip(Rect::new(0, 0, 20, 20))?;
+        canvas.with_clip(Rect::new(5, 5, 50, 50), |canvas| {
+            assert_eq!(canvas.clip(), Some(Rect::new(5, 5, 15, 15)));
+            Ok(())
+        })?;
+        assert_eq!(canvas.clip(), Some(Rect::new(0, 0, 20, 20)));
+        canvas.pop_clip()?;
+        assert_eq!(canvas.clip(), None);
+        Ok(())
+    });
+
+    assert_eq!(
+        clips,
+        vec![
+            Some(Rect::new(0, 0, 20, 20)),
+            Some(Rect::new(5, 5, 15, 15)),
+            Some(Rect::new(0, 0, 20, 20)),
+            None,
+        ]
+    );
+}
+
+#[test]
+fn clips_are_moved_by_the_camera() {
+    let mut camera = Camera2D::new(Rect::new(0, 0, 100, 100));
+    camera.look_at(Point::new(0, 0));
+    camera.zoom = 2.0;
+
+    let clips = clips(move |_, canvas| {
+        canvas.with_camera(&camera, |canvas| {
+            canvas.with_clip(Rect::new(-5, 0, 10, 10), |_| Ok(()))
+        })
+    });
+
+    let viewport = Some(camera.viewport);
+    assert_eq!(
+        clips,
+        vec![viewport, Some(Rect::new(40, 50, 20, 20)), viewport, None]
+    );
+}
+
+#[test]
+fn targets_are_drawn_unclipped_and_the_clip_comes_back() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| context.create_target(32, 32),
+        |target, canvas| {
+            canvas.with_clip(Rect::new(1, 2, 3, 4), |canvas| {
+                canvas.with_target(Some(target), |canvas| {
+                    assert_eq!(canvas.clip(), None);
+                    canvas.clear(Color::RED)
+                })
+            })
+        },
+    )
+    .unwrap();
+
+    let clip = Some(Rect::new(1, 2, 3, 4));
+    assert_eq!(
+        frame_commands(&log),
+        vec![
+            Command::RenderSetClipRect(clip),
+            Command::RenderSetClipRect(None),
+            Command::RenderSetTarget(Some(FIRST_TEXTURE)),
+            Command::RenderFillRect {
+                rect: None,
+                color: Color::RED,
+            },
+            Command::RenderSetTarget(None),
+            Command::RenderSetClipRect(clip),
+            Command::RenderSetClipRect(None),
+        ]
+    );
+}
+
+#[test]
+fn a_clip_left_pushed_is_cleared_with_the_frame() {
+    let clips = clips(|_, canvas| canvas.push_clip(Rect::new(0, 0, 8, 8)));
+
+    assert_eq!(clips, vec![Some(Rect::new(0, 0, 8, 8)), None]);
+}
+
+#[test]
+fn capturing_a_target_keeps_the_clip() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| context.create_target(32, 32),
+        |target, canvas| {
+            canvas.with_clip(Rect::new(1, 2, 3, 4), |canvas| {
+                canvas.capture_texture(target)?;
+                assert_eq!(canvas.clip(), Some(Rect::new(1, 2, 3, 4)));
+                Ok(())
+            })
+        },
+    )
+    .unwrap();
+
+    let clip = Some(Rect::new(1, 2, 3, 4));
+    assert_eq!(
+        frame_commands(&log),
+        vec![
+            Command::RenderSetClipRect(clip),
+            Command::RenderSetClipRect(None),
+            Command::RenderSetTarget(Some(FIRST_TEXTURE)),
+            Command::RenderOutputSize,
+            Command::RenderReadPixels(None),
+            Command::RenderSetTarget(None),
+            Command::RenderSetClipRect(clip),
+            Command::RenderSetClipRect(None),
+        ]
+    );
+}
@@ -0,0 +1,93 @@
+#![allow(dead_code)]
+
+use panko::canvas::Canvas;
+use panko::recording::{Command, CommandLog, RecordingBackend};
+use panko::{run_event_loop, Application, Context, Result};
+use std::path::PathBuf;
+
+/// Runs a single frame: `load` builds the state, `draw` is called once with the screen canvas.
+pub fn draw_frame<S>(
+    backend: RecordingBackend,
+    load: impl FnOnce(&mut Context) -> Result<S>,
+    draw: impl FnMut(&mut S, &mut Canvas) -> Result,
+) -> Result<CommandLog> {
+    let log = backend.log();
+    run_event_loop(backend, |context| {
+        Ok(SingleFrame {
+            state: load(context)?,
+            draw,
+        })
+    })?;
+    Ok(log)
+}
+
+struct SingleFrame<S, F> {
+    state: S,
+    draw: F,
+}
+
+impl<S, F> Application for SingleFrame<S, F>
+where
+    F: FnMut(&mut S, &mut Canvas) -> Result,
+{
+    fn update(&mut self, context: &mut Context, _delta_ms: u64) -> Result {
+        context.request_quit();
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, _context: &mut Context, _fixed_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, _alpha_secs: f32) -> Result {
+        (self.draw)(&mut self.state, canvas)
+    }
+}
+
+/// Returns the commands issued by `draw` as the backend received them: everything after the
+/// screen canvas has been set up (`RenderClear` followed by `RenderSetTarget(None)`) and before
+/// the `RenderPresent` that ends the frame.
+pub fn submitted_commands(log: &CommandLog) -> Vec<Command> {
+    log.commands()
+        .into_iter()
+        .skip_while(|c| *c != Command::RenderClear)
+        .skip(2)
+        .take_while(|c| *c != Command::RenderPresent)
+        .collect()
+}
+
+/// Returns the commands issued by `draw`, like `submitted_commands`, with batched copies listed
+/// one by one as `draw` made them.
+pub fn frame_commands(log: &CommandLog) -> Vec<Command> {
+    submitted_commands(log)
+        .into_iter()
+        .flat_map(|c| match c {
+            Command::RenderCopyTextureBatch { texture, copies } => copies
+                .into_iter()
+                .map(|options| Command::RenderCopyTexture { texture, options })
+                .collect(),
+            c => vec![c],
+        })
+        .collect()
+}
+
+/// How many of the commands match.
+pub fn count(commands: &[Command], matches: impl Fn(&Command) -> bool) -> usize {
+    commands.iter().filter(|c| matches(c)).count()
+}
+
+/// A fresh temporary directory holding `files`, at paths relative to it. Named after the test
+/// and its test file, so tests running in parallel do not see each other.
+pub fn directory(test: &str, files: &[(&str, &[u8])]) -> PathBuf {
+    // `common` is compiled into each test file, whose crate is named after it
+    let suite = module_path!().split("::").next().unwrap();
+    let dir = std::env::temp_dir().join(format!("panko-{}-{}", suite, test));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for (path, bytes) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, bytes).unwrap();
+    }
+    dir
+}
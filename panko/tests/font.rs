@@ -0,0 +1,216 @@
+mod common;
+
+use common::{draw_frame, frame_commands};
+use panko::recording::{monospace_metrics, Command, RecordingBackend};
+use panko::types::*;
+use panko::Error;
+
+const ATLAS: TextureId = TextureId(ResourceId::new(0, 0));
+const FONT: FontId = FontId(ResourceId::new(0, 0));
+
+fn glyph_copy(src_x: i32, dest: Point, color: Color) -> Command {
+    Command::RenderCopyTexture {
+        texture: ATLAS,
+        options: CopyTextureOptions {
+            src: Some(Rect::new(src_x, 0, 8, 16)),
+            dest: Some(Rect::new(dest.x, dest.y, 8, 16)),
+            color_mod: Some(color),
+            ..Default::default()
+        },
+    }
+}
+
+#[test]
+fn load_font_creates_first_atlas() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| context.load_font("font.ttf", 12),
+        |_, _| Ok(()),
+    )
+    .unwrap();
+
+    let commands = log.commands();
+    assert!(matches!(commands[0], Command::FontLoad { scale: 12, .. }));
+    assert!(matches!(
+        commands[1],
+        Command::TextureCreate(TextureData {
+            width: 1024,
+            height: 1024,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn draw_text_registers_each_glyph_once() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| context.load_font("font.ttf", 12),
+        |font, canvas| canvas.draw_text(font, "aba", Point::new(5, 7), Color::RED),
+    )
+    .unwrap();
+
+    assert_eq!(
+        frame_commands(&log),
+        vec![
+            Command::RenderSetTarget(Some(ATLAS)),
+            Command::FontGlyphMetrics {
+                font: FONT,
+                glyph: 'a',
+            },
+            Command::RenderFontGlyph {
+                font: FONT,
+                glyph: 'a',
+                origin: Point::new(0, 0),
+            },
+            Command::FontGlyphMetrics {
+                font: FONT,
+                glyph: 'b',
+            },
+            Command::RenderFontGlyph {
+                font: FONT,
+                glyph: 'b',
+                origin: Point::new(8, 0),
+            },
+            Command::RenderSetTarget(None),
+            glyph_copy(0, Point::new(5, 7), Color::RED),
+            glyph_copy(8, Point::new(13, 7), Color::RED),
+            glyph_copy(0, Point::new(21, 7), Color::RED),
+        ]
+    );
+}
+
+#[test]
+fn registered_glyphs_are_not_rendered_again() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| context.load_font("font.ttf", 12),
+        |font, canvas| {
+            canvas.register_text(font, "ab")?;
+            canvas.draw_text(font, "ba", Point::new(0, 0), Color::WHITE)
+        },
+    )
+    .unwrap();
+
+    let glyph_renders = frame_commands(&log)
+        .iter()
+        .filter(|c| matches!(c, Command::RenderFontGlyph { .. }))
+        .count();
+    assert_eq!(glyph_renders, 2);
+}
+
+#[test]
+fn text_width_sums_advances() {
+    let mut backend = RecordingBackend::new();
+    backend.set_glyph_metrics(|c| monospace_metrics(if c == 'w' { 12 } else { 5 }, 16));
+    draw_frame(
+        backend,
+        |context| context.load_font("font.ttf", 12),
+        |font, canvas| {
+            assert_eq!(canvas.text_width(font, "wow")?, 29);
+            Ok(())
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn full_atlas_spills_into_a_new_one() {
+    // 512x512 glyphs fit four to a 1024x1024 atlas.
+    let mut backend = RecordingBackend::new();
+    backend.set_glyphs_height(512);
+    backend.set_glyph_metrics(|_| monospace_metrics(512, 512));
+    let log = draw_frame(
+        backend,
+        |context| context.load_font("font.ttf", 12),
+        |font, canvas| canvas.draw_text(font, "abcde", Point::new(0, 0), Color::WHITE),
+    )
+    .unwrap();
+
+    let commands = frame_commands(&log);
+    let second_atlas = commands
+        .iter()
+        .find_map(|c| match c {
+            Command::TextureCreate(data) => Some(data.id),
+            _ => None,
+        })
+        .expect("second atlas was not created");
+    let last_glyph = commands.iter().rev().find_map(|c| match c {
+        Command::RenderCopyTexture { texture, options } => Some((*texture, options.src)),
+        _ => None,
+    });
+    assert_eq!(
+        last_glyph,
+        Some((second_atlas, Some(Rect::new(0, 0, 512, 512))))
+    );
+}
+
+#[test]
+fn draw_text_bounded_wraps_lines() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| context.load_font("font.ttf", 12),
+        |font, canvas| {
+            canvas.draw_text_bounded(
+                font,
+                "aa bb cc",
+                Color::WHITE,
+                Rect::new(0, 0, 48, 64),
+                TextAlign::Left,
+                TextCrossAlign::Start,
+                TextPadding::default(),
+            )
+        },
+    )
+    .unwrap();
+
+    let dests = frame_commands(&log)
+        .into_iter()
+        .filter_map(|c| match c {
+            Command::RenderCopyTexture { options, .. } => options.dest.map(|d| d.point()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        dests,
+        vec![
+            Point::new(0, 0),
+            Point::new(8, 0),
+            Point::new(16, 0),
+            Point::new(24, 0),
+            Point::new(32, 0),
+            Point::new(0, 16),
+            Point::new(8, 16),
+        ]
+    );
+}
+
+#[test]
+fn glyph_larger_than_atlas_fails() {
+    let mut backend = RecordingBackend::new();
+    backend.set_glyph_metrics(|_| monospace_metrics(2048, 16));
+    let result = draw_frame(
+        backend,
+        |context| context.load_font("font.ttf", 12),
+        |font, canvas| canvas.draw_text(font, "a", Point::new(0, 0), Color::WHITE),
+    );
+    assert_eq!(result.err(), Some(Error::AtlasFull));
+}
+
+#[test]
+fn dropped_font_releases_font_and_atlases() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| Ok(Some(context.load_font("font.ttf", 12)?)),
+        |font, _| {
+            font.take();
+            Ok(())
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        frame_commands(&log),
+        vec![Command::FontDestroy(FONT), Command::TextureDestroy(ATLAS)]
+    );
+}
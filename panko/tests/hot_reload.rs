@@ -0,0 +1,134 @@
+#![cfg(feature = "hot-reload")]
+
+mod common;
+
+use common::{count, directory};
+use panko::assets::Handle;
+use panko::canvas::Canvas;
+use panko::font::Font;
+use panko::recording::{Command, RecordingBackend};
+use panko::types::{Color, Point};
+use panko::{run_event_loop, Application, Context, Result};
+use std::fs::File;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// A file in a fresh temporary directory, so tests running in parallel do not see each other.
+fn asset(test: &str, name: &str) -> String {
+    let dir = directory(test, &[(name, b"v1")]);
+    dir.join(name).to_string_lossy().into_owned()
+}
+
+/// Rewrites the file with a modification time that is guaranteed to differ from the last one.
+fn touch(path: &str) {
+    let path = PathBuf::from(path);
+    let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+    std::fs::write(&path, b"v2").unwrap();
+    File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(modified + Duration::from_secs(1))
+        .unwrap();
+}
+
+#[test]
+fn changed_texture_is_reloaded_in_place() {
+    let path = asset("texture", "tiles.png");
+    let mut backend = RecordingBackend::new();
+    backend.set_texture_size(&path, 16, 8);
+    let log = backend.log();
+    let mut context = Context::new(backend);
+
+    let texture = context.load_texture(&path).unwrap();
+    assert_eq!(context.reload_changed_assets(), 0);
+
+    touch(&path);
+    assert_eq!(context.reload_changed_assets(), 1);
+    // already up to date
+    assert_eq!(context.reload_changed_assets(), 0);
+    assert_eq!((texture.width(), texture.height()), (16, 8));
+
+    let commands = log.commands();
+    assert_eq!(
+        count(&commands, |c| matches!(c, Command::TextureReload { .. })),
+        1
+    );
+    assert_eq!(
+        count(&commands, |c| matches!(c, Command::TextureDestroy(_))),
+        0
+    );
+}
+
+#[test]
+fn dropped_texture_is_no_longer_watched() {
+    let path = asset("dropped", "tiles.png");
+    let mut context = Context::new(RecordingBackend::new());
+
+    drop(context.load_texture(&path).unwrap());
+    touch(&path);
+    assert_eq!(context.reload_changed_assets(), 0);
+}
+
+struct TextTwice {
+    font: Handle<Font>,
+    path: String,
+    frame: u32,
+}
+
+impl Application for TextTwice {
+    fn update(&mut self, context: &mut Context, _delta_ms: u64) -> Result {
+        self.frame += 1;
+        match self.frame {
+            2 => {
+                touch(&self.path);
+                assert_eq!(context.reload_changed_assets(), 1);
+            }
+            3 => context.request_quit(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, _context: &mut Context, _fixed_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, _alpha_secs: f32) -> Result {
+        if self.frame <= 2 {
+            canvas.draw_text(&self.font, "ab", Point::new(0, 0), Color::WHITE)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn changed_font_rebuilds_its_atlases() {
+    let path = asset("font", "ui.ttf");
+    let backend = RecordingBackend::new();
+    let log = backend.log();
+
+    run_event_loop(backend, |context| {
+        Ok(TextTwice {
+            font: context.load_font(&path, 14)?,
+            path: path.clone(),
+            frame: 0,
+        })
+    })
+    .unwrap();
+
+    let commands = log.commands();
+    assert_eq!(
+        count(&commands, |c| matches!(c, Command::FontReload { .. })),
+        1
+    );
+    // the first atlas is replaced and every glyph is rendered again with the new font
+    assert_eq!(
+        count(&commands, |c| matches!(c, Command::TextureCreate(_))),
+        2
+    );
+    assert_eq!(
+        count(&commands, |c| matches!(c, Command::RenderFontGlyph { .. })),
+        4
+    );
+}
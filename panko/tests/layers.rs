@@ -0,0 +1,134 @@
+mod common;
+
+use common::{draw_frame, frame_commands};
+use panko::canvas::Canvas;
+use panko::recording::{Command, RecordingBackend};
+use panko::types::*;
+use panko::{Error, Result};
+
+/// A rect standing for something drawn at `y`.
+fn at(y: i32) -> Option<Rect> {
+    Some(Rect::new(0, y, 1, 1))
+}
+
+/// The commands issued by `draw`.
+fn drawn(draw: impl FnMut(&mut (), &mut Canvas) -> Result) -> Vec<Command> {
+    let log = draw_frame(RecordingBackend::new(), |_| Ok(()), draw).unwrap();
+    frame_commands(&log)
+}
+
+fn fill(y: i32, color: Color) -> Command {
+    Command::RenderFillRect { rect: at(y), color }
+}
+
+#[test]
+fn layers_are_drawn_from_the_lowest_up_after_everything_else() {
+    let commands = drawn(|_, canvas| {
+        canvas.with_layer(DrawOrder::new(2), |canvas| {
+            canvas.fill_rect(at(0), Color::RED)
+        })?;
+        canvas.with_layer(DrawOrder::new(-1), |canvas| {
+            canvas.fill_rect(at(1), Color::GREEN)
+        })?;
+        canvas.fill_rect(at(2), Color::WHITE)
+    });
+
+    assert_eq!(
+        commands,
+        vec![
+            fill(2, Color::WHITE),
+            fill(1, Color::GREEN),
+            fill(0, Color::RED),
+        ]
+    );
+}
+
+#[test]
+fn y_sorting_keeps_the_order_of_equal_keys() {
+    let commands = drawn(|_, canvas| {
+        for (y, color) in [(30, Color::RED), (10, Color::GREEN), (30, Color::WHITE)] {
+            canvas.with_layer(DrawOrder::y_sorted(0, y), |canvas| {
+                canvas.fill_rect(at(y), color)
+            })?;
+        }
+        Ok(())
+    });
+
+    assert_eq!(
+        commands,
+        vec![
+            fill(10, Color::GREEN),
+            fill(30, Color::RED),
+            fill(30, Color::WHITE),
+        ]
+    );
+}
+
+#[test]
+fn held_back_drawing_keeps_its_clip() {
+    let clip = Rect::new(0, 0, 5, 5);
+    let commands = drawn(|_, canvas| {
+        canvas.with_layer(DrawOrder::new(0), |canvas| {
+            canvas.with_clip(clip, |canvas| canvas.fill_rect(at(0), Color::RED))?;
+            canvas.fill_rect(at(1), Color::GREEN)
+        })
+    });
+
+    assert_eq!(
+        commands,
+        vec![
+            Command::RenderSetClipRect(Some(clip)),
+            Command::RenderSetClipRect(None),
+            Command::RenderSetClipRect(Some(clip)),
+            fill(0, Color::RED),
+            Command::RenderSetClipRect(None),
+            fill(1, Color::GREEN),
+        ]
+    );
+}
+
+#[test]
+fn targets_draw_their_layers_before_going_back() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| context.create_target(8, 8),
+        |target, canvas| {
+            canvas.with_target(Some(target), |canvas| {
+                canvas.with_layer(DrawOrder::new(1), |canvas| {
+                    canvas.fill_rect(at(0), Color::RED)
+                })
+            })
+        },
+    )
+    .unwrap();
+
+    let commands = frame_commands(&log);
+    assert_eq!(commands[1], fill(0, Color::RED));
+    assert_eq!(commands[2], Command::RenderSetTarget(None));
+}
+
+#[test]
+fn held_back_copies_are_checked_when_they_are_made() {
+    // a texture that outlives its context is unknown to the next one
+    let mut stale = None;
+    draw_frame(
+        RecordingBackend::new(),
+        |context| Ok(Some(context.load_texture("a.png")?)),
+        |texture, _| {
+            stale = texture.take();
+            Ok(())
+        },
+    )
+    .unwrap();
+    let stale = stale.unwrap();
+
+    let commands = drawn(|_, canvas| {
+        canvas.with_layer(DrawOrder::new(1), |canvas| {
+            let copy = canvas.copy_texture(&stale, CopyTextureOptions::default());
+            assert!(matches!(copy, Err(Error::InvalidResource(_))));
+            canvas.fill_rect(at(0), Color::RED)
+        })
+    });
+
+    assert_eq!(commands, vec![fill(0, Color::RED)]);
+}
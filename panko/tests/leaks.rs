@@ -0,0 +1,84 @@
+mod common;
+
+use common::draw_frame;
+use panko::recording::{Command, RecordingBackend};
+
+fn logged(commands: Vec<Command>) -> Vec<String> {
+    commands
+        .into_iter()
+        .filter_map(|c| match c {
+            Command::Log(line) => Some(line),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn leaked_resources_are_reported_on_shutdown() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            let texture = context.load_texture("tiles.png")?;
+            let target = context.create_target(64, 32)?;
+            let font = context.load_font("ui.ttf", 14)?;
+            Ok(Some((texture, target, font)))
+        },
+        |resources, _| {
+            core::mem::forget(resources.take());
+            Ok(())
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        logged(log.commands()),
+        vec![
+            "Leaked texture ResourceId { index: 0, generation: 0 } loaded from 'tiles.png'.",
+            "Leaked 64x32 render target ResourceId { index: 1, generation: 0 }.",
+            "Leaked 1024x1024 render target ResourceId { index: 2, generation: 0 }.",
+            "Leaked font ResourceId { index: 0, generation: 0 } loaded from 'ui.ttf' at scale 14.",
+        ]
+    );
+}
+
+#[test]
+fn nothing_is_reported_when_everything_is_dropped() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            let texture = context.load_texture("tiles.png")?;
+            let font = context.load_font("ui.ttf", 14)?;
+            Ok((texture, font))
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+
+    assert!(logged(log.commands()).is_empty());
+}
+
+#[test]
+fn leaked_memory_resources_are_reported_without_a_path() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            let texture = context.load_texture_from_memory(&[0; 8])?;
+            let font = context.load_font_from_memory(&[0; 8], 12)?;
+            Ok(Some((texture, font)))
+        },
+        |resources, _| {
+            core::mem::forget(resources.take());
+            Ok(())
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        logged(log.commands()),
+        vec![
+            "Leaked texture ResourceId { index: 0, generation: 0 } loaded from memory.",
+            "Leaked 1024x1024 render target ResourceId { index: 1, generation: 0 }.",
+            "Leaked font ResourceId { index: 0, generation: 0 } loaded from memory at scale 12.",
+        ]
+    );
+}
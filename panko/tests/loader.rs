@@ -0,0 +1,248 @@
+mod common;
+
+use common::{directory, draw_frame};
+use panko::archive::{Archive, ArchiveWriter};
+use panko::assets::Handle;
+use panko::canvas::Canvas;
+use panko::image::Image;
+use panko::loader::{LoadProgress, Pending};
+use panko::recording::{Command, RecordingBackend};
+use panko::texture::Texture;
+use panko::types::{PixelFormat, TextureData};
+use panko::{run_event_loop, Application, Context, Error, Result};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[test]
+fn assets_load_in_the_background() {
+    let dir = directory(
+        "background",
+        &[("tiles.png", b"tiles.png"), ("ui.ttf", b"ui.ttf")],
+    );
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            context.vfs().mount_directory(&dir);
+            let tiles = context.load_texture_async("tiles.png");
+            let font = context.load_font_async("ui.ttf", 14);
+            let missing = context.load_texture_async("missing.png");
+            assert_eq!(context.loading_progress().total, 3);
+
+            context.assets().wait();
+            assert_eq!(
+                context.loading_progress(),
+                LoadProgress {
+                    total: 3,
+                    loaded: 2,
+                    failed: 1
+                }
+            );
+            assert_eq!(
+                context.assets().errors(),
+                &[(
+                    String::from("missing.png"),
+                    Error::AssetNotFound(String::from("missing.png"))
+                )]
+            );
+            assert!(matches!(missing.get(), Some(Err(Error::AssetNotFound(_)))));
+
+            // finished loads are shared like any other asset
+            let tiles = tiles.get().unwrap()?;
+            assert!(Handle::ptr_eq(&tiles, &context.assets().texture("tiles.png")?));
+            Ok((tiles, font.get().unwrap()?))
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+
+    // both were decoded by the loader, leaving only their creation to the backend
+    let commands = log.commands();
+    assert!(commands.iter().any(|c| matches!(
+        c,
+        Command::TextureCreateFromPixels {
+            data: TextureData {
+                width: 32,
+                height: 32,
+                ..
+            },
+            ..
+        }
+    )));
+    assert!(commands.iter().any(|c| matches!(
+        c,
+        Command::FontCreateDecoded {
+            len: 6,
+            scale: 14,
+            ..
+        }
+    )));
+    assert!(!commands.iter().any(|c| matches!(
+        c,
+        Command::TextureLoad { .. }
+            | Command::TextureLoadFromMemory { .. }
+            | Command::FontLoad { .. }
+            | Command::FontLoadFromMemory { .. }
+    )));
+}
+
+#[test]
+fn archive_entries_load_at_the_same_time() {
+    let dir = directory("archive", &[]);
+    // noise, so compressed entries stay large enough for reads to overlap
+    let mut seed = 1u32;
+    let files: Vec<(String, Vec<u8>)> = (0..16)
+        .map(|i| {
+            let width = 100 + i;
+            let pixels = (0..width * 50 * 4)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                    (seed >> 24) as u8
+                })
+                .collect();
+            let image = Image {
+                width,
+                height: 50,
+                pixels,
+            };
+            (format!("sprites/{}.png", i), image.encode_png().unwrap())
+        })
+        .collect();
+    let mut writer = ArchiveWriter::new();
+    for (path, bytes) in &files {
+        writer.add(path, bytes, true).unwrap();
+    }
+    let path = dir.join("assets.pak");
+    writer.write(std::fs::File::create(&path).unwrap()).unwrap();
+
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            context.vfs().mount(Archive::open(&path)?);
+            let pending: Vec<_> = files
+                .iter()
+                .map(|(path, _)| context.load_texture_async(path))
+                .collect();
+            context.assets().wait();
+            assert_eq!(context.assets().errors(), &[]);
+            pending
+                .iter()
+                .map(|pending| pending.get().unwrap())
+                .collect::<Result<Vec<_>>>()
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+
+    let mut widths: Vec<u32> = log
+        .commands()
+        .into_iter()
+        .filter_map(|c| match c {
+            Command::TextureCreateFromPixels { data, .. } => Some(data.width),
+            _ => None,
+        })
+        .collect();
+    widths.sort();
+    assert_eq!(widths, (100..116).collect::<Vec<_>>());
+}
+
+#[test]
+fn pngs_are_decoded_in_the_background() {
+    let dir = directory("decode", &[]);
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, 2, 1);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&[255; 8]).unwrap();
+    writer.finish().unwrap();
+    std::fs::write(dir.join("tiles.png"), png).unwrap();
+
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            context.vfs().mount_directory(&dir);
+            let tiles = context.load_texture_async("tiles.png");
+            context.assets().wait();
+            let tiles = tiles.get().unwrap()?;
+            assert_eq!((tiles.width(), tiles.height()), (2, 1));
+            Ok(tiles)
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+
+    assert!(log.commands().iter().any(|c| matches!(
+        c,
+        Command::TextureCreateFromPixels {
+            format: PixelFormat::Rgba8,
+            len: 8,
+            ..
+        }
+    )));
+}
+
+#[test]
+fn cached_assets_are_ready_immediately() {
+    let dir = directory("cached", &[("tiles.png", b"tiles.png")]);
+    draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            context.vfs().mount_directory(&dir);
+            let tiles = context.assets().texture("tiles.png")?;
+            let pending = context.load_texture_async("tiles.png");
+            assert!(pending.is_ready());
+            assert!(Handle::ptr_eq(&tiles, &pending.get().unwrap()?));
+            assert!(context.loading_progress().is_done());
+            Ok(tiles)
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+}
+
+/// Shows a loading screen until the texture is uploaded by the event loop.
+struct LoadingScreen {
+    texture: Pending<Texture>,
+    progress: Rc<RefCell<Vec<LoadProgress>>>,
+}
+
+impl Application for LoadingScreen {
+    fn update(&mut self, context: &mut Context, _delta_ms: u64) -> Result {
+        let mut progress = self.progress.borrow_mut();
+        progress.push(context.loading_progress());
+        if self.texture.is_ready() || progress.len() == MAX_FRAMES {
+            context.request_quit();
+        }
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, _context: &mut Context, _fixed_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn draw(&mut self, _canvas: &mut Canvas, _alpha_secs: f32) -> Result {
+        Ok(())
+    }
+}
+
+const MAX_FRAMES: usize = 100_000;
+
+#[test]
+fn event_loop_uploads_loaded_assets() {
+    let dir = directory("event-loop", &[("tiles.png", b"tiles.png")]);
+    let path = dir.join("tiles.png").to_string_lossy().into_owned();
+    let progress = Rc::new(RefCell::new(Vec::new()));
+
+    run_event_loop(RecordingBackend::new(), |context| {
+        Ok(LoadingScreen {
+            texture: context.load_texture_async(&path),
+            progress: Rc::clone(&progress),
+        })
+    })
+    .unwrap();
+
+    let progress = progress.borrow();
+    assert!(progress.len() < MAX_FRAMES);
+    assert_eq!(progress.last().unwrap().fraction(), 1.0);
+    assert_eq!(progress.last().unwrap().loaded, 1);
+}
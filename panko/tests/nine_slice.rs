@@ -0,0 +1,83 @@
+mod common;
+
+use common::{draw_frame, frame_commands};
+use panko::recording::{Command, RecordingBackend};
+use panko::types::*;
+
+/// The `(src, dest)` rects copied when drawing a 32x32 texture into `dest`.
+fn copies(dest: Rect, options: NineSliceOptions) -> Vec<(Rect, Rect)> {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| context.load_texture("panel.png"),
+        move |texture, canvas| canvas.draw_nine_slice(texture, Insets::uniform(8), dest, options),
+    )
+    .unwrap();
+
+    frame_commands(&log)
+        .into_iter()
+        .map(|command| match command {
+            Command::RenderCopyTexture { options, .. } => {
+                (options.src.unwrap(), options.dest.unwrap())
+            }
+            command => panic!("unexpected {:?}", command),
+        })
+        .collect()
+}
+
+#[test]
+fn corners_keep_their_size_while_the_rest_stretches() {
+    let copies = copies(Rect::new(10, 20, 64, 48), NineSliceOptions::default());
+
+    assert_eq!(
+        copies,
+        vec![
+            (Rect::new(0, 0, 8, 8), Rect::new(10, 20, 8, 8)),
+            (Rect::new(8, 0, 16, 8), Rect::new(18, 20, 48, 8)),
+            (Rect::new(24, 0, 8, 8), Rect::new(66, 20, 8, 8)),
+            (Rect::new(0, 8, 8, 16), Rect::new(10, 28, 8, 32)),
+            (Rect::new(8, 8, 16, 16), Rect::new(18, 28, 48, 32)),
+            (Rect::new(24, 8, 8, 16), Rect::new(66, 28, 8, 32)),
+            (Rect::new(0, 24, 8, 8), Rect::new(10, 60, 8, 8)),
+            (Rect::new(8, 24, 16, 8), Rect::new(18, 60, 48, 8)),
+            (Rect::new(24, 24, 8, 8), Rect::new(66, 60, 8, 8)),
+        ]
+    );
+}
+
+#[test]
+fn tiled_slices_repeat_and_cut_the_last_one_short() {
+    let options = NineSliceOptions {
+        edges: SliceFill::Tile,
+        center: SliceFill::Tile,
+        color_mod: None,
+    };
+    let copies = copies(Rect::new(0, 0, 60, 32), options);
+
+    // the top edge: 44 pixels filled by 16, 16 and 12
+    assert_eq!(
+        copies[1..4],
+        [
+            (Rect::new(8, 0, 16, 8), Rect::new(8, 0, 16, 8)),
+            (Rect::new(8, 0, 16, 8), Rect::new(24, 0, 16, 8)),
+            (Rect::new(8, 0, 12, 8), Rect::new(40, 0, 12, 8)),
+        ]
+    );
+    // corners, 3 per horizontal edge, 1 per vertical edge and 3 in the center
+    assert_eq!(copies.len(), 4 + 3 * 2 + 2 + 3);
+    assert!(copies.contains(&(Rect::new(8, 8, 12, 16), Rect::new(40, 8, 12, 16))));
+}
+
+#[test]
+fn corners_shrink_to_fit_small_rects() {
+    let copies = copies(Rect::new(0, 0, 10, 4), NineSliceOptions::default());
+
+    assert_eq!(
+        copies,
+        vec![
+            (Rect::new(0, 0, 8, 8), Rect::new(0, 0, 5, 2)),
+            (Rect::new(24, 0, 8, 8), Rect::new(5, 0, 5, 2)),
+            (Rect::new(0, 24, 8, 8), Rect::new(0, 2, 5, 2)),
+            (Rect::new(24, 24, 8, 8), Rect::new(5, 2, 5, 2)),
+        ]
+    );
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use common::draw_frame;
+use panko::canvas::Canvas;
+use panko::recording::{Command, RecordingBackend};
+use panko::types::*;
+use panko::{run_event_loop, Application, Context, Result};
+use std::cell::Cell;
+use std::rc::Rc;
+
+const SCREEN: Dimensions = Dimensions::new(320, 180);
+
+fn logical_screens(
+    backend: RecordingBackend,
+    policy: ResolutionPolicy,
+) -> Vec<Option<LogicalScreen>> {
+    let log = draw_frame(
+        backend,
+        |context| context.set_resolution(policy),
+        |_, _| Ok(()),
+    )
+    .unwrap();
+    log.commands()
+        .into_iter()
+        .filter_map(|command| match command {
+            Command::RenderSetLogicalScreen(screen) => Some(screen),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn letterbox_keeps_the_aspect_ratio() {
+    let screen = ResolutionPolicy::Letterbox(SCREEN)
+        .logical_screen(800, 600)
+        .unwrap();
+
+    assert_eq!(screen.size, SCREEN);
+    assert_eq!(screen.viewport, Rect::new(0, 75, 800, 450));
+}
+
+#[test]
+fn integer_scale_only_scales_by_whole_multiples() {
+    let policy = ResolutionPolicy::IntegerScale(SCREEN);
+
+    assert_eq!(
+        policy.logical_screen(1000, 600).unwrap().viewport,
+        Rect::new(20, 30, 960, 540)
+    );
+    // too small a window crops the screen around its center
+    assert_eq!(
+        policy.logical_screen(300, 180).unwrap().viewport,
+        Rect::new(-10, 0, 320, 180)
+    );
+}
+
+#[test]
+fn stretch_fills_the_window() {
+    let screen = ResolutionPolicy::Stretch(SCREEN)
+        .logical_screen(500, 500)
+        .unwrap();
+
+    assert_eq!(screen.viewport, Rect::new(0, 0, 500, 500));
+}
+
+#[test]
+fn window_points_map_into_the_logical_screen() {
+    let screen = ResolutionPolicy::IntegerScale(SCREEN)
+        .logical_screen(1000, 600)
+        .unwrap();
+
+    assert_eq!(screen.window_to_logical(20, 30), (0, 0));
+    assert_eq!(screen.window_to_logical(979, 569), (319, 179));
+    // the bars are outside of the screen
+    assert_eq!(screen.window_to_logical(19, 29), (-1, -1));
+}
+
+#[test]
+fn setting_a_policy_sets_the_logical_screen() {
+    let screens = logical_screens(RecordingBackend::new(), ResolutionPolicy::Letterbox(SCREEN));
+
+    assert_eq!(
+        screens,
+        vec![Some(LogicalScreen {
+            size: SCREEN,
+            viewport: Rect::new(0, 60, 640, 360),
+        })]
+    );
+}
+
+#[test]
+fn resizing_the_window_fits_the_screen_again() {
+    let mut backend = RecordingBackend::new();
+    backend.push_event(Event::WindowResized(Dimensions::new(1280, 800)));
+
+    let screens = logical_screens(backend, ResolutionPolicy::IntegerScale(SCREEN));
+
+    assert_eq!(screens.len(), 2);
+    assert_eq!(screens[1].unwrap().viewport, Rect::new(0, 40, 1280, 720));
+}
+
+/// Quits on the first update, keeping the mouse position it saw.
+struct MouseProbe {
+    position: Rc<Cell<(i32, i32)>>,
+}
+
+impl Application for MouseProbe {
+    fn update(&mut self, context: &mut Context, _delta_ms: u64) -> Result {
+        let mouse = context.input().mouse;
+        self.position.set((mouse.x(), mouse.y()));
+        context.request_quit();
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, _context: &mut Context, _fixed_ms: u64) -> Result {
+        Ok(())
+    }
+
+    fn draw(&mut self, _canvas: &mut Canvas, _alpha_secs: f32) -> Result {
+        Ok(())
+    }
+}
+
+#[test]
+fn the_mouse_is_reported_in_logical_pixels() {
+    let mut backend = RecordingBackend::new();
+    // the letterboxed screen is doubled into (0, 60, 640, 360)
+    backend.set_mouse_position(321, 241);
+    let position = Rc::new(Cell::new((0, 0)));
+
+    let probe = MouseProbe {
+        position: Rc::clone(&position),
+    };
+    run_event_loop(backend, |context| {
+        context.set_resolution(ResolutionPolicy::Letterbox(SCREEN))?;
+        assert_eq!(context.screen_size()?, SCREEN);
+        Ok(probe)
+    })
+    .unwrap();
+
+    assert_eq!(position.get(), (160, 90));
+}
@@ -0,0 +1,180 @@
+mod common;
+
+use common::{draw_frame, frame_commands};
+use panko::camera::Camera2D;
+use panko::canvas::Canvas;
+use panko::recording::{Command, RecordingBackend};
+use panko::types::*;
+use panko::Result;
+
+fn drawn(mut draw: impl FnMut(&mut Canvas) -> Result) -> Vec<Command> {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |_| Ok(()),
+        |_, canvas| draw(canvas),
+    )
+    .unwrap();
+    frame_commands(&log)
+}
+
+fn geometry(commands: &[Command]) -> Vec<Vertex> {
+    commands
+        .iter()
+        .flat_map(|command| match command {
+            Command::RenderGeometry(vertices) => vertices.clone(),
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+#[test]
+fn thin_lines_are_drawn_pixel_to_pixel() {
+    let commands =
+        drawn(|canvas| canvas.draw_line(Point::new(1, 2), Point::new(8, 5), 1.0, Color::RED));
+
+    assert_eq!(
+        commands,
+        vec![Command::RenderDrawLines {
+            points: vec![Point::new(1, 2), Point::new(8, 5)],
+            color: Color::RED,
+        }]
+    );
+}
+
+#[test]
+fn thick_lines_are_filled_around_their_end_pixels() {
+    let commands =
+        drawn(|canvas| canvas.draw_line(Point::new(0, 4), Point::new(10, 4), 4.0, Color::RED));
+
+    let vertices = geometry(&commands);
+    assert_eq!(vertices.len(), 6);
+    let xs = vertices.iter().map(|v| v.x);
+    let ys = vertices.iter().map(|v| v.y);
+    assert_eq!(xs.clone().fold(f32::MAX, f32::min), 0.0);
+    assert_eq!(xs.fold(f32::MIN, f32::max), 11.0);
+    assert_eq!(ys.clone().fold(f32::MAX, f32::min), 2.5);
+    assert_eq!(ys.fold(f32::MIN, f32::max), 6.5);
+    assert!(vertices.iter().all(|v| v.color == Color::RED));
+}
+
+#[test]
+fn concave_polygons_are_split_into_triangles() {
+    // an L made of six corners
+    let points = [
+        Point::new(0, 0),
+        Point::new(2, 0),
+        Point::new(2, 4),
+        Point::new(6, 4),
+        Point::new(6, 6),
+        Point::new(0, 6),
+    ];
+    let commands = drawn(|canvas| canvas.fill_polygon(&points, Color::GREEN));
+
+    let vertices = geometry(&commands);
+    assert_eq!(vertices.len(), 4 * 3);
+    let area: f32 = vertices
+        .chunks(3)
+        .map(|t| {
+            ((t[1].x - t[0].x) * (t[2].y - t[0].y) - (t[2].x - t[0].x) * (t[1].y - t[0].y)).abs()
+                / 2.0
+        })
+        .sum();
+    assert_eq!(area, 2.0 * 6.0 + 4.0 * 2.0);
+}
+
+#[test]
+fn circles_get_more_segments_as_they_grow() {
+    let segments = |radius: f32| {
+        let commands = drawn(|canvas| canvas.fill_circle(Point::new(0, 0), radius, Color::WHITE));
+        geometry(&commands).len() / 3
+    };
+
+    assert_eq!(segments(2.0), 8);
+    assert!(segments(100.0) > segments(10.0));
+}
+
+#[test]
+fn triangles_keep_their_vertex_colors_and_drop_leftovers() {
+    let vertices = [
+        Vertex::new(0.0, 0.0, Color::RED),
+        Vertex::new(4.0, 0.0, Color::GREEN),
+        Vertex::new(0.0, 4.0, Color::BLACK),
+        Vertex::new(9.0, 9.0, Color::WHITE),
+    ];
+    let commands = drawn(|canvas| canvas.draw_triangles(&vertices));
+
+    assert_eq!(
+        commands,
+        vec![Command::RenderGeometry(vertices[..3].to_vec())]
+    );
+}
+
+#[test]
+fn shapes_are_drawn_through_the_camera() {
+    let mut camera = Camera2D::new(Rect::new(0, 0, 100, 100));
+    camera.look_at(Point::new(0, 0));
+    camera.zoom = 2.0;
+
+    let commands = drawn(|canvas| {
+        canvas.with_camera(&camera, |canvas| {
+            canvas.fill_rect(Some(Rect::new(0, 0, 5, 5)), Color::RED)?;
+            canvas.draw_point(Point::new(1, 1), Color::RED)?;
+            canvas.draw_line(Point::new(0, 0), Point::new(10, 0), 2.0, Color::RED)
+        })
+    });
+
+    assert_eq!(
+        commands[0],
+        Command::RenderSetClipRect(Some(camera.viewport))
+    );
+    assert_eq!(
+        commands[1],
+        Command::RenderFillRect {
+            rect: Some(Rect::new(50, 50, 10, 10)),
+            color: Color::RED,
+        }
+    );
+    assert_eq!(
+        commands[2],
+        Command::RenderDrawPoints {
+            points: vec![Point::new(53, 53)],
+            color: Color::RED,
+        }
+    );
+    // 2 pixels thick in the world, 4 on the screen
+    let ys: Vec<f32> = geometry(&commands).iter().map(|v| v.y).collect();
+    assert_eq!(ys.iter().copied().fold(f32::MAX, f32::min), 49.0);
+    assert_eq!(ys.iter().copied().fold(f32::MIN, f32::max), 53.0);
+}
+
+#[test]
+fn rects_turn_with_a_turned_camera() {
+    let mut camera = Camera2D::new(Rect::new(0, 0, 100, 100));
+    camera.look_at(Point::new(0, 0));
+    camera.rotation = 90.0;
+
+    let commands = drawn(|canvas| {
+        canvas.with_camera(&camera, |canvas| {
+            canvas.fill_rect(Some(Rect::new(0, 0, 10, 5)), Color::RED)?;
+            canvas.draw_rect(Some(Rect::new(0, 0, 10, 5)), Color::RED)
+        })
+    });
+
+    // the wide rect stands upright on the screen, left of the viewport center
+    let corners: Vec<(i32, i32)> = geometry(&commands)
+        .iter()
+        .map(|v| (v.x.round() as i32, v.y.round() as i32))
+        .collect();
+    assert_eq!(corners.len(), 6);
+    for corner in [(50, 50), (50, 60), (45, 60), (45, 50)] {
+        assert!(corners.contains(&corner));
+    }
+    match &commands[2] {
+        Command::RenderDrawLines { points, .. } => {
+            assert_eq!(points.len(), 5);
+            assert_eq!(points[0], Point::new(49, 50));
+            assert_eq!(points[2], Point::new(45, 59));
+        }
+        command => panic!("unexpected {:?}", command),
+    }
+}
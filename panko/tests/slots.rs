@@ -0,0 +1,63 @@
+use panko::slots::Slots;
+use panko::types::ResourceId;
+use panko::{Error, ResourceKind};
+
+#[test]
+fn removed_slots_are_reused_with_a_new_generation() {
+    let mut slots = Slots::new(ResourceKind::Texture);
+    let a = slots.insert("a");
+    let b = slots.insert("b");
+    assert_eq!(slots.remove(a), Some("a"));
+
+    let c = slots.insert("c");
+    assert_eq!(c, ResourceId::new(a.index, a.generation + 1));
+    assert_eq!(slots.capacity(), 2);
+    assert_eq!(slots.len(), 2);
+    assert_eq!(slots.get(b), Ok(&"b"));
+    assert_eq!(slots.get(c), Ok(&"c"));
+}
+
+#[test]
+fn stale_ids_do_not_alias_new_values() {
+    let mut slots = Slots::new(ResourceKind::Font);
+    let a = slots.insert(1);
+    slots.remove(a);
+    assert_eq!(slots.get(a), Err(Error::StaleResource(ResourceKind::Font)));
+
+    slots.insert(2);
+    assert_eq!(slots.get(a), Err(Error::StaleResource(ResourceKind::Font)));
+    assert_eq!(slots.remove(a), None);
+    assert_eq!(slots.len(), 1);
+}
+
+#[test]
+fn unknown_ids_are_invalid() {
+    let mut slots = Slots::<()>::new(ResourceKind::Texture);
+    let a = slots.insert(());
+    assert_eq!(
+        slots.get(ResourceId::new(5, 0)),
+        Err(Error::InvalidResource(ResourceKind::Texture))
+    );
+    assert_eq!(
+        slots.get(ResourceId::new(a.index, a.generation + 1)),
+        Err(Error::InvalidResource(ResourceKind::Texture))
+    );
+}
+
+#[test]
+fn drain_frees_every_slot() {
+    let mut slots = Slots::new(ResourceKind::Texture);
+    let a = slots.insert(1);
+    let b = slots.insert(2);
+    slots.remove(a);
+    assert_eq!(slots.drain().collect::<Vec<_>>(), vec![2]);
+    assert!(slots.is_empty());
+    assert_eq!(
+        slots.get(b),
+        Err(Error::StaleResource(ResourceKind::Texture))
+    );
+
+    slots.insert(3);
+    slots.insert(4);
+    assert_eq!(slots.capacity(), 2);
+}
@@ -0,0 +1,475 @@
+mod common;
+
+use base64::Engine;
+use common::{draw_frame, frame_commands};
+use panko::camera::Camera2D;
+use panko::canvas::Canvas;
+use panko::recording::{Command, RecordingBackend};
+use panko::tilemap::*;
+use panko::types::*;
+use panko::{Context, Error, Result};
+use std::path::PathBuf;
+
+const FLIP_H: u32 = 0x8000_0000;
+const FLIP_V: u32 = 0x4000_0000;
+const FLIP_D: u32 = 0x2000_0000;
+
+/// Row by row, on a 4x3 map.
+const GROUND: [u32; 12] = [1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0];
+#[rustfmt::skip]
+const DECOR: [u32; 12] = [
+    9 | FLIP_H, 0, 0, 0,
+    0, 10 | FLIP_D, 0, 0,
+    0, 0, 0, 11 | FLIP_V,
+];
+
+const TSX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" name="props" tilewidth="16" tileheight="16" tilecount="4" columns="2">
+ <image source="props.png" width="32" height="32"/>
+ <tile id="1">
+  <properties>
+   <property name="solid" type="bool" value="true"/>
+  </properties>
+ </tile>
+</tileset>"#;
+
+fn tmx() -> String {
+    let csv = GROUND.map(|gid| gid.to_string()).join(",");
+    let bytes: Vec<u8> = DECOR.iter().flat_map(|gid| gid.to_le_bytes()).collect();
+    let zlib = miniz_oxide::deflate::compress_to_vec_zlib(&bytes, 6);
+    let base64 = base64::engine::general_purpose::STANDARD.encode(zlib);
+    format!(
+        r##"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" renderorder="right-down" width="4" height="3" tilewidth="16" tileheight="16" infinite="0">
+ <properties>
+  <property name="gravity" type="float" value="9.5"/>
+  <property name="music" type="file" value="theme.ogg"/>
+  <property name="tint" type="color" value="#80ff0000"/>
+ </properties>
+ <tileset firstgid="1" name="terrain" tilewidth="16" tileheight="16" spacing="2" margin="1" tilecount="8" columns="4">
+  <image source="terrain.png" width="72" height="38"/>
+ </tileset>
+ <tileset firstgid="9" source="props.tsx"/>
+ <layer id="1" name="ground" width="4" height="3">
+  <data encoding="csv">
+{csv}
+</data>
+ </layer>
+ <group id="2" name="details" offsetx="2">
+  <layer id="3" name="decor" width="4" height="3" opacity="0.5">
+   <data encoding="base64" compression="zlib">
+   {base64}
+   </data>
+  </layer>
+ </group>
+ <objectgroup id="4" name="spawns">
+  <object id="1" name="player" type="spawn" x="32" y="16">
+   <properties>
+    <property name="hp" type="int" value="3"/>
+    <property name="facing" value="left"/>
+   </properties>
+   <point/>
+  </object>
+  <object id="2" class="trigger" x="0" y="32" width="16" height="16">
+   <polygon points="0,0 16,0 16,16"/>
+  </object>
+ </objectgroup>
+</map>"##
+    )
+}
+
+fn json() -> String {
+    format!(
+        r##"{{
+  "type": "map", "version": "1.10", "orientation": "orthogonal", "renderorder": "right-down",
+  "width": 4, "height": 3, "tilewidth": 16, "tileheight": 16, "infinite": false,
+  "properties": [
+    {{ "name": "gravity", "type": "float", "value": 9.5 }},
+    {{ "name": "music", "type": "file", "value": "theme.ogg" }},
+    {{ "name": "tint", "type": "color", "value": "#80ff0000" }}
+  ],
+  "tilesets": [
+    {{ "firstgid": 1, "name": "terrain", "tilewidth": 16, "tileheight": 16, "spacing": 2, "margin": 1,
+      "tilecount": 8, "columns": 4, "image": "terrain.png", "imagewidth": 72, "imageheight": 38 }},
+    {{ "firstgid": 9, "source": "props.tsx" }}
+  ],
+  "layers": [
+    {{ "type": "tilelayer", "id": 1, "name": "ground", "width": 4, "height": 3, "x": 0, "y": 0,
+      "opacity": 1, "visible": true, "data": {ground:?} }},
+    {{ "type": "group", "id": 2, "name": "details", "offsetx": 2, "opacity": 1, "visible": true, "layers": [
+      {{ "type": "tilelayer", "id": 3, "name": "decor", "width": 4, "height": 3, "opacity": 0.5,
+        "visible": true, "data": {decor:?} }}
+    ] }},
+    {{ "type": "objectgroup", "id": 4, "name": "spawns", "opacity": 1, "visible": true, "objects": [
+      {{ "id": 1, "name": "player", "type": "spawn", "x": 32, "y": 16, "width": 0, "height": 0,
+        "rotation": 0, "visible": true, "point": true, "properties": [
+          {{ "name": "hp", "type": "int", "value": 3 }},
+          {{ "name": "facing", "type": "string", "value": "left" }}
+        ] }},
+      {{ "id": 2, "name": "", "class": "trigger", "x": 0, "y": 32, "width": 16, "height": 16,
+        "rotation": 0, "visible": true, "polygon": [{{ "x": 0, "y": 0 }}, {{ "x": 16, "y": 0 }}, {{ "x": 16, "y": 16 }}] }}
+    ] }}
+  ]
+}}"##,
+        ground = GROUND,
+        decor = DECOR,
+    )
+}
+
+fn directory(test: &str) -> PathBuf {
+    // the recording backend never decodes the tilesets
+    common::directory(
+        test,
+        &[
+            ("map.tmx", tmx().as_bytes()),
+            ("map.json", json().as_bytes()),
+            ("props.tsx", TSX.as_bytes()),
+            ("terrain.png", b""),
+            ("props.png", b""),
+        ],
+    )
+}
+
+fn load(context: &mut Context, test: &str, path: &str) -> Result<Tilemap> {
+    context.vfs().mount_directory(directory(test));
+    context.load_tilemap(path)
+}
+
+fn with_map(test: &str, path: &'static str, check: impl FnOnce(Tilemap) + 'static) {
+    let test = test.to_string();
+    draw_frame(
+        RecordingBackend::new(),
+        move |context| {
+            check(load(context, &test, path)?);
+            Ok(())
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+}
+
+/// The `(src, dest, flip_h, flip_v, angle)` of every tile drawn inside `viewport`.
+fn drawn(viewport: Rect) -> Vec<(Rect, Rect, bool, bool, f64)> {
+    copies(&format!("draw-{:?}", viewport), move |map, canvas| {
+        map.draw(canvas, viewport)
+    })
+}
+
+fn copies(
+    test: &str,
+    draw: impl FnMut(&mut Tilemap, &mut Canvas) -> Result,
+) -> Vec<(Rect, Rect, bool, bool, f64)> {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| load(context, test, "map.tmx"),
+        draw,
+    )
+    .unwrap();
+
+    frame_commands(&log)
+        .into_iter()
+        .filter_map(|command| match command {
+            Command::RenderCopyTexture { options, .. } => Some((
+                options.src.unwrap(),
+                options.dest.unwrap(),
+                options.flip_h,
+                options.flip_v,
+                options.angle,
+            )),
+            // drawing through a camera is clipped to its viewport
+            Command::RenderSetClipRect(_) => None,
+            command => panic!("unexpected {:?}", command),
+        })
+        .collect()
+}
+
+#[test]
+fn tmx_map_is_read() {
+    with_map("tmx", "map.tmx", |map| {
+        assert_eq!((map.width(), map.height()), (4, 3));
+        assert_eq!((map.pixel_width(), map.pixel_height()), (64, 48));
+        assert_eq!(map.properties().get_float("gravity"), Some(9.5));
+        assert_eq!(map.properties().get_str("music"), Some("theme.ogg"));
+        assert_eq!(
+            map.properties().get_color("tint"),
+            Some(Color::new(255, 0, 0, 128))
+        );
+
+        let terrain = &map.tilesets()[0];
+        // margin 1, then 16 pixel tiles 2 pixels apart
+        assert_eq!(terrain.tile_rect(5), Rect::new(19, 19, 16, 16));
+        assert_eq!(map.tileset(11).unwrap().1.name, "props");
+        assert!(map.tileset(13).is_none());
+        assert_eq!(
+            map.tile_properties(10).unwrap().get_bool("solid"),
+            Some(true)
+        );
+
+        let ground = map.tile_layer("ground").unwrap();
+        assert_eq!(ground.tile(1, 1).unwrap().gid, 6);
+        assert_eq!(ground.tile(0, 2), None);
+
+        let decor = map.tile_layer("decor").unwrap();
+        assert_eq!(decor.offset, Point::new(2, 0));
+        assert_eq!(decor.opacity, 0.5);
+        assert_eq!(
+            decor.tile(1, 1),
+            Some(Tile {
+                gid: 10,
+                flip_h: false,
+                flip_v: false,
+                flip_d: true,
+            })
+        );
+        assert!(decor.tile(0, 0).unwrap().flip_h);
+    });
+}
+
+#[test]
+fn object_layers_are_typed() {
+    with_map("objects", "map.tmx", |map| {
+        let spawns = map.object_layer("spawns").unwrap();
+        let player = spawns.objects_of_class("spawn").next().unwrap();
+        assert_eq!(
+            (player.name.as_str(), player.x, player.y),
+            ("player", 32.0, 16.0)
+        );
+        assert_eq!(player.shape, ObjectShape::Point);
+        assert_eq!(player.properties.get_int("hp"), Some(3));
+        assert_eq!(player.properties.get_str("facing"), Some("left"));
+
+        let trigger = spawns.objects_of_class("trigger").next().unwrap();
+        assert_eq!(
+            trigger.shape,
+            ObjectShape::Polygon(vec![(0.0, 0.0), (16.0, 0.0), (16.0, 16.0)])
+        );
+        assert_eq!(map.objects().count(), 2);
+    });
+}
+
+#[test]
+fn json_map_reads_like_tmx() {
+    draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            let json = load(context, "json", "map.json")?;
+            let tmx = context.load_tilemap("map.tmx")?;
+            assert_eq!(json.layers(), tmx.layers());
+            assert_eq!(json.tilesets(), tmx.tilesets());
+            assert_eq!(json.properties(), tmx.properties());
+            Ok(())
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+}
+
+#[test]
+fn only_tiles_inside_the_viewport_are_drawn() {
+    let tiles = drawn(Rect::new(16, 0, 32, 16));
+
+    // ground tiles 2 and 3, then the edge of the first decor tile, which is offset by 2
+    assert_eq!(
+        tiles,
+        vec![
+            (
+                Rect::new(19, 1, 16, 16),
+                Rect::new(0, 0, 16, 16),
+                false,
+                false,
+                0.0
+            ),
+            (
+                Rect::new(37, 1, 16, 16),
+                Rect::new(16, 0, 16, 16),
+                false,
+                false,
+                0.0
+            ),
+            (
+                Rect::new(0, 0, 16, 16),
+                Rect::new(-14, 0, 16, 16),
+                true,
+                false,
+                0.0
+            ),
+        ]
+    );
+}
+
+#[test]
+fn partly_visible_tiles_are_drawn_with_their_flips() {
+    let tiles = drawn(Rect::new(8, 8, 16, 16));
+
+    assert_eq!(
+        tiles,
+        vec![
+            (
+                Rect::new(1, 1, 16, 16),
+                Rect::new(-8, -8, 16, 16),
+                false,
+                false,
+                0.0
+            ),
+            (
+                Rect::new(19, 1, 16, 16),
+                Rect::new(8, -8, 16, 16),
+                false,
+                false,
+                0.0
+            ),
+            (
+                Rect::new(1, 19, 16, 16),
+                Rect::new(-8, 8, 16, 16),
+                false,
+                false,
+                0.0
+            ),
+            (
+                Rect::new(19, 19, 16, 16),
+                Rect::new(8, 8, 16, 16),
+                false,
+                false,
+                0.0
+            ),
+            // decor's first tile is flipped horizontally
+            (
+                Rect::new(0, 0, 16, 16),
+                Rect::new(-6, -8, 16, 16),
+                true,
+                false,
+                0.0
+            ),
+            // a diagonal flip is a quarter turn of the tile flipped vertically
+            (
+                Rect::new(16, 0, 16, 16),
+                Rect::new(10, 8, 16, 16),
+                false,
+                true,
+                90.0
+            ),
+        ]
+    );
+}
+
+#[test]
+fn camera_draws_the_tiles_it_sees_in_the_world() {
+    let mut camera = Camera2D::new(Rect::new(0, 0, 16, 16));
+    camera.look_at(Point::new(24, 24));
+
+    let tiles = copies("camera", move |map, canvas| {
+        map.draw_with_camera(canvas, &camera)
+    });
+
+    assert_eq!(
+        tiles,
+        vec![
+            (
+                Rect::new(19, 19, 16, 16),
+                Rect::new(0, 0, 16, 16),
+                false,
+                false,
+                0.0
+            ),
+            (
+                Rect::new(16, 0, 16, 16),
+                Rect::new(2, 0, 16, 16),
+                false,
+                true,
+                90.0
+            ),
+        ]
+    );
+}
+
+#[test]
+fn layer_opacity_fades_its_tiles() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| load(context, "opacity", "map.tmx"),
+        |map, canvas| map.draw(canvas, Rect::new(0, 0, 16, 16)),
+    )
+    .unwrap();
+
+    let color_mods: Vec<Option<Color>> = frame_commands(&log)
+        .into_iter()
+        .filter_map(|command| match command {
+            Command::RenderCopyTexture { options, .. } => Some(options.color_mod),
+            _ => None,
+        })
+        .collect();
+    // a ground tile, then the decor tile at half opacity
+    assert_eq!(
+        color_mods,
+        vec![None, Some(Color::new(255, 255, 255, 128))]
+    );
+}
+
+#[test]
+fn infinite_maps_are_rejected() {
+    let result = draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            let dir = directory("infinite");
+            std::fs::write(
+                dir.join("infinite.json"),
+                r#"{ "width": 4, "height": 3, "tilewidth": 16, "tileheight": 16, "infinite": true }"#,
+            )
+            .unwrap();
+            context.vfs().mount_directory(&dir);
+            context.load_tilemap("infinite.json")
+        },
+        |_, _| Ok(()),
+    );
+
+    assert_eq!(
+        result.err(),
+        Some(Error::Decode(String::from(
+            "Infinite maps are not supported."
+        )))
+    );
+}
+
+#[test]
+fn tilesets_are_found_in_sibling_directories() {
+    let tmx = tmx()
+        .replace(r#"source="terrain.png""#, r#"source="../images/terrain.png""#)
+        .replace(r#"source="props.tsx""#, r#"source="../tilesets/props.tsx""#);
+    let dir = common::directory(
+        "siblings",
+        &[
+            ("maps/level.tmx", tmx.as_bytes()),
+            ("tilesets/props.tsx", TSX.as_bytes()),
+            ("tilesets/props.png", b""),
+            ("images/terrain.png", b""),
+        ],
+    );
+
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            context.vfs().mount_directory(&dir);
+            let map = context.load_tilemap("maps/level.tmx")?;
+            assert_eq!(map.tilesets().len(), 2);
+            Ok(map)
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+
+    let loaded: Vec<PathBuf> = log
+        .commands()
+        .into_iter()
+        .filter_map(|command| match command {
+            Command::TextureLoad { path, .. } => Some(PathBuf::from(path)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        loaded,
+        vec![
+            dir.join("images/terrain.png"),
+            dir.join("tilesets/props.png")
+        ]
+    );
+}
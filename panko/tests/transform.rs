@@ -0,0 +1,157 @@
+mod common;
+
+use common::{draw_frame, frame_commands};
+use panko::assets::Handle;
+use panko::camera::Camera2D;
+use panko::canvas::Canvas;
+use panko::recording::{Command, RecordingBackend};
+use panko::texture::Texture;
+use panko::transform::Transform2D;
+use panko::types::*;
+use panko::Result;
+
+/// The options of every copy made by `draw`, and the rects of every fill.
+fn drawn(
+    draw: impl FnMut(&mut Handle<Texture>, &mut Canvas) -> Result,
+) -> (Vec<CopyTextureOptions>, Vec<Rect>) {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| context.load_texture("sprite.png"),
+        draw,
+    )
+    .unwrap();
+
+    let mut copies = Vec::new();
+    let mut rects = Vec::new();
+    for command in frame_commands(&log) {
+        match command {
+            Command::RenderCopyTexture { options, .. } => copies.push(options),
+            Command::RenderFillRect { rect, .. } => rects.push(rect.unwrap()),
+            _ => {}
+        }
+    }
+    (copies, rects)
+}
+
+fn copy_to(dest: Rect) -> CopyTextureOptions {
+    CopyTextureOptions {
+        dest: Some(dest),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn transforms_combine_with_the_ones_pushed_before() {
+    let moved = Transform2D::translation(10.0, 20.0);
+    let combined = moved.then(Transform2D::scaling(2.0));
+
+    assert_eq!(combined.apply(Point::new(3, 4)), Point::new(16, 28));
+    assert_eq!(
+        Transform2D::rotation(90.0).apply(Point::new(5, 0)),
+        Point::new(0, 5)
+    );
+}
+
+#[test]
+fn pushed_transforms_move_and_scale_drawing() {
+    let (copies, rects) = drawn(|texture, canvas| {
+        canvas.push_transform(Transform2D::translation(10.0, 20.0));
+        canvas.push_transform(Transform2D::scaling(2.0));
+        canvas.fill_rect(Some(Rect::new(1, 1, 3, 2)), Color::RED)?;
+        canvas.pop_transform();
+        canvas.copy_texture(texture, copy_to(Rect::new(0, 0, 4, 4)))?;
+        canvas.pop_transform();
+        canvas.fill_rect(Some(Rect::new(1, 1, 3, 2)), Color::RED)
+    });
+
+    assert_eq!(rects, vec![Rect::new(12, 22, 6, 4), Rect::new(1, 1, 3, 2)]);
+    assert_eq!(copies, vec![copy_to(Rect::new(10, 20, 4, 4))]);
+}
+
+#[test]
+fn rotation_turns_copies_around_the_local_origin() {
+    let (copies, _) = drawn(|texture, canvas| {
+        let transform = Transform2D {
+            x: 50.0,
+            y: 50.0,
+            scale: 1.0,
+            rotation: 90.0,
+        };
+        canvas.with_transform(transform, |canvas| {
+            canvas.copy_texture(texture, copy_to(Rect::new(10, 0, 10, 10)))
+        })
+    });
+
+    // the center (15, 5) turns a quarter clockwise to (-5, 15)
+    assert_eq!(copies[0].dest, Some(Rect::new(40, 60, 10, 10)));
+    assert_eq!(copies[0].center, Some(Point::new(5, 5)));
+    assert_eq!(copies[0].angle, 90.0);
+}
+
+#[test]
+fn transforms_are_placed_in_the_world_of_the_camera() {
+    let mut camera = Camera2D::new(Rect::new(0, 0, 100, 100));
+    camera.look_at(Point::new(0, 0));
+    camera.zoom = 2.0;
+
+    let (_, rects) = drawn(move |_, canvas| {
+        canvas.with_camera(&camera, |canvas| {
+            canvas.with_transform(Transform2D::translation(5.0, 0.0), |canvas| {
+                assert_eq!(canvas.transform(), Transform2D::translation(5.0, 0.0));
+                canvas.fill_rect(Some(Rect::new(0, 0, 2, 2)), Color::RED)
+            })
+        })
+    });
+
+    assert_eq!(rects, vec![Rect::new(60, 50, 4, 4)]);
+}
+
+#[test]
+fn targets_start_without_the_transforms_of_the_screen() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| context.create_target(32, 32),
+        |target, canvas| {
+            canvas.with_transform(Transform2D::translation(8.0, 8.0), |canvas| {
+                canvas.with_target(Some(target), |canvas| {
+                    assert_eq!(canvas.transform(), Transform2D::IDENTITY);
+                    canvas.with_transform(Transform2D::scaling(3.0), |canvas| {
+                        canvas.fill_rect(Some(Rect::new(1, 1, 1, 1)), Color::RED)
+                    })
+                })
+            })
+        },
+    )
+    .unwrap();
+
+    assert!(frame_commands(&log).contains(&Command::RenderFillRect {
+        rect: Some(Rect::new(3, 3, 3, 3)),
+        color: Color::RED,
+    }));
+}
+
+#[test]
+fn text_is_drawn_through_transforms() {
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| context.load_font("font.ttf", 12),
+        |font, canvas| {
+            let transform = Transform2D::translation(40.0, 10.0).then(Transform2D::scaling(2.0));
+            canvas.with_transform(transform, |canvas| {
+                canvas.draw_text(font, "ab", Point::new(0, 0), Color::WHITE)
+            })
+        },
+    )
+    .unwrap();
+
+    let dests: Vec<Rect> = frame_commands(&log)
+        .into_iter()
+        .filter_map(|command| match command {
+            Command::RenderCopyTexture { options, .. } => options.dest,
+            _ => None,
+        })
+        .collect();
+    assert_eq!(dests.len(), 2);
+    assert_eq!((dests[0].x, dests[0].y, dests[0].w), (40, 10, 16));
+    assert_eq!((dests[1].x, dests[1].y), (56, 10));
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use common::{directory, draw_frame};
+use panko::archive::{Archive, ArchiveWriter};
+use panko::recording::{Command, RecordingBackend};
+use panko::vfs::{Mount, Vfs};
+use panko::Error;
+
+fn archive(files: &[(&str, &[u8])], compress: bool) -> Vec<u8> {
+    let mut writer = ArchiveWriter::new();
+    for (path, bytes) in files {
+        writer.add(path, bytes, compress).unwrap();
+    }
+    writer.to_bytes()
+}
+
+#[test]
+fn archive_round_trips_stored_and_compressed_files() {
+    let big = vec![7; 4096];
+    let files: &[(&str, &[u8])] = &[("tiles.png", b"tiles"), ("fonts/ui.ttf", &big)];
+
+    for compress in [false, true] {
+        let bytes = archive(files, compress);
+        let archive = Archive::from_bytes(bytes.clone()).unwrap();
+        assert_eq!(archive.len(), 2);
+        assert_eq!(archive.read("tiles.png").unwrap().unwrap(), b"tiles");
+        assert_eq!(archive.read("fonts/ui.ttf").unwrap().unwrap(), big);
+        assert_eq!(archive.read("missing.png").unwrap(), None);
+        // only files that shrink are deflated
+        assert_eq!(bytes.len() < big.len(), compress);
+    }
+}
+
+#[test]
+fn archive_is_read_from_disk() {
+    let dir = directory("archive-file", &[]);
+    let path = dir.join("assets.pak");
+    std::fs::write(&path, archive(&[("a/b.png", b"b")], true)).unwrap();
+
+    let mut vfs = Vfs::new();
+    vfs.mount_archive(&path).unwrap();
+    assert_eq!(vfs.read("./a\\b.png").unwrap(), b"b");
+}
+
+#[test]
+fn archive_file_is_read_from_several_threads() {
+    let dir = directory("archive-threads", &[]);
+    let files: Vec<(String, Vec<u8>)> = (0..8u8)
+        .map(|i| (format!("{}.bin", i), vec![i; 3000 + i as usize]))
+        .collect();
+    let entries: Vec<(&str, &[u8])> = files
+        .iter()
+        .map(|(path, bytes)| (path.as_str(), bytes.as_slice()))
+        .collect();
+    let path = dir.join("assets.pak");
+    std::fs::write(&path, archive(&entries, false)).unwrap();
+    let archive = Archive::open(&path).unwrap();
+
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..50 {
+                    for (path, bytes) in &files {
+                        assert_eq!(&archive.read(path).unwrap().unwrap(), bytes);
+                    }
+                }
+            });
+        }
+    });
+}
+
+#[test]
+fn corrupt_archive_is_rejected() {
+    let mut bytes = archive(&[("tiles.png", b"tiles")], false);
+    assert!(matches!(
+        Archive::from_bytes(&b"nope"[..]),
+        Err(Error::Decode(_))
+    ));
+    bytes.truncate(20);
+    assert!(matches!(Archive::from_bytes(bytes), Err(Error::Decode(_))));
+}
+
+#[test]
+fn corrupt_index_is_rejected_before_reading_entries() {
+    // index fields of the only entry, after the 12 byte header and its 2 + 9 byte path
+    const COUNT: usize = 8;
+    const OFFSET: usize = 23;
+    const STORED_LEN: usize = 31;
+    let bytes = archive(&[("tiles.png", b"tiles")], false);
+    let corrupted = |at: usize, value: &[u8]| {
+        let mut bytes = bytes.clone();
+        bytes[at..at + value.len()].copy_from_slice(value);
+        bytes
+    };
+
+    for bytes in [
+        corrupted(COUNT, &u32::MAX.to_le_bytes()),
+        corrupted(OFFSET, &u64::MAX.to_le_bytes()),
+        corrupted(STORED_LEN, &1000u64.to_le_bytes()),
+        corrupted(STORED_LEN, &u64::MAX.to_le_bytes()),
+    ] {
+        assert!(matches!(Archive::from_bytes(bytes), Err(Error::Decode(_))));
+    }
+}
+
+#[test]
+fn truncated_archive_file_is_rejected() {
+    let bytes = archive(&[("tiles.png", b"tiles")], false);
+    let dir = directory(
+        "truncated-archive",
+        &[("assets.pak", &bytes[..bytes.len() - 1])],
+    );
+
+    assert!(matches!(
+        Archive::open(dir.join("assets.pak")),
+        Err(Error::Decode(_))
+    ));
+}
+
+#[test]
+fn later_mounts_shadow_earlier_ones() {
+    let dir = directory("shadow", &[("tiles.png", b"loose"), ("hero.png", b"hero")]);
+    let mut vfs = Vfs::new();
+    vfs.mount(Archive::from_bytes(archive(&[("tiles.png", b"packed")], true)).unwrap());
+    vfs.mount_directory(&dir);
+
+    assert_eq!(vfs.read("tiles.png").unwrap(), b"loose");
+    assert_eq!(vfs.read("hero.png").unwrap(), b"hero");
+    assert_eq!(
+        vfs.read("missing.png"),
+        Err(Error::AssetNotFound(String::from("missing.png")))
+    );
+    // mounts can not be escaped
+    assert!(vfs.read("../shadow/tiles.png").is_err());
+}
+
+#[test]
+fn parent_components_stay_inside_the_mount() {
+    let mut vfs = Vfs::new();
+    vfs.mount(Archive::from_bytes(archive(&[("tiles/a.png", b"a")], false)).unwrap());
+
+    assert_eq!(vfs.read("maps/../tiles/a.png").unwrap(), b"a");
+    assert_eq!(vfs.read("maps/./../tiles//a.png").unwrap(), b"a");
+    assert_eq!(
+        vfs.read("maps/../../tiles/a.png"),
+        Err(Error::AssetNotFound(String::from("maps/../../tiles/a.png")))
+    );
+}
+
+#[test]
+fn context_loads_through_the_vfs() {
+    let dir = directory("context", &[("hero.png", b"hero")]);
+    let packed = archive(&[("tiles.png", b"tiles")], false);
+
+    let log = draw_frame(
+        RecordingBackend::new(),
+        |context| {
+            context
+                .vfs()
+                .mount(Archive::from_bytes(packed.clone()).unwrap());
+            context.vfs().mount_directory(&dir);
+            let tiles = context.load_texture("tiles.png")?;
+            let hero = context.assets().texture("hero.png")?;
+            assert!(context.load_texture("missing.png").is_err());
+            Ok((tiles, hero))
+        },
+        |_, _| Ok(()),
+    )
+    .unwrap();
+
+    let commands = log.commands();
+    assert!(commands
+        .iter()
+        .any(|c| matches!(c, Command::TextureLoadFromMemory { len: 5, .. })));
+    let hero = dir.join("hero.png").to_string_lossy().into_owned();
+    assert!(commands
+        .iter()
+        .any(|c| matches!(c, Command::TextureLoad { path, .. } if *path == hero)));
+}